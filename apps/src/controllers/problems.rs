@@ -0,0 +1,57 @@
+use rocket::response::content::RawHtml;
+
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+
+pub use crate::responders::problem_detail::problem_type::BASE_URL;
+
+fn page(title: &str, body: &str) -> RawHtml<String> {
+    RawHtml(format!(
+        "<!DOCTYPE html>\
+         <html lang=\"en\">\
+         <head><meta charset=\"utf-8\"><title>{title}</title></head>\
+         <body><h1>{title}</h1>{body}</body>\
+         </html>"
+    ))
+}
+
+#[get("/")]
+pub fn problem_types() -> RawHtml<String> {
+    let items: String = ProblemType::ALL
+        .iter()
+        .map(|problem_type| {
+            format!(
+                "<li><a href=\"{}\">{}</a> ({})</li>",
+                problem_type.uri(),
+                problem_type.title(),
+                problem_type.status().code
+            )
+        })
+        .collect();
+
+    page("Problem types", &format!("<ul>{items}</ul>"))
+}
+
+#[get("/<slug>")]
+pub fn problem_type(slug: &str) -> Result<RawHtml<String>, ProblemDetail> {
+    let Some(problem_type) = ProblemType::from_slug(slug) else {
+        return Err(ProblemDetailBuilder::problem(ProblemType::ResourceNotFound)
+            .detail(format!("The problem type '{slug}' doesn't exist"))
+            .build());
+    };
+
+    let status = problem_type.status();
+
+    Ok(page(
+        problem_type.title(),
+        &format!(
+            "<p><code>type: {}</code></p>\
+             <p><code>status: {} {}</code></p>\
+             <p>{}</p>",
+            problem_type.uri(),
+            status.code,
+            status.reason_lossy(),
+            problem_type.description()
+        ),
+    ))
+}
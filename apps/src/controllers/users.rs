@@ -1,84 +1,292 @@
+mod api_keys;
+mod constraints;
 mod criteria;
 mod delete;
 mod find;
+mod login;
+mod password_reset;
+mod patch;
 mod register;
+mod restore;
+mod search;
+mod sessions;
+mod status;
+mod two_factor;
+mod unlock;
 mod update;
+mod verify_email;
 
+pub use api_keys::{user_api_key_create, user_api_key_revoke, user_api_keys};
 pub use criteria::user_criteria;
 pub use delete::user_delete;
 pub use find::{user_get, user_get_all};
+pub use login::user_login;
+pub use password_reset::{user_password_reset, user_password_reset_confirm};
+pub use patch::user_patch;
 pub use register::user_register;
+pub use restore::user_restore;
+pub use search::user_search;
+pub use sessions::{user_refresh, user_session_revoke, user_sessions, user_sessions_revoke};
+pub use status::{user_close, user_lock, user_reactivate, user_suspend};
+pub use two_factor::{
+    user_login_two_factor, user_two_factor_confirm, user_two_factor_enroll, user_two_factor_reset,
+};
+pub use unlock::user_unlock;
 pub use update::user_update;
+pub use verify_email::user_verify_email;
 
+use chrono::{DateTime, SecondsFormat, Utc};
+use contexts::users::domain::users::user_password::UserPasswordErrors;
+use contexts::users::domain::users::user_status::UserStatusErrors;
 use contexts::users::domain::users::{User, UserErrors};
 use garde::Validate;
-use rocket::http::Status;
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
 
+use crate::guard::JsonGuardErrors;
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
 
+/// Where the user routes are mounted.
+///
+/// Reading users needs a logged in user or an API key with the `read` scope. Changing a user needs
+/// that user, API keys with the `write` scope included, or an administrator logged in with an
+/// access token. The administration routes, changing the status, unlocking, restoring, resetting
+/// the two-factor authentication and getting the deleted users, need the administrator.
 pub const BASE_URL: &str = "/users";
 
-#[derive(Debug, Deserialize, Validate, Default)]
-#[garde(allow_unvalidated)]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        register::user_register,
+        find::user_get_all,
+        find::user_get,
+        update::user_update,
+        patch::user_patch,
+        delete::user_delete,
+        restore::user_restore,
+        status::user_suspend,
+        status::user_reactivate,
+        status::user_lock,
+        status::user_close,
+        unlock::user_unlock,
+        criteria::user_criteria,
+        search::user_search,
+        login::user_login,
+        sessions::user_refresh,
+        sessions::user_sessions,
+        sessions::user_session_revoke,
+        sessions::user_sessions_revoke,
+        api_keys::user_api_key_create,
+        api_keys::user_api_keys,
+        api_keys::user_api_key_revoke,
+        two_factor::user_login_two_factor,
+        two_factor::user_two_factor_enroll,
+        two_factor::user_two_factor_confirm,
+        two_factor::user_two_factor_reset,
+        password_reset::user_password_reset,
+        password_reset::user_password_reset_confirm,
+        verify_email::user_verify_email,
+    ),
+    components(schemas(
+        UserRequest,
+        UserResponse,
+        update::UserUpdateRequest,
+        criteria::FilterRequest,
+        criteria::OrderRequest,
+        search::UserSearchResponse,
+        search::UserSearchHighlights,
+        login::UserLoginRequest,
+        login::UserLoginResponse,
+        sessions::UserRefreshRequest,
+        sessions::UserTokensResponse,
+        sessions::UserSessionResponse,
+        api_keys::UserApiKeyRequest,
+        api_keys::UserApiKeyResponse,
+        api_keys::UserApiKeyCreatedResponse,
+        two_factor::UserTwoFactorEnrollmentResponse,
+        two_factor::UserTwoFactorConfirmRequest,
+        two_factor::UserRecoveryCodesResponse,
+        two_factor::UserTwoFactorChallengeResponse,
+        two_factor::UserTwoFactorLoginRequest,
+        password_reset::PasswordResetRequest,
+        password_reset::PasswordResetConfirmRequest,
+        status::UserSuspendRequest,
+    )),
+    tags((name = "users", description = "Users management"))
+)]
+pub struct UsersApiDoc;
+
+#[derive(Debug, Deserialize, Validate, Default, ToSchema)]
+#[garde(context(constraints::UserConstraints))]
 pub struct UserRequest<'a> {
-    // language=RegExp
-    #[garde(pattern(
-        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-7[0-9a-fA-F]{3}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
-    ))]
-    uuid: &'a str,
-    #[garde(length(chars, min = 8))]
+    // Checked by the user, an invalid id has its own problem type.
+    #[garde(skip)]
+    #[schema(schema_with = constraints::user_id_schema, required = false)]
+    #[serde(default)]
+    uuid: Option<&'a str>,
+    #[garde(custom(constraints::name))]
+    #[schema(schema_with = constraints::name_schema)]
     name: &'a str,
-    // language=RegExp
-    #[garde(length(chars, min = 8), pattern(r"\d.*[\W_]|[\W_].*\d"))]
+    #[garde(custom(constraints::password(self.name, self.email)))]
+    #[schema(schema_with = constraints::password_schema)]
     password: &'a str,
-    #[garde(email)]
+    #[garde(custom(constraints::email))]
+    #[schema(schema_with = constraints::email_schema)]
     email: &'a str,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
+    #[schema(format = Uuid)]
     uuid: String,
     name: String,
-    #[serde(rename = "password")]
-    password: String,
     email: String,
+    /// When the user verified its email, missing until it does.
+    #[schema(format = DateTime)]
+    email_verified_at: Option<String>,
+    #[schema(example = "active", pattern = "^(pending|active|suspended|locked|closed)$")]
+    status: String,
+    /// Why the user was suspended, missing unless it is.
+    status_reason: Option<String>,
+    #[schema(format = DateTime)]
+    created_at: String,
+    /// Id of the user that created this one, missing when it registered itself.
+    #[schema(format = Uuid)]
+    created_by: Option<String>,
+    #[schema(format = DateTime)]
+    updated_at: String,
+    /// Id of the user that last changed this one, missing when it wasn't a known user.
+    #[schema(format = Uuid)]
+    updated_by: Option<String>,
+    /// When the user last logged in, missing until it does.
+    #[schema(format = DateTime)]
+    last_login_at: Option<String>,
+    /// When the user was deleted, missing unless deleted users were asked for.
+    #[schema(format = DateTime)]
+    deleted_at: Option<String>,
 }
 
 impl From<User<'_>> for UserResponse {
     fn from(value: User) -> Self {
-        let (uuid, name, password, email) = value.into_inners();
+        let email_verified_at = value
+            .get_email_verification()
+            .verified_at()
+            .map(timestamp);
+        let status = value.get_status().get().to_owned();
+        let status_reason = value.get_status().get_reason().map(str::to_owned);
+        let audit = value.get_audit().clone();
+
+        let (uuid, name, _, email) = value.into_inners();
         UserResponse {
             uuid,
             name,
-            password,
             email,
+            email_verified_at,
+            status,
+            status_reason,
+            created_at: timestamp(audit.get_created_at()),
+            created_by: audit.get_created_by().map(str::to_owned),
+            updated_at: timestamp(audit.get_updated_at()),
+            updated_by: audit.get_updated_by().map(str::to_owned),
+            last_login_at: audit.get_last_login_at().map(timestamp),
+            deleted_at: audit.get_deleted_at().map(timestamp),
         }
     }
 }
 
+/// RFC 3339 timestamp in UTC, to the second as they're stored.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Fields of the requests the errors of the user are about, with their details.
+fn field_errors(error: &UserErrors) -> Vec<(&'static str, String)> {
+    let (field, details) = match error {
+        UserErrors::UserIDError { source } => ("uuid", source.to_string()),
+        UserErrors::UserNameError { source } => ("name", source.to_string()),
+        UserErrors::UserPasswordError { source } => ("password", source.to_string()),
+        UserErrors::UserEmailError { source } => ("email", source.to_string()),
+        UserErrors::UserStatusError { source } => match source {
+            UserStatusErrors::MissingReason => ("reason", source.to_string()),
+            _ => ("status", source.to_string()),
+        },
+        UserErrors::InvalidFields(errors) => {
+            return errors.iter().flat_map(field_errors).collect();
+        }
+    };
+
+    vec![(field, details)]
+}
+
+/// Adds the errors of the user to the report, keyed by the fields of the requests.
+fn append_user_errors(report: &mut garde::Report, error: &UserErrors) {
+    for (field, details) in field_errors(error) {
+        report.append(garde::Path::new(field), garde::Error::new(details));
+    }
+}
+
+fn validation_failed(detail: String, error: &UserErrors) -> ProblemDetail {
+    let mut report = garde::Report::new();
+    append_user_errors(&mut report, error);
+
+    ProblemDetailBuilder::problem(ProblemType::ValidationFailed)
+        .detail(detail)
+        .extensions(JsonGuardErrors::ValidationError(&report).get_problem_detail_extensions())
+        .build()
+}
+
 impl From<UserErrors> for ProblemDetail {
     fn from(value: UserErrors) -> Self {
-        match value {
+        if let UserErrors::UserPasswordError {
+            source: UserPasswordErrors::HashingError { .. },
+        }
+        | UserErrors::UserStatusError {
+            source: UserStatusErrors::UnknownStatus(_),
+        } = value
+        {
+            return ProblemDetail::internal_server_error(Some(value.into()));
+        }
+
+        match &value {
             UserErrors::UserIDError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
-                    .detail(source.to_string())
-                    .build()
-            }
-            UserErrors::UserNameError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
+                ProblemDetailBuilder::problem(ProblemType::InvalidUserId)
                     .detail(source.to_string())
                     .build()
             }
+            UserErrors::UserNameError { source } => validation_failed(source.to_string(), &value),
             UserErrors::UserPasswordError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
-                    .detail(source.to_string())
-                    .build()
+                validation_failed(source.to_string(), &value)
             }
-            UserErrors::UserEmailError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
+            UserErrors::UserEmailError { source } => validation_failed(source.to_string(), &value),
+            UserErrors::UserStatusError { source } => match source {
+                UserStatusErrors::MissingReason => validation_failed(source.to_string(), &value),
+                _ => ProblemDetailBuilder::problem(ProblemType::InvalidStatusTransition)
                     .detail(source.to_string())
-                    .build()
+                    .build(),
+            },
+            UserErrors::InvalidFields(errors) => {
+                // An invalid id keeps its problem type, listing the other fields with it.
+                let id_error = errors.iter().find_map(|error| match error {
+                    UserErrors::UserIDError { source } => Some(source),
+                    _ => None,
+                });
+
+                match id_error {
+                    Some(source) => {
+                        let mut report = garde::Report::new();
+                        append_user_errors(&mut report, &value);
+
+                        ProblemDetailBuilder::problem(ProblemType::InvalidUserId)
+                            .detail(source.to_string())
+                            .extensions(
+                                JsonGuardErrors::ValidationError(&report)
+                                    .get_problem_detail_extensions(),
+                            )
+                            .build()
+                    }
+                    None => validation_failed(value.to_string(), &value),
+                }
             }
         }
     }
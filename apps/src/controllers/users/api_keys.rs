@@ -0,0 +1,217 @@
+use crate::controllers::users::{timestamp, BASE_URL};
+use crate::guard::{Body, JsonGuardErrors, SessionAuthenticated};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::responders::Negotiated;
+use crate::Inject;
+use contexts::users::application::create_api_key::{UserCreateApiKey, UserCreateApiKeyErrors};
+use contexts::users::application::list_api_keys::{UserListApiKeys, UserListApiKeysErrors};
+use contexts::users::application::revoke_api_key::{UserRevokeApiKey, UserRevokeApiKeyErrors};
+use contexts::users::domain::users::api_key::{ApiKey, ApiKeyErrors};
+use garde::Validate;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Scheme the API key is sent with in the `Authorization` header, or as is in `X-API-Key`.
+const KEY_TYPE: &str = "ApiKey";
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserApiKeyRequest<'a> {
+    /// Name telling the key apart, like the client using it.
+    #[garde(skip)]
+    #[schema(example = "Nightly export")]
+    name: &'a str,
+    /// `read` lets the key send safe requests, `write` the other ones.
+    #[garde(skip)]
+    #[schema(example = json!(["read"]))]
+    scopes: Vec<&'a str>,
+    /// Days the key lasts, the default lifetime when missing.
+    #[garde(skip)]
+    #[serde(default)]
+    #[schema(example = 30)]
+    expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserApiKeyResponse {
+    #[schema(format = Uuid)]
+    id: String,
+    name: String,
+    /// Public start of the key, to recognize it.
+    #[schema(example = "3f9a1c0b7d2e")]
+    prefix: String,
+    #[schema(example = json!(["read", "write"]))]
+    scopes: Vec<String>,
+    #[schema(format = DateTime)]
+    created_at: String,
+    #[schema(format = DateTime)]
+    expires_at: String,
+    /// When the key was last used, missing until it is.
+    #[schema(format = DateTime)]
+    last_used_at: Option<String>,
+}
+
+impl From<ApiKey> for UserApiKeyResponse {
+    fn from(value: ApiKey) -> Self {
+        UserApiKeyResponse {
+            id: value.get_id().to_owned(),
+            name: value.get_name().to_owned(),
+            prefix: value.get_prefix().to_owned(),
+            scopes: value
+                .get_scopes()
+                .iter()
+                .map(|scope| scope.get().to_owned())
+                .collect(),
+            created_at: timestamp(value.get_created_at()),
+            expires_at: timestamp(value.get_expires_at()),
+            last_used_at: value.get_last_used_at().map(timestamp),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserApiKeyCreatedResponse {
+    #[serde(flatten)]
+    api_key: UserApiKeyResponse,
+    #[schema(example = "ApiKey")]
+    key_type: String,
+    /// Value of the key, only shown this once.
+    key: String,
+}
+
+impl From<ApiKeyErrors> for ProblemDetail {
+    fn from(value: ApiKeyErrors) -> Self {
+        let field = match value {
+            ApiKeyErrors::InvalidName => "name",
+            ApiKeyErrors::UnknownScope(_) | ApiKeyErrors::MissingScope => "scopes",
+            ApiKeyErrors::InvalidLifetime(_) => "expires_in_days",
+        };
+
+        let mut report = garde::Report::new();
+        report.append(
+            garde::Path::new(field),
+            garde::Error::new(value.to_string()),
+        );
+
+        ProblemDetailBuilder::problem(ProblemType::ValidationFailed)
+            .detail(value.to_string())
+            .extensions(JsonGuardErrors::ValidationError(&report).get_problem_detail_extensions())
+            .build()
+    }
+}
+
+impl From<UserCreateApiKeyErrors> for ProblemDetail {
+    fn from(value: UserCreateApiKeyErrors) -> Self {
+        match value {
+            UserCreateApiKeyErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserCreateApiKeyErrors::ApiKeyError { source } => ProblemDetail::from(source),
+        }
+    }
+}
+
+impl From<UserListApiKeysErrors> for ProblemDetail {
+    fn from(value: UserListApiKeysErrors) -> Self {
+        match value {
+            UserListApiKeysErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+        }
+    }
+}
+
+impl From<UserRevokeApiKeyErrors> for ProblemDetail {
+    fn from(value: UserRevokeApiKeyErrors) -> Self {
+        match value {
+            UserRevokeApiKeyErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserRevokeApiKeyErrors::NotFound => {
+                ProblemDetailBuilder::problem(ProblemType::ApiKeyNotFound)
+                    .detail(UserRevokeApiKeyErrors::NotFound.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    request_body = UserApiKeyRequest,
+    responses(
+        (status = 201, description = "API key created, its value is only shown this once", body = UserApiKeyCreatedResponse),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Authenticated with an API key instead of an access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid name, unknown or missing scopes, or lifetime longer than allowed", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/me/api-keys", data = "<request>")]
+pub fn user_api_key_create(
+    request: Body<UserApiKeyRequest>,
+    authenticated: SessionAuthenticated,
+    create_service: Inject<'_, dyn UserCreateApiKey>,
+) -> Result<Negotiated<UserApiKeyCreatedResponse>, ProblemDetail> {
+    let request = request.into_inner();
+
+    let (api_key, key) = create_service.create(
+        authenticated.get_user_id(),
+        request.name,
+        &request.scopes,
+        request.expires_in_days,
+    )?;
+
+    Ok(Negotiated::created(UserApiKeyCreatedResponse {
+        api_key: UserApiKeyResponse::from(api_key),
+        key_type: String::from(KEY_TYPE),
+        key,
+    }))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "API keys of the logged in user that weren't revoked nor expired, the latest created first", body = [UserApiKeyResponse]),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Authenticated with an API key instead of an access token", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[get("/me/api-keys")]
+pub fn user_api_keys(
+    authenticated: SessionAuthenticated,
+    list_service: Inject<'_, dyn UserListApiKeys>,
+) -> Result<Negotiated<Vec<UserApiKeyResponse>>, ProblemDetail> {
+    let api_keys = list_service.list(authenticated.get_user_id())?;
+
+    Ok(Negotiated::ok(
+        api_keys.into_iter().map(UserApiKeyResponse::from).collect(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("id" = String, Path, format = Uuid, description = "Identifier of the API key")),
+    responses(
+        (status = 204, description = "API key revoked, requests with it are refused from now on"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Authenticated with an API key instead of an access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "The user has no active API key with this identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[delete("/me/api-keys/<id>")]
+pub fn user_api_key_revoke(
+    id: &str,
+    authenticated: SessionAuthenticated,
+    revoke_service: Inject<'_, dyn UserRevokeApiKey>,
+) -> Result<Status, ProblemDetail> {
+    revoke_service.revoke(authenticated.get_user_id(), id)?;
+
+    Ok(Status::NoContent)
+}
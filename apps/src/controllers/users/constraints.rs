@@ -0,0 +1,153 @@
+//! HTTP validation rules and OpenAPI schemas of the user fields, both derived from the user
+//! value objects and the configured policies so the domain stays the single source of truth.
+//! Requests are validated once here, reporting every invalid field at once.
+
+use std::sync::Arc;
+
+use contexts::shared::domain::regex::{
+    LOWERCASE_PATTERN, NUMBER_PATTERN, SYMBOL_PATTERN, UPPERCASE_PATTERN,
+};
+use contexts::shared::infrastructure::dependency_container::AppContainer;
+use contexts::users::domain::users::email_policy::EmailPolicy;
+use contexts::users::domain::users::password_policy::{
+    PasswordPolicy, PasswordPolicyRules, MAX_PASSWORD_LENGTH, MIN_PASSWORD_LENGTH,
+};
+use contexts::users::domain::users::user_email::EMAIL_PATTERN;
+use contexts::users::domain::users::user_id::UUID_TIMESTAMP_RAND_VERSION;
+use contexts::users::domain::users::user_name::MIN_NAME_LENGTH;
+use contexts::users::domain::users::{User, UserErrors};
+use rocket::Request;
+use serde_json::json;
+use shaku::HasComponent;
+use utoipa::openapi::{KnownFormat, Object, ObjectBuilder, SchemaFormat, SchemaType};
+
+use crate::controllers::users::field_errors;
+use crate::guard::ValidationContext;
+
+/// Policies the user fields are checked against, resolved from the container.
+pub struct UserConstraints {
+    password_policy: Arc<dyn PasswordPolicy>,
+    email_policy: Arc<dyn EmailPolicy>,
+}
+
+impl UserConstraints {
+    pub fn new(container: &AppContainer) -> Self {
+        UserConstraints {
+            password_policy: container.resolve(),
+            email_policy: container.resolve(),
+        }
+    }
+}
+
+impl ValidationContext for UserConstraints {
+    fn from_request(req: &Request<'_>) -> Option<Self> {
+        req.rocket()
+            .state::<Box<AppContainer>>()
+            .map(|container| UserConstraints::new(container))
+    }
+}
+
+/// Error of the field validated, reported like the user reports it.
+fn garde_error(error: UserErrors) -> garde::Error {
+    let details: Vec<_> = field_errors(&error)
+        .into_iter()
+        .map(|(_, details)| details)
+        .collect();
+
+    garde::Error::new(details.join(", "))
+}
+
+pub fn name(value: &&str, _: &UserConstraints) -> garde::Result {
+    User::validate_name(value).map_err(garde_error)
+}
+
+/// Checks the password of the user with the given name and email against the password policy.
+pub fn password<'a>(
+    name: &'a str,
+    email: &'a str,
+) -> impl FnOnce(&&str, &UserConstraints) -> garde::Result + 'a {
+    move |value, constraints| {
+        User::validate_password(value, name, email, constraints.password_policy.as_ref())
+            .map_err(garde_error)
+    }
+}
+
+pub fn email(value: &&str, constraints: &UserConstraints) -> garde::Result {
+    User::validate_email(value, constraints.email_policy.as_ref()).map_err(garde_error)
+}
+
+pub fn user_id_schema() -> Object {
+    ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .format(Some(SchemaFormat::KnownFormat(KnownFormat::Uuid)))
+        .description(Some(format!(
+            "UUID v{UUID_TIMESTAMP_RAND_VERSION} of the user"
+        )))
+        .example(Some(json!("018f3d6e-2c5a-7ab3-ac03-68587d2c3d65")))
+        .build()
+}
+
+pub fn name_schema() -> Object {
+    ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .min_length(Some(MIN_NAME_LENGTH))
+        .example(Some(json!("John Doe Horrible")))
+        .build()
+}
+
+/// Schema of the passwords the default password policy accepts, deployments can configure
+/// another one.
+pub fn password_schema() -> Object {
+    let rules = PasswordPolicyRules::default();
+    let classes: Vec<_> = [
+        (
+            rules.require_lowercase,
+            "lowercase letters",
+            LOWERCASE_PATTERN,
+        ),
+        (
+            rules.require_uppercase,
+            "uppercase letters",
+            UPPERCASE_PATTERN,
+        ),
+        (rules.require_number, "numbers", NUMBER_PATTERN),
+        (rules.require_symbol, "symbols", SYMBOL_PATTERN),
+    ]
+    .into_iter()
+    .filter(|(required, ..)| *required)
+    .map(|(_, class, pattern)| (class, pattern))
+    .collect();
+
+    let names: Vec<_> = classes.iter().map(|(class, _)| *class).collect();
+    let lookaheads: String = classes
+        .iter()
+        .map(|(_, pattern)| format!("(?=.*{pattern})"))
+        .collect();
+
+    ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .format(Some(SchemaFormat::KnownFormat(KnownFormat::Password)))
+        .min_length(Some(MIN_PASSWORD_LENGTH))
+        .max_length(Some(MAX_PASSWORD_LENGTH))
+        .pattern((!classes.is_empty()).then(|| format!("^{lookaheads}")))
+        .description(Some(match names.as_slice() {
+            [] => "Checked against the password policy configured for the deployment".to_owned(),
+            names => format!(
+                "Checked against the password policy configured for the deployment, the default \
+                 one needs {}",
+                names.join(" and ")
+            ),
+        }))
+        .build()
+}
+
+pub fn email_schema() -> Object {
+    ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .pattern(Some(EMAIL_PATTERN))
+        .description(Some(
+            "Parsed following RFC 5321, the domain is lowercased and converted to punycode",
+        ))
+        .example(Some(json!("john.doe@example.com")))
+        .build()
+}
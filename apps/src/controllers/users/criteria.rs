@@ -1,33 +1,49 @@
-use crate::controllers::users::UserResponse;
+use crate::controllers::users::{UserResponse, BASE_URL};
+use crate::guard::{Admin, Authenticated, AuthenticationError};
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
-use crate::responders::JsonResponse;
+use crate::responders::Negotiated;
 use crate::Inject;
 use contexts::shared::domain::criteria::filter::{Filter, Operator, OperatorNotFound};
 use contexts::shared::domain::criteria::order::{Order, OrderType, OrderTypeNotFound};
 use contexts::shared::domain::criteria::Criteria;
 use contexts::users::application::criteria::{UserCriteria, UserCriteriaErrors};
-use rocket::http::Status;
 use std::num::ParseIntError;
 use thiserror::Error;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, FromForm)]
+#[derive(Debug, FromForm, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CriteriaRequest<'a> {
+    /// Filters as `filters[n].field`, `filters[n].operator` and `filters[n].value`
+    #[param(style = DeepObject, explode, value_type = Option<Vec<FilterRequest>>)]
     pub filters: Vec<FilterRequest<'a>>,
+    /// Order as `order.field` and `order.ty`
+    #[param(style = DeepObject, explode, value_type = Option<OrderRequest>)]
     pub order: Option<OrderRequest<'a>>,
+    /// Maximum number of users returned
+    #[param(value_type = Option<u32>)]
     pub limit: Option<&'a str>,
+    /// Number of users skipped
+    #[param(value_type = Option<u32>)]
     pub offset: Option<&'a str>,
+    /// Whether deleted users not purged yet are included, restricted to administrators
+    #[param(value_type = Option<bool>)]
+    pub include_deleted: bool,
 }
 
-#[derive(Debug, FromForm)]
+#[derive(Debug, FromForm, ToSchema)]
 pub struct FilterRequest<'a> {
     pub field: &'a str,
+    #[schema(example = "eq", pattern = "(?i)^(eq|gt|ge|lt|le|co|nc)$")]
     pub operator: &'a str,
     pub value: &'a str,
 }
 
-#[derive(Debug, FromForm)]
+#[derive(Debug, FromForm, ToSchema)]
 pub struct OrderRequest<'a> {
     pub field: &'a str,
+    #[schema(example = "asc", pattern = "(?i)^(asc|desc)$")]
     pub ty: &'a str,
 }
 
@@ -52,7 +68,7 @@ pub enum CriteriaError {
 
 impl From<CriteriaError> for ProblemDetail {
     fn from(value: CriteriaError) -> Self {
-        ProblemDetailBuilder::from(Status::UnprocessableEntity)
+        ProblemDetailBuilder::problem(ProblemType::InvalidCriteria)
             .detail(value.to_string())
             .build()
     }
@@ -130,16 +146,15 @@ impl From<UserCriteriaErrors> for ProblemDetail {
     fn from(value: UserCriteriaErrors) -> Self {
         match value {
             UserCriteriaErrors::InternalServerError { source } => {
-                let mut err = ProblemDetailBuilder::from(Status::InternalServerError);
-
-                if let Some(source) = source {
-                    err = err.detail(source.to_string());
-                }
-
-                err.build()
+                ProblemDetail::internal_server_error(source)
             }
             UserCriteriaErrors::FieldNotFound(_) => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
+                ProblemDetailBuilder::problem(ProblemType::InvalidCriteriaField)
+                    .detail(value.to_string())
+                    .build()
+            }
+            UserCriteriaErrors::InvalidTimestamp(_) => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidCriteria)
                     .detail(value.to_string())
                     .build()
             }
@@ -147,24 +162,34 @@ impl From<UserCriteriaErrors> for ProblemDetail {
     }
 }
 
-#[get("/?<filters>&<order>&<limit>&<offset>")]
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(CriteriaRequest),
+    responses(
+        (status = 200, description = "Users matching the criteria", body = [UserResponse]),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the read scope, or deleted users asked for by a user that isn't an administrator logged in with an access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid criteria", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[get("/?<criteria..>")]
 pub fn user_criteria(
-    filters: Vec<FilterRequest>,
-    order: Option<OrderRequest>,
-    limit: Option<&str>,
-    offset: Option<&str>,
+    criteria: CriteriaRequest,
+    _authenticated: Authenticated,
+    admin: Result<Admin, AuthenticationError>,
     criteria_service: Inject<'_, dyn UserCriteria>,
-) -> Result<JsonResponse<Vec<UserResponse>>, ProblemDetail> {
-    let criteria = CriteriaRequest {
-        order,
-        filters,
-        limit,
-        offset,
-    };
-
-    Ok(JsonResponse::ok(
+) -> Result<Negotiated<Vec<UserResponse>>, ProblemDetail> {
+    let include_deleted = criteria.include_deleted;
+
+    if let (true, Err(error)) = (include_deleted, &admin) {
+        return Err(ProblemDetail::from(error));
+    }
+
+    Ok(Negotiated::ok(
         criteria_service
-            .find_by(&Criteria::try_from(criteria)?)?
+            .find_by(&Criteria::try_from(criteria)?, include_deleted)?
             .into_iter()
             .map(UserResponse::from)
             .collect(),
@@ -1,4 +1,7 @@
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::controllers::users::BASE_URL;
+use crate::guard::{Authenticated, AuthenticationError};
 use crate::Inject;
 use contexts::users::application::delete::{UserDelete, UserDeleteErrors};
 use rocket::http::Status;
@@ -7,32 +10,47 @@ impl From<UserDeleteErrors> for ProblemDetail {
     fn from(value: UserDeleteErrors) -> Self {
         match value {
             UserDeleteErrors::InternalServerError { source } => {
-                let mut err = ProblemDetailBuilder::from(Status::InternalServerError);
-
-                if let Some(source) = source {
-                    err = err.detail(source.to_string());
-                }
-
-                err.build()
+                ProblemDetail::internal_server_error(source)
             }
             UserDeleteErrors::UserIDError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
+                ProblemDetailBuilder::problem(ProblemType::InvalidUserId)
                     .detail(source.to_string())
                     .build()
             }
+            UserDeleteErrors::NotFound => ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                .detail(UserDeleteErrors::NotFound.to_string())
+                .build(),
         }
     }
 }
 
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "User deleted, it can be restored until its restore period is over"),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The user isn't the logged in one and the logged in user isn't an administrator, or the API key lacks the write scope", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 500, description = "User couldn't be deleted", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
 #[delete("/<uuid>")]
 pub fn user_delete(
     uuid: String,
+    authenticated: Authenticated,
     delete_service: Inject<'_, dyn UserDelete>,
 ) -> Result<Status, ProblemDetail> {
-    let result = delete_service.delete_by(&uuid);
-
-    match result {
-        Ok(_) => Ok(Status::NoContent),
-        Err(_) => Err(ProblemDetail::from(Status::InternalServerError)),
+    if !authenticated.can_manage(&uuid) {
+        return Err(ProblemDetail::from(
+            &AuthenticationError::OwnerOrAdminRequired,
+        ));
     }
+
+    delete_service.delete_by(&uuid, Some(authenticated.get_user_id()))?;
+
+    Ok(Status::NoContent)
 }
@@ -1,24 +1,19 @@
-use rocket::http::Status;
 use contexts::users::application::find::{UserFind, UserFindErrors};
-use crate::controllers::users::UserResponse;
+use crate::controllers::users::{UserResponse, BASE_URL};
+use crate::guard::Authenticated;
 use crate::Inject;
-use crate::responders::JsonResponse;
+use crate::responders::Negotiated;
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
 
 impl From<UserFindErrors> for ProblemDetail {
     fn from(value: UserFindErrors) -> Self {
         match value {
             UserFindErrors::InternalServerError { source } => {
-                let mut err = ProblemDetailBuilder::from(Status::InternalServerError);
-
-                if let Some(source) = source {
-                    err = err.detail(source.to_string());
-                }
-
-                err.build()
+                ProblemDetail::internal_server_error(source)
             }
             UserFindErrors::UserIDError { source } => {
-                ProblemDetailBuilder::from(Status::UnprocessableEntity)
+                ProblemDetailBuilder::problem(ProblemType::InvalidUserId)
                     .detail(source.to_string())
                     .build()
             }
@@ -26,24 +21,51 @@ impl From<UserFindErrors> for ProblemDetail {
     }
 }
 
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Every registered user", body = [UserResponse]),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the read scope", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
 #[get("/")]
-pub fn user_get_all(user_service: Inject<'_, dyn UserFind>) -> JsonResponse<Vec<UserResponse>> {
-    JsonResponse::ok(
+pub fn user_get_all(
+    _authenticated: Authenticated,
+    user_service: Inject<'_, dyn UserFind>,
+) -> Negotiated<Vec<UserResponse>> {
+    Negotiated::ok(
         user_service
-            .get_all()
+            .get_all(false)
             .into_iter()
             .map(UserResponse::from)
             .collect(),
     )
 }
 
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 200, description = "User found", body = UserResponse),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the read scope", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
 #[get("/<uuid>")]
 pub fn user_get(
     uuid: String,
+    _authenticated: Authenticated,
     user_service: Inject<'_, dyn UserFind>,
-) -> Result<JsonResponse<UserResponse>, ProblemDetail> {
+) -> Result<Negotiated<UserResponse>, ProblemDetail> {
     match user_service.find_by(&uuid)? {
-        Some(user) => Ok(JsonResponse::ok(UserResponse::from(user))),
-        None => Err(ProblemDetail::from(Status::NotFound)),
+        Some(user) => Ok(Negotiated::ok(UserResponse::from(user))),
+        None => Err(ProblemDetailBuilder::problem(ProblemType::UserNotFound).build()),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,124 @@
+use crate::controllers::users::sessions::UserTokensResponse;
+use crate::controllers::users::two_factor::UserTwoFactorChallengeResponse;
+use crate::controllers::users::{UserResponse, BASE_URL};
+use crate::guard::{Body, UserAgent};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::responders::Negotiated;
+use crate::Inject;
+use contexts::users::application::authenticate::{UserAuthenticate, UserAuthenticateErrors};
+use contexts::users::application::start_session::UserStartSession;
+use contexts::users::application::two_factor_login::UserTwoFactorLogin;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserLoginRequest<'a> {
+    #[garde(skip)]
+    #[schema(example = "john.doe@example.com")]
+    email: &'a str,
+    #[garde(skip)]
+    #[schema(format = Password)]
+    password: &'a str,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserLoginResponse {
+    #[serde(flatten)]
+    tokens: UserTokensResponse,
+    user: UserResponse,
+}
+
+impl UserLoginResponse {
+    pub fn new(tokens: UserTokensResponse, user: UserResponse) -> Self {
+        UserLoginResponse { tokens, user }
+    }
+}
+
+/// Users with two-factor authentication enabled are only logged in once they send a code.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum UserLoginOutcome {
+    LoggedIn(Box<UserLoginResponse>),
+    TwoFactorRequired(UserTwoFactorChallengeResponse),
+}
+
+impl From<UserAuthenticateErrors> for ProblemDetail {
+    fn from(value: UserAuthenticateErrors) -> Self {
+        match value {
+            UserAuthenticateErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserAuthenticateErrors::InvalidCredentials => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidCredentials)
+                    .detail(UserAuthenticateErrors::InvalidCredentials.to_string())
+                    .build()
+            }
+            UserAuthenticateErrors::EmailNotVerified => {
+                ProblemDetailBuilder::problem(ProblemType::EmailNotVerified)
+                    .detail(UserAuthenticateErrors::EmailNotVerified.to_string())
+                    .build()
+            }
+            UserAuthenticateErrors::AccountUnavailable(_) => {
+                ProblemDetailBuilder::problem(ProblemType::AccountUnavailable)
+                    .detail(value.to_string())
+                    .build()
+            }
+            UserAuthenticateErrors::TooManyAttempts(retry_after) => {
+                ProblemDetailBuilder::problem(ProblemType::TooManyLoginAttempts)
+                    .detail(value.to_string())
+                    .retry_after(retry_after)
+                    .build()
+            }
+            UserAuthenticateErrors::LockedOut(retry_after) => {
+                ProblemDetailBuilder::problem(ProblemType::AccountLockedOut)
+                    .detail(value.to_string())
+                    .retry_after(retry_after)
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    request_body = UserLoginRequest,
+    responses(
+        (status = 200, description = "User authenticated, with the tokens of its new session", body = UserLoginResponse),
+        (status = 202, description = "Password right but the user has two-factor authentication enabled, the login is completed sending a code with the challenge token", body = UserTwoFactorChallengeResponse),
+        (status = 401, description = "Invalid email or password", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Email not verified, when required to log in, or account suspended, locked or closed", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 423, description = "Too many failed logins of the account, retry after the Retry-After header seconds", body = ProblemDetail, content_type = "application/problem+json",
+            headers(("Retry-After" = u64, description = "Seconds until the account can log in again"))),
+        (status = 429, description = "Too many failed logins from the client address, retry after the Retry-After header seconds", body = ProblemDetail, content_type = "application/problem+json",
+            headers(("Retry-After" = u64, description = "Seconds until the address can log in again"))),
+    )
+)]
+#[post("/login", data = "<credentials>")]
+pub fn user_login(
+    credentials: Body<UserLoginRequest>,
+    user_agent: UserAgent,
+    ip: Option<IpAddr>,
+    authenticate_service: Inject<'_, dyn UserAuthenticate>,
+    two_factor_service: Inject<'_, dyn UserTwoFactorLogin>,
+    session_service: Inject<'_, dyn UserStartSession>,
+) -> Result<Negotiated<UserLoginOutcome>, ProblemDetail> {
+    let credentials = credentials.into_inner();
+
+    let user = authenticate_service.authenticate(credentials.email, credentials.password, ip)?;
+
+    if let Some(challenge) = two_factor_service.challenge(user.get_id())? {
+        return Ok(Negotiated::accepted(UserLoginOutcome::TwoFactorRequired(
+            UserTwoFactorChallengeResponse::from(challenge),
+        )));
+    }
+
+    let tokens = session_service.start(user.get_id(), user_agent.0, ip)?;
+
+    Ok(Negotiated::ok(UserLoginOutcome::LoggedIn(Box::new(
+        UserLoginResponse::new(UserTokensResponse::from(tokens), UserResponse::from(user)),
+    ))))
+}
@@ -0,0 +1,85 @@
+use crate::controllers::users::{constraints, BASE_URL};
+use crate::fairings::PasswordResetQueue;
+use crate::guard::Body;
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::reset_password::{UserPasswordReset, UserPasswordResetErrors};
+use garde::Validate;
+use rocket::http::Status;
+use rocket::State;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PasswordResetRequest<'a> {
+    #[garde(skip)]
+    #[schema(example = "john.doe@example.com")]
+    email: &'a str,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PasswordResetConfirmRequest<'a> {
+    /// Token received by mail.
+    #[garde(skip)]
+    token: &'a str,
+    #[garde(skip)]
+    #[schema(schema_with = constraints::password_schema)]
+    password: &'a str,
+}
+
+impl From<UserPasswordResetErrors> for ProblemDetail {
+    fn from(value: UserPasswordResetErrors) -> Self {
+        match value {
+            UserPasswordResetErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserPasswordResetErrors::UserError { source } => ProblemDetail::from(source),
+            UserPasswordResetErrors::InvalidToken => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidResetToken)
+                    .detail(UserPasswordResetErrors::InvalidToken.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    request_body = PasswordResetRequest,
+    responses(
+        (status = 202, description = "A reset link is mailed when the email belongs to a user, which isn't disclosed"),
+    )
+)]
+#[post("/password-reset", data = "<request>")]
+pub fn user_password_reset(
+    request: Body<PasswordResetRequest>,
+    queue: &State<PasswordResetQueue>,
+) -> Status {
+    queue.push(request.into_inner().email);
+
+    Status::Accepted
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    request_body = PasswordResetConfirmRequest,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 400, description = "Invalid, expired or used token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid password", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/password-reset/confirm", data = "<request>")]
+pub fn user_password_reset_confirm(
+    request: Body<PasswordResetConfirmRequest>,
+    reset_service: Inject<'_, dyn UserPasswordReset>,
+) -> Result<Status, ProblemDetail> {
+    let request = request.into_inner();
+
+    reset_service.reset(request.token, request.password)?;
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,98 @@
+use crate::controllers::users::{UserResponse, BASE_URL};
+use crate::guard::{Authenticated, AuthenticationError, Patch};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::find::UserFind;
+use contexts::users::application::update::UserUpdate;
+use rocket::http::Status;
+use serde::Deserialize;
+
+/// The user document patches are applied to, the same one returned when getting a user.
+///
+/// The password is write-only, it's missing from the document and adding it sets a new password.
+#[derive(Debug, Deserialize)]
+struct UserDocument {
+    uuid: String,
+    name: String,
+    #[serde(default)]
+    password: Option<String>,
+    email: String,
+}
+
+fn invalid_patch(detail: impl Into<String>) -> ProblemDetail {
+    ProblemDetailBuilder::problem(ProblemType::InvalidPatch)
+        .detail(detail)
+        .build()
+}
+
+/// Returns the patched value when it differs from the current one.
+fn changed<'a>(current: &str, patched: &'a str) -> Option<&'a str> {
+    (current != patched).then_some(patched)
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    request_body(
+        content = Object,
+        content_type = "application/merge-patch+json",
+        description = "JSON Merge Patch of the user, a JSON Patch is accepted as application/json-patch+json. \
+                       Adding a password sets a new one, it's never part of the user",
+    ),
+    responses(
+        (status = 204, description = "User patched"),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The user isn't the logged in one and the logged in user isn't an administrator, or the API key lacks the write scope", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "Email already taken", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 415, description = "Unsupported patch format", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid patch or user data", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[patch("/<uuid>", data = "<patch>")]
+pub fn user_patch(
+    uuid: String,
+    authenticated: Authenticated,
+    patch: Patch,
+    find_service: Inject<'_, dyn UserFind>,
+    update_service: Inject<'_, dyn UserUpdate>,
+) -> Result<Status, ProblemDetail> {
+    if !authenticated.can_manage(&uuid) {
+        return Err(ProblemDetail::from(
+            &AuthenticationError::OwnerOrAdminRequired,
+        ));
+    }
+
+    let Some(user) = find_service.find_by(&uuid)? else {
+        return Err(ProblemDetailBuilder::problem(ProblemType::UserNotFound).build());
+    };
+
+    let current = UserResponse::from(user);
+
+    let mut document = serde_json::to_value(&current)
+        .map_err(|error| ProblemDetail::internal_server_error(Some(error.into())))?;
+
+    patch
+        .apply(&mut document)
+        .map_err(|error| invalid_patch(error.to_string()))?;
+
+    let patched: UserDocument =
+        serde_json::from_value(document).map_err(|error| invalid_patch(error.to_string()))?;
+
+    if patched.uuid != current.uuid {
+        return Err(invalid_patch("The user id can't be modified"));
+    }
+
+    update_service.update(
+        &uuid,
+        changed(&current.name, &patched.name),
+        patched.password.as_deref(),
+        changed(&current.email, &patched.email),
+        Some(authenticated.get_user_id()),
+    )?;
+
+    Ok(Status::NoContent)
+}
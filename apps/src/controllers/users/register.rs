@@ -1,44 +1,57 @@
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
 use contexts::users::application::register::{UserRegister, UserRegisterErrors};
 use contexts::users::application::register::UserRegisterErrors::AlreadyExists;
-use rocket::http::Status;
-use crate::controllers::users::UserRequest;
-use crate::guard::Json;
+use rocket::response::status::Created;
+use crate::controllers::users::{UserRequest, UserResponse, BASE_URL};
+use crate::guard::{Authenticated, Body};
+use crate::responders::Negotiated;
 use crate::Inject;
 
 impl From<UserRegisterErrors> for ProblemDetail {
     fn from(value: UserRegisterErrors) -> Self {
         match value {
-            AlreadyExists => ProblemDetailBuilder::from(Status::Conflict)
+            AlreadyExists => ProblemDetailBuilder::problem(ProblemType::UserAlreadyExists)
                 .detail("The uuid for the user trying to register, is already registered.")
                 .build(),
             UserRegisterErrors::InternalServerError { source } => {
-                let mut err = ProblemDetailBuilder::from(Status::InternalServerError);
-
-                if let Some(source) = source {
-                    err = err.detail(source.to_string());
-                }
-
-                err.build()
+                ProblemDetail::internal_server_error(source)
             }
             UserRegisterErrors::UserError { source } => ProblemDetail::from(source),
+            UserRegisterErrors::EmailAlreadyTaken => {
+                ProblemDetailBuilder::problem(ProblemType::EmailAlreadyTaken)
+                    .detail(UserRegisterErrors::EmailAlreadyTaken.to_string())
+                    .build()
+            }
         }
     }
 }
 
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security((), ("bearer" = []), ("api_key" = [])),
+    request_body = UserRequest,
+    responses(
+        (status = 201, description = "User registered", body = UserResponse, headers(("Location" = String, description = "URI of the user"))),
+        (status = 409, description = "User already registered or email already taken", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user data", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
 #[post("/register", data = "<new_user>")]
 pub fn user_register(
-    new_user: Json<UserRequest>,
+    new_user: Body<UserRequest>,
+    authenticated: Option<Authenticated>,
     register_service: Inject<'_, dyn UserRegister>,
-) -> Result<Status, ProblemDetail> {
+) -> Result<Created<Negotiated<UserResponse>>, ProblemDetail> {
     let user = new_user.into_inner();
 
-    register_service.register(
-        &user.uuid.to_string(),
-        &user.name,
-        &user.password,
-        &user.email,
-    )?;
+    // Users registering themselves have no actor, the ones registered by a logged in user are
+    // recorded as created by it.
+    let actor = authenticated.as_ref().map(Authenticated::get_user_id);
 
-    Ok(Status::Created)
-}
\ No newline at end of file
+    let user = register_service.register(user.uuid, user.name, user.password, user.email, actor)?;
+    let location = format!("{BASE_URL}/{}", user.get_id());
+
+    Ok(Created::new(location).body(Negotiated::created(UserResponse::from(user))))
+}
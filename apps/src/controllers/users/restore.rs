@@ -0,0 +1,55 @@
+use crate::controllers::users::BASE_URL;
+use crate::guard::Admin;
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::restore::{UserRestore, UserRestoreErrors};
+use rocket::http::Status;
+
+impl From<UserRestoreErrors> for ProblemDetail {
+    fn from(value: UserRestoreErrors) -> Self {
+        match value {
+            UserRestoreErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserRestoreErrors::UserIDError { source } => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidUserId)
+                    .detail(source.to_string())
+                    .build()
+            }
+            UserRestoreErrors::NotFound => ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                .detail(UserRestoreErrors::NotFound.to_string())
+                .build(),
+            UserRestoreErrors::RestorePeriodOver(_) => {
+                ProblemDetailBuilder::problem(ProblemType::RestorePeriodOver)
+                    .detail(value.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the deleted user")),
+    responses(
+        (status = 204, description = "User restored"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "No deleted user has this identifier", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 410, description = "Restore period over", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/restore")]
+pub fn user_restore(
+    uuid: &str,
+    admin: Admin,
+    restore_service: Inject<'_, dyn UserRestore>,
+) -> Result<Status, ProblemDetail> {
+    restore_service.restore(uuid, Some(admin.get_user_id()))?;
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,79 @@
+use crate::controllers::users::{UserResponse, BASE_URL};
+use crate::guard::Authenticated;
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::responders::Negotiated;
+use crate::Inject;
+use contexts::users::application::search::{UserSearch, UserSearchErrors};
+use contexts::users::domain::users::user_search_repository::UserSearchMatch;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserSearchHighlights {
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserSearchResponse {
+    #[serde(flatten)]
+    user: UserResponse,
+    score: f64,
+    highlights: UserSearchHighlights,
+}
+
+impl From<UserSearchMatch<'_>> for UserSearchResponse {
+    fn from(value: UserSearchMatch) -> Self {
+        UserSearchResponse {
+            user: UserResponse::from(value.user),
+            score: value.score,
+            highlights: UserSearchHighlights {
+                name: value.name_highlight,
+                email: value.email_highlight,
+            },
+        }
+    }
+}
+
+impl From<UserSearchErrors> for ProblemDetail {
+    fn from(value: UserSearchErrors) -> Self {
+        match value {
+            UserSearchErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserSearchErrors::EmptyQuery => {
+                ProblemDetailBuilder::problem(ProblemType::EmptySearchQuery)
+                    .detail(value.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("q" = String, Query, description = "Terms to search for in the name and email of the users")),
+    responses(
+        (status = 200, description = "Matching users, best matches first", body = [UserSearchResponse]),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the read scope", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Empty search query", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[get("/search?<q>")]
+pub fn user_search(
+    q: Option<&str>,
+    _authenticated: Authenticated,
+    search_service: Inject<'_, dyn UserSearch>,
+) -> Result<Negotiated<Vec<UserSearchResponse>>, ProblemDetail> {
+    Ok(Negotiated::ok(
+        search_service
+            .search(q.unwrap_or_default())?
+            .into_iter()
+            .map(UserSearchResponse::from)
+            .collect(),
+    ))
+}
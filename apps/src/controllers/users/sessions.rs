@@ -0,0 +1,223 @@
+use crate::controllers::users::{timestamp, BASE_URL};
+use crate::guard::{Authenticated, Body, UserAgent};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::responders::Negotiated;
+use crate::Inject;
+use contexts::users::application::list_sessions::{UserListSessions, UserListSessionsErrors};
+use contexts::users::application::refresh_session::{UserRefreshSession, UserRefreshSessionErrors};
+use contexts::users::application::revoke_sessions::{UserRevokeSessions, UserRevokeSessionsErrors};
+use contexts::users::application::start_session::{UserStartSessionErrors, UserTokens};
+use contexts::users::domain::users::user_session::UserSession;
+use garde::Validate;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+/// Scheme the access token is sent with, in the `Authorization` header.
+const TOKEN_TYPE: &str = "Bearer";
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserRefreshRequest<'a> {
+    #[garde(skip)]
+    refresh_token: &'a str,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserTokensResponse {
+    #[schema(format = Uuid)]
+    session_id: String,
+    #[schema(example = "Bearer")]
+    token_type: String,
+    access_token: String,
+    /// When the access token expires, the session has to be refreshed for a new one.
+    #[schema(format = DateTime)]
+    expires_at: String,
+    /// Single-use token exchanged for new tokens, only shown this once.
+    refresh_token: String,
+}
+
+impl From<UserTokens> for UserTokensResponse {
+    fn from(value: UserTokens) -> Self {
+        UserTokensResponse {
+            session_id: value.session_id,
+            token_type: String::from(TOKEN_TYPE),
+            access_token: value.access_token,
+            expires_at: timestamp(value.access_token_expires_at),
+            refresh_token: value.refresh_token,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserSessionResponse {
+    #[schema(format = Uuid)]
+    id: String,
+    /// User agent of the device that last used the session, missing when it sent none.
+    user_agent: Option<String>,
+    /// Address of the device that last used the session, missing when unknown.
+    ip: Option<String>,
+    #[schema(format = DateTime)]
+    created_at: String,
+    #[schema(format = DateTime)]
+    last_used_at: String,
+    /// When the session ends unless it's refreshed before.
+    #[schema(format = DateTime)]
+    expires_at: String,
+    /// Whether it's the session of the access token of the request, never for API keys.
+    current: bool,
+}
+
+impl UserSessionResponse {
+    fn new(session: UserSession, current_session_id: Option<&str>) -> Self {
+        UserSessionResponse {
+            current: current_session_id == Some(session.get_id()),
+            id: session.get_id().to_owned(),
+            user_agent: session.get_user_agent().map(str::to_owned),
+            ip: session.get_ip().map(str::to_owned),
+            created_at: timestamp(session.get_created_at()),
+            last_used_at: timestamp(session.get_last_used_at()),
+            expires_at: timestamp(session.get_expires_at()),
+        }
+    }
+}
+
+impl From<UserStartSessionErrors> for ProblemDetail {
+    fn from(value: UserStartSessionErrors) -> Self {
+        match value {
+            UserStartSessionErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+        }
+    }
+}
+
+impl From<UserRefreshSessionErrors> for ProblemDetail {
+    fn from(value: UserRefreshSessionErrors) -> Self {
+        match value {
+            UserRefreshSessionErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserRefreshSessionErrors::InvalidToken | UserRefreshSessionErrors::TokenReused => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidRefreshToken)
+                    .detail(value.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+impl From<UserListSessionsErrors> for ProblemDetail {
+    fn from(value: UserListSessionsErrors) -> Self {
+        match value {
+            UserListSessionsErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+        }
+    }
+}
+
+impl From<UserRevokeSessionsErrors> for ProblemDetail {
+    fn from(value: UserRevokeSessionsErrors) -> Self {
+        match value {
+            UserRevokeSessionsErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserRevokeSessionsErrors::NotFound => {
+                ProblemDetailBuilder::problem(ProblemType::SessionNotFound)
+                    .detail(UserRevokeSessionsErrors::NotFound.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    request_body = UserRefreshRequest,
+    responses(
+        (status = 200, description = "New tokens of the session, the refresh token sent is used up", body = UserTokensResponse),
+        (status = 401, description = "Invalid or already used refresh token, or session ended", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/refresh", data = "<request>")]
+pub fn user_refresh(
+    request: Body<UserRefreshRequest>,
+    user_agent: UserAgent,
+    ip: Option<IpAddr>,
+    refresh_service: Inject<'_, dyn UserRefreshSession>,
+) -> Result<Negotiated<UserTokensResponse>, ProblemDetail> {
+    let tokens = refresh_service.refresh(request.into_inner().refresh_token, user_agent.0, ip)?;
+
+    Ok(Negotiated::ok(UserTokensResponse::from(tokens)))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "Active sessions of the logged in user, the most recently used first", body = [UserSessionResponse]),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the scope of the request", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[get("/me/sessions")]
+pub fn user_sessions(
+    authenticated: Authenticated,
+    sessions_service: Inject<'_, dyn UserListSessions>,
+) -> Result<Negotiated<Vec<UserSessionResponse>>, ProblemDetail> {
+    let sessions = sessions_service.list(authenticated.get_user_id())?;
+
+    Ok(Negotiated::ok(
+        sessions
+            .into_iter()
+            .map(|session| UserSessionResponse::new(session, authenticated.get_session_id()))
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("id" = String, Path, format = Uuid, description = "Identifier of the session")),
+    responses(
+        (status = 204, description = "Logged out of the session"),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the scope of the request", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "The user has no active session with this identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[delete("/me/sessions/<id>")]
+pub fn user_session_revoke(
+    id: &str,
+    authenticated: Authenticated,
+    revoke_service: Inject<'_, dyn UserRevokeSessions>,
+) -> Result<Status, ProblemDetail> {
+    revoke_service.revoke(authenticated.get_user_id(), id)?;
+
+    Ok(Status::NoContent)
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "Logged out everywhere, the current session included"),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The API key lacks the scope of the request", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[delete("/me/sessions")]
+pub fn user_sessions_revoke(
+    authenticated: Authenticated,
+    revoke_service: Inject<'_, dyn UserRevokeSessions>,
+) -> Result<Status, ProblemDetail> {
+    revoke_service.revoke_all(authenticated.get_user_id())?;
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,148 @@
+use crate::controllers::users::BASE_URL;
+use crate::guard::{Admin, Body};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::change_status::{
+    UserChangeStatus, UserChangeStatusErrors, UserStatusChange,
+};
+use garde::Validate;
+use rocket::http::Status;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserSuspendRequest<'a> {
+    /// Why the user is suspended, kept until it's reactivated.
+    #[garde(skip)]
+    #[schema(example = "Sending spam")]
+    reason: &'a str,
+}
+
+impl From<UserChangeStatusErrors> for ProblemDetail {
+    fn from(value: UserChangeStatusErrors) -> Self {
+        match value {
+            UserChangeStatusErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserChangeStatusErrors::UserError { source } => ProblemDetail::from(source),
+            UserChangeStatusErrors::NotFound => {
+                ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                    .detail(UserChangeStatusErrors::NotFound.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    request_body = UserSuspendRequest,
+    responses(
+        (status = 204, description = "User suspended"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "The user can't be suspended from its status", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier or missing reason", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/suspend", data = "<request>")]
+pub fn user_suspend(
+    uuid: &str,
+    request: Body<UserSuspendRequest>,
+    admin: Admin,
+    status_service: Inject<'_, dyn UserChangeStatus>,
+) -> Result<Status, ProblemDetail> {
+    let reason = request.into_inner().reason;
+
+    status_service.change_status(
+        uuid,
+        UserStatusChange::Suspend { reason },
+        Some(admin.get_user_id()),
+    )?;
+
+    Ok(Status::NoContent)
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "Suspended or locked user reactivated"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "The user isn't suspended nor locked", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/reactivate")]
+pub fn user_reactivate(
+    uuid: &str,
+    admin: Admin,
+    status_service: Inject<'_, dyn UserChangeStatus>,
+) -> Result<Status, ProblemDetail> {
+    status_service.change_status(
+        uuid,
+        UserStatusChange::Reactivate,
+        Some(admin.get_user_id()),
+    )?;
+
+    Ok(Status::NoContent)
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "User locked"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "The user isn't pending nor active", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/lock")]
+pub fn user_lock(
+    uuid: &str,
+    admin: Admin,
+    status_service: Inject<'_, dyn UserChangeStatus>,
+) -> Result<Status, ProblemDetail> {
+    status_service.change_status(uuid, UserStatusChange::Lock, Some(admin.get_user_id()))?;
+
+    Ok(Status::NoContent)
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "User closed for good"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "The user is already closed", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/close")]
+pub fn user_close(
+    uuid: &str,
+    admin: Admin,
+    status_service: Inject<'_, dyn UserChangeStatus>,
+) -> Result<Status, ProblemDetail> {
+    status_service.change_status(uuid, UserStatusChange::Close, Some(admin.get_user_id()))?;
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,269 @@
+use crate::controllers::users::login::UserLoginResponse;
+use crate::controllers::users::sessions::UserTokensResponse;
+use crate::controllers::users::{timestamp, UserResponse, BASE_URL};
+use crate::guard::{Admin, Body, SessionAuthenticated, UserAgent};
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::responders::Negotiated;
+use crate::Inject;
+use contexts::users::application::enroll_two_factor::{
+    TwoFactorEnrollment, UserEnrollTwoFactor, UserEnrollTwoFactorErrors,
+};
+use contexts::users::application::reset_two_factor::{
+    UserResetTwoFactor, UserResetTwoFactorErrors,
+};
+use contexts::users::application::start_session::UserStartSession;
+use contexts::users::application::two_factor_login::{
+    TwoFactorChallengeIssued, UserTwoFactorLogin, UserTwoFactorLoginErrors,
+};
+use garde::Validate;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserTwoFactorEnrollmentResponse {
+    /// Base32 encoded secret, for authenticator apps that can't scan the provisioning URI.
+    #[schema(example = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")]
+    secret: String,
+    /// URI to show as a QR code, registering the secret in an authenticator app.
+    #[schema(
+        example = "otpauth://totp/Rust%20Hexagonal%20Architecture:john.doe%40example.com?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=Rust%20Hexagonal%20Architecture"
+    )]
+    provisioning_uri: String,
+}
+
+impl From<TwoFactorEnrollment> for UserTwoFactorEnrollmentResponse {
+    fn from(value: TwoFactorEnrollment) -> Self {
+        UserTwoFactorEnrollmentResponse {
+            secret: value.secret,
+            provisioning_uri: value.provisioning_uri,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserTwoFactorConfirmRequest<'a> {
+    /// Current code of the authenticator app.
+    #[garde(skip)]
+    #[schema(example = "123456")]
+    code: &'a str,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserRecoveryCodesResponse {
+    /// Codes logging in once each without the authenticator app, only shown this once.
+    #[schema(example = json!(["k3m9p-x2vqa", "7hdn4-bw8rt"]))]
+    recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserTwoFactorChallengeResponse {
+    /// Single-use token sent along with the code to complete the login.
+    challenge_token: String,
+    /// When the login has to start over unless completed before.
+    #[schema(format = DateTime)]
+    expires_at: String,
+}
+
+impl From<TwoFactorChallengeIssued> for UserTwoFactorChallengeResponse {
+    fn from(value: TwoFactorChallengeIssued) -> Self {
+        UserTwoFactorChallengeResponse {
+            challenge_token: value.token,
+            expires_at: timestamp(value.expires_at),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UserTwoFactorLoginRequest<'a> {
+    #[garde(skip)]
+    challenge_token: &'a str,
+    /// Current code of the authenticator app, or one of the recovery codes.
+    #[garde(skip)]
+    #[schema(example = "123456")]
+    code: &'a str,
+}
+
+impl From<UserEnrollTwoFactorErrors> for ProblemDetail {
+    fn from(value: UserEnrollTwoFactorErrors) -> Self {
+        match value {
+            UserEnrollTwoFactorErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserEnrollTwoFactorErrors::UserError { source } => ProblemDetail::from(source),
+            UserEnrollTwoFactorErrors::NotFound => {
+                ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                    .detail(UserEnrollTwoFactorErrors::NotFound.to_string())
+                    .build()
+            }
+            UserEnrollTwoFactorErrors::AlreadyEnabled => {
+                ProblemDetailBuilder::problem(ProblemType::TwoFactorAlreadyEnabled)
+                    .detail(UserEnrollTwoFactorErrors::AlreadyEnabled.to_string())
+                    .build()
+            }
+            UserEnrollTwoFactorErrors::NotPending => {
+                ProblemDetailBuilder::problem(ProblemType::NoPendingTwoFactor)
+                    .detail(UserEnrollTwoFactorErrors::NotPending.to_string())
+                    .build()
+            }
+            UserEnrollTwoFactorErrors::InvalidCode => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidTwoFactorCode)
+                    .detail(UserEnrollTwoFactorErrors::InvalidCode.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+impl From<UserTwoFactorLoginErrors> for ProblemDetail {
+    fn from(value: UserTwoFactorLoginErrors) -> Self {
+        match value {
+            UserTwoFactorLoginErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserTwoFactorLoginErrors::InvalidChallenge => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidTwoFactorChallenge)
+                    .detail(UserTwoFactorLoginErrors::InvalidChallenge.to_string())
+                    .build()
+            }
+            UserTwoFactorLoginErrors::InvalidCode => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidTwoFactorCode)
+                    .detail(UserTwoFactorLoginErrors::InvalidCode.to_string())
+                    .build()
+            }
+            UserTwoFactorLoginErrors::AccountUnavailable(_) => {
+                ProblemDetailBuilder::problem(ProblemType::AccountUnavailable)
+                    .detail(value.to_string())
+                    .build()
+            }
+            UserTwoFactorLoginErrors::LockedOut(retry_after) => {
+                ProblemDetailBuilder::problem(ProblemType::AccountLockedOut)
+                    .detail(value.to_string())
+                    .retry_after(retry_after)
+                    .build()
+            }
+        }
+    }
+}
+
+impl From<UserResetTwoFactorErrors> for ProblemDetail {
+    fn from(value: UserResetTwoFactorErrors) -> Self {
+        match value {
+            UserResetTwoFactorErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserResetTwoFactorErrors::UserError { source } => ProblemDetail::from(source),
+            UserResetTwoFactorErrors::NotFound => {
+                ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                    .detail(UserResetTwoFactorErrors::NotFound.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Secret to register in an authenticator app, two-factor authentication is enabled once confirmed with a code", body = UserTwoFactorEnrollmentResponse),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Authenticated with an API key instead of an access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "Two-factor authentication is already enabled", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/me/two-factor")]
+pub fn user_two_factor_enroll(
+    authenticated: SessionAuthenticated,
+    enroll_service: Inject<'_, dyn UserEnrollTwoFactor>,
+) -> Result<Negotiated<UserTwoFactorEnrollmentResponse>, ProblemDetail> {
+    let enrollment = enroll_service.enroll(authenticated.get_user_id())?;
+
+    Ok(Negotiated::ok(UserTwoFactorEnrollmentResponse::from(
+        enrollment,
+    )))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    request_body = UserTwoFactorConfirmRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled, with the recovery codes of the user", body = UserRecoveryCodesResponse),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Authenticated with an API key instead of an access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "No enrollment is waiting for confirmation", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid code", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/me/two-factor/confirm", data = "<request>")]
+pub fn user_two_factor_confirm(
+    request: Body<UserTwoFactorConfirmRequest>,
+    authenticated: SessionAuthenticated,
+    enroll_service: Inject<'_, dyn UserEnrollTwoFactor>,
+) -> Result<Negotiated<UserRecoveryCodesResponse>, ProblemDetail> {
+    let recovery_codes =
+        enroll_service.confirm(authenticated.get_user_id(), request.into_inner().code)?;
+
+    Ok(Negotiated::ok(UserRecoveryCodesResponse { recovery_codes }))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    request_body = UserTwoFactorLoginRequest,
+    responses(
+        (status = 200, description = "Login completed, with the tokens of the new session", body = UserLoginResponse),
+        (status = 401, description = "Unknown, expired or completed challenge, or too many wrong codes", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "Account suspended, locked or closed since the password was checked", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid or already used code", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 423, description = "Too many failed logins of the account, wrong codes included, retry after the Retry-After header seconds", body = ProblemDetail, content_type = "application/problem+json",
+            headers(("Retry-After" = u64, description = "Seconds until the account can log in again"))),
+    )
+)]
+#[post("/login/two-factor", data = "<request>")]
+pub fn user_login_two_factor(
+    request: Body<UserTwoFactorLoginRequest>,
+    user_agent: UserAgent,
+    ip: Option<IpAddr>,
+    two_factor_service: Inject<'_, dyn UserTwoFactorLogin>,
+    session_service: Inject<'_, dyn UserStartSession>,
+) -> Result<Negotiated<UserLoginResponse>, ProblemDetail> {
+    let request = request.into_inner();
+
+    let user = two_factor_service.verify(request.challenge_token, request.code)?;
+    let tokens = session_service.start(user.get_id(), user_agent.0, ip)?;
+
+    Ok(Negotiated::ok(UserLoginResponse::new(
+        UserTokensResponse::from(tokens),
+        UserResponse::from(user),
+    )))
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "Two-factor authentication of the user reset, it logs in with its password alone until it enrolls again"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[delete("/<uuid>/two-factor")]
+pub fn user_two_factor_reset(
+    uuid: &str,
+    admin: Admin,
+    reset_service: Inject<'_, dyn UserResetTwoFactor>,
+) -> Result<Status, ProblemDetail> {
+    reset_service.reset(uuid, Some(admin.get_user_id()))?;
+
+    Ok(Status::NoContent)
+}
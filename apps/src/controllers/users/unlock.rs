@@ -0,0 +1,45 @@
+use crate::controllers::users::BASE_URL;
+use crate::guard::Admin;
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::unlock::{UserUnlock, UserUnlockErrors};
+use rocket::http::Status;
+
+impl From<UserUnlockErrors> for ProblemDetail {
+    fn from(value: UserUnlockErrors) -> Self {
+        match value {
+            UserUnlockErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserUnlockErrors::UserError { source } => ProblemDetail::from(source),
+            UserUnlockErrors::NotFound => ProblemDetailBuilder::problem(ProblemType::UserNotFound)
+                .detail(UserUnlockErrors::NotFound.to_string())
+                .build(),
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    responses(
+        (status = 204, description = "Failed logins of the user forgotten and user reactivated when it was locked"),
+        (status = 401, description = "Missing, invalid or expired access token", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The logged in user isn't an administrator, or authenticated with an API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user identifier", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[post("/<uuid>/unlock")]
+pub fn user_unlock(
+    uuid: &str,
+    admin: Admin,
+    unlock_service: Inject<'_, dyn UserUnlock>,
+) -> Result<Status, ProblemDetail> {
+    unlock_service.unlock(uuid, Some(admin.get_user_id()))?;
+
+    Ok(Status::NoContent)
+}
@@ -1,52 +1,141 @@
-use crate::guard::Json;
+use crate::controllers::users::{constraints, BASE_URL};
+use crate::guard::{Authenticated, AuthenticationError, Body};
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
 use crate::Inject;
-use contexts::users::application::update::{UserUpdate, UserUpdateErrors};
+use contexts::users::application::replace::{UserReplace, UserReplaceErrors, UserReplaced};
+use contexts::users::application::update::UserUpdateErrors;
+use contexts::users::domain::users::user_id::UserID;
+use contexts::users::domain::users::UserErrors;
 use garde::Validate;
 use rocket::http::Status;
+use rocket::response::status::Created;
 use rocket::serde::Deserialize;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Validate, Default)]
+/// Full representation of a user replacing the one stored, the id in the path is the one used.
+#[derive(Debug, Deserialize, Validate, Default, ToSchema)]
+#[garde(context(constraints::UserConstraints))]
 pub struct UserUpdateRequest<'a> {
+    // Checked by the user, an invalid id has its own problem type.
     #[garde(skip)]
-    uuid: Uuid,
-    #[garde(skip)]
-    name: Option<&'a str>,
-    #[garde(skip)]
-    password: Option<&'a str>,
-    #[garde(skip)]
-    email: Option<&'a str>,
+    #[schema(schema_with = constraints::user_id_schema, required = false)]
+    #[serde(default)]
+    uuid: Option<&'a str>,
+    #[garde(custom(constraints::name))]
+    #[schema(schema_with = constraints::name_schema)]
+    name: &'a str,
+    #[garde(custom(constraints::password(self.name, self.email)))]
+    #[schema(schema_with = constraints::password_schema)]
+    password: &'a str,
+    #[garde(custom(constraints::email))]
+    #[schema(schema_with = constraints::email_schema)]
+    email: &'a str,
+}
+
+#[derive(Responder)]
+pub enum UserUpdateResponse {
+    Created(Created<()>),
+    Replaced(Status),
 }
 
 impl From<UserUpdateErrors> for ProblemDetail {
     fn from(value: UserUpdateErrors) -> Self {
         match value {
             UserUpdateErrors::InternalServerError { source } => {
-                let mut err = ProblemDetailBuilder::from(Status::InternalServerError);
-
-                if let Some(source) = source {
-                    err = err.detail(source.to_string());
-                }
-
-                err.build()
+                ProblemDetail::internal_server_error(source)
             }
-            UserUpdateErrors::NotFound => ProblemDetailBuilder::from(Status::NotFound)
+            UserUpdateErrors::NotFound => ProblemDetailBuilder::problem(ProblemType::UserNotFound)
                 .detail(UserUpdateErrors::NotFound.to_string())
                 .build(),
             UserUpdateErrors::UserError { source } => ProblemDetail::from(source),
+            UserUpdateErrors::EmailAlreadyTaken => {
+                ProblemDetailBuilder::problem(ProblemType::EmailAlreadyTaken)
+                    .detail(UserUpdateErrors::EmailAlreadyTaken.to_string())
+                    .build()
+            }
         }
     }
 }
 
-#[put("/", data = "<updated_user>")]
+impl From<UserReplaceErrors> for ProblemDetail {
+    fn from(value: UserReplaceErrors) -> Self {
+        match value {
+            UserReplaceErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserReplaceErrors::UserError { source } => ProblemDetail::from(source),
+            UserReplaceErrors::Conflict => {
+                ProblemDetailBuilder::problem(ProblemType::UserAlreadyExists)
+                    .detail(UserReplaceErrors::Conflict.to_string())
+                    .build()
+            }
+            UserReplaceErrors::EmailAlreadyTaken => {
+                ProblemDetailBuilder::problem(ProblemType::EmailAlreadyTaken)
+                    .detail(UserReplaceErrors::EmailAlreadyTaken.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    security(("bearer" = []), ("api_key" = [])),
+    params(("uuid" = String, Path, format = Uuid, description = "Identifier of the user")),
+    request_body = UserUpdateRequest,
+    responses(
+        (status = 201, description = "User registered", headers(("Location" = String, description = "URI of the user"))),
+        (status = 204, description = "User replaced"),
+        (status = 401, description = "Missing, invalid or expired access token or API key", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 403, description = "The user isn't the logged in one and the logged in user isn't an administrator, or the API key lacks the write scope", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 409, description = "User registered concurrently or email already taken", body = ProblemDetail, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid user data or ids not matching", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[put("/<uuid>", data = "<updated_user>")]
 pub fn user_update(
-    updated_user: Json<UserUpdateRequest>,
-    update_service: Inject<'_, dyn UserUpdate>,
-) -> Result<Status, ProblemDetail> {
+    uuid: String,
+    authenticated: Authenticated,
+    updated_user: Body<UserUpdateRequest>,
+    replace_service: Inject<'_, dyn UserReplace>,
+) -> Result<UserUpdateResponse, ProblemDetail> {
+    if !authenticated.can_manage(&uuid) {
+        return Err(ProblemDetail::from(
+            &AuthenticationError::OwnerOrAdminRequired,
+        ));
+    }
+
     let user = updated_user.into_inner();
 
-    update_service.update(&user.uuid.to_string(), user.name, user.password, user.email)?;
+    if let Some(body_id) = user.uuid {
+        UserID::validate(body_id).map_err(UserErrors::from)?;
+    }
 
-    Ok(Status::NoContent)
+    if let (Some(Ok(body_id)), Ok(path_id)) =
+        (user.uuid.map(Uuid::parse_str), Uuid::parse_str(&uuid))
+    {
+        if body_id != path_id {
+            return Err(ProblemDetailBuilder::problem(ProblemType::UserIdMismatch)
+                .detail(format!(
+                    "The id of the body '{body_id}' doesn't match the id of the path '{path_id}'"
+                ))
+                .build());
+        }
+    }
+
+    match replace_service.replace(
+        &uuid,
+        user.name,
+        user.password,
+        user.email,
+        Some(authenticated.get_user_id()),
+    )? {
+        UserReplaced::Created => Ok(UserUpdateResponse::Created(Created::new(format!(
+            "{BASE_URL}/{uuid}"
+        )))),
+        UserReplaced::Replaced => Ok(UserUpdateResponse::Replaced(Status::NoContent)),
+    }
 }
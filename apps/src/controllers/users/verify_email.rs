@@ -0,0 +1,41 @@
+use crate::controllers::users::BASE_URL;
+use crate::responders::problem_detail::problem_type::ProblemType;
+use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
+use crate::Inject;
+use contexts::users::application::verify_email::{UserEmailVerify, UserEmailVerifyErrors};
+use rocket::http::Status;
+
+impl From<UserEmailVerifyErrors> for ProblemDetail {
+    fn from(value: UserEmailVerifyErrors) -> Self {
+        match value {
+            UserEmailVerifyErrors::InternalServerError { source } => {
+                ProblemDetail::internal_server_error(source)
+            }
+            UserEmailVerifyErrors::UserError { source } => ProblemDetail::from(source),
+            UserEmailVerifyErrors::InvalidToken => {
+                ProblemDetailBuilder::problem(ProblemType::InvalidVerificationToken)
+                    .detail(UserEmailVerifyErrors::InvalidToken.to_string())
+                    .build()
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = BASE_URL,
+    tag = "users",
+    params(("token" = String, Query, description = "Token received by mail")),
+    responses(
+        (status = 204, description = "Email verified"),
+        (status = 400, description = "Invalid, expired or used token", body = ProblemDetail, content_type = "application/problem+json"),
+    )
+)]
+#[get("/verify-email?<token>")]
+pub fn user_verify_email(
+    token: &str,
+    verify_service: Inject<'_, dyn UserEmailVerify>,
+) -> Result<Status, ProblemDetail> {
+    verify_service.verify(token)?;
+
+    Ok(Status::NoContent)
+}
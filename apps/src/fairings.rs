@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use contexts::shared::infrastructure::dependency_container::AppContainer;
+use contexts::users::application::purge::UserPurge;
+use contexts::users::application::request_password_reset::UserPasswordResetRequest;
+use contexts::users::domain::users::email_policy::EmailPolicy;
+use contexts::users::infrastructure::sqlite::migrate_emails;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::tokio::sync::mpsc;
+use rocket::tokio::{select, task, time};
+use rocket::{Build, Data, Orbit, Request, Response, Rocket};
+use shaku::HasComponent;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Identifier generated for every request, sent back in the `X-Request-Id` header.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    fn new() -> Self {
+        RequestId(Uuid::now_v7().to_string())
+    }
+
+    /// Returns the id of the request, generating it the first time it's asked for.
+    pub fn of<'r>(req: &'r Request<'_>) -> &'r RequestId {
+        req.local_cache(RequestId::new)
+    }
+
+    pub fn get(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r RequestId {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId::of(req))
+    }
+}
+
+pub struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        RequestId::of(req);
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        res.set_header(Header::new(REQUEST_ID_HEADER, RequestId::of(req).get().to_owned()));
+    }
+}
+
+/// Normalizes the emails of the users saved before they were, aborting the launch when some
+/// users share an email once normalized.
+pub struct UserEmailMigrationFairing;
+
+#[rocket::async_trait]
+impl Fairing for UserEmailMigrationFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "User email migration",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let Some(container) = rocket.state::<Box<AppContainer>>() else {
+            log::error!("Emails can't be migrated, the container isn't managed");
+            return Err(rocket);
+        };
+
+        let email_policy: &dyn EmailPolicy = container.resolve_ref();
+
+        match migrate_emails(email_policy) {
+            Ok(()) => Ok(rocket),
+            Err(error) => {
+                log::error!("Emails couldn't be migrated: {}", error);
+                Err(rocket)
+            }
+        }
+    }
+}
+
+/// Purges the deleted users kept past their retention, right after liftoff and then every
+/// interval until the server shuts down.
+pub struct UserPurgeFairing {
+    pub interval: Duration,
+}
+
+#[rocket::async_trait]
+impl Fairing for UserPurgeFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "User purge",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let Some(container) = rocket.state::<Box<AppContainer>>() else {
+            log::error!("Deleted users won't be purged, the container isn't managed");
+            return;
+        };
+
+        let purge_service: Arc<dyn UserPurge> = container.resolve();
+        let mut interval = time::interval(self.interval);
+        let shutdown = rocket.shutdown();
+
+        rocket::tokio::spawn(async move {
+            let mut shutdown = pin!(shutdown);
+
+            loop {
+                select! {
+                    _ = interval.tick() => {}
+                    _ = &mut shutdown => break,
+                }
+
+                // SQLite blocks, so the purge doesn't run on the async workers.
+                let purge_service = purge_service.clone();
+                match task::spawn_blocking(move || purge_service.purge()).await {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(purged)) => log::info!("Purged {} deleted users", purged),
+                    Ok(Err(error)) => log::error!("Deleted users couldn't be purged: {:?}", error),
+                    Err(error) => log::error!("Deleted users couldn't be purged: {}", error),
+                }
+            }
+        });
+    }
+}
+
+/// Password resets waiting to be requested, after answering so neither the latency nor the status
+/// tell whether the email is registered.
+pub struct PasswordResetQueue {
+    sender: mpsc::Sender<String>,
+    email_policy: Arc<dyn EmailPolicy>,
+    cooldown: Duration,
+    /// When each email was last queued, forgotten once the cooldown is over.
+    queued_at: Mutex<HashMap<String, Instant>>,
+}
+
+impl PasswordResetQueue {
+    /// Queues a password reset of the email, unless it isn't one, it was queued during the
+    /// cooldown or the queue is full.
+    pub fn push(&self, email: &str) {
+        let Ok(email) = self.email_policy.parse(email) else {
+            return;
+        };
+        let email = email.get_normalized().to_owned();
+
+        let mut queued_at = self
+            .queued_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+
+        if queued_at
+            .get(&email)
+            .is_some_and(|at| now.duration_since(*at) < self.cooldown)
+        {
+            return;
+        }
+
+        match self.sender.try_send(email.clone()) {
+            Ok(()) => {
+                queued_at.retain(|_, at| now.duration_since(*at) < self.cooldown);
+                queued_at.insert(email, now);
+            }
+            Err(error) => log::warn!("Password reset dropped: {}", error),
+        }
+    }
+}
+
+/// Manages the [`PasswordResetQueue`] and requests the password resets queued one at a time, so
+/// a flood of requests can't pile up background work.
+pub struct PasswordResetQueueFairing {
+    pub size: usize,
+    pub cooldown: Duration,
+}
+
+#[rocket::async_trait]
+impl Fairing for PasswordResetQueueFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Password reset queue",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let Some(container) = rocket.state::<Box<AppContainer>>() else {
+            log::error!("Password resets can't be queued, the container isn't managed");
+            return Err(rocket);
+        };
+
+        let request_service: Arc<dyn UserPasswordResetRequest> = container.resolve();
+        let (sender, mut receiver) = mpsc::channel::<String>(self.size);
+
+        let queue = PasswordResetQueue {
+            sender,
+            email_policy: container.resolve(),
+            cooldown: self.cooldown,
+            queued_at: Mutex::new(HashMap::new()),
+        };
+
+        // Ends with the queue, once the server shuts down.
+        rocket::tokio::spawn(async move {
+            while let Some(email) = receiver.recv().await {
+                // SQLite and the mailer block, so requests don't run on the async workers.
+                let request_service = request_service.clone();
+                match task::spawn_blocking(move || request_service.request(&email)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(error)) => {
+                        log::error!("Password reset couldn't be requested: {:?}", error)
+                    }
+                    Err(error) => log::error!("Password reset couldn't be requested: {}", error),
+                }
+            }
+        });
+
+        Ok(rocket.manage(queue))
+    }
+}
@@ -0,0 +1,188 @@
+use rocket::http::{ContentType, MediaType};
+use rocket::Request;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Root element of the XML documents, bodies are wrapped so sequences have a single root.
+const XML_ROOT: &str = "response";
+const XML_PROBLEM_ROOT: &str = "problem";
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("{source}")]
+    Json {
+        #[from]
+        source: serde_json::Error,
+    },
+    #[error("{0}")]
+    Cbor(String),
+    #[error("{0}")]
+    MessagePack(String),
+    #[error("{0}")]
+    Xml(String),
+    /// The document is well-formed but doesn't have the expected shape.
+    #[error("{0}")]
+    Data(String),
+}
+
+impl FormatError {
+    /// Whether the document was well-formed but its content didn't match the expected data.
+    pub fn is_data(&self) -> bool {
+        match self {
+            FormatError::Json { source } => source.classify() == serde_json::error::Category::Data,
+            FormatError::Data(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Representations the API can read and write.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    Json,
+    Cbor,
+    MessagePack,
+    Xml,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Json, Format::Cbor, Format::MessagePack, Format::Xml];
+
+    pub fn content_type(&self) -> ContentType {
+        match self {
+            Format::Json => ContentType::JSON,
+            Format::Cbor => ContentType::new("application", "cbor"),
+            Format::MessagePack => ContentType::MsgPack,
+            Format::Xml => ContentType::new("application", "xml"),
+        }
+    }
+
+    /// Content type of an RFC 9457 problem, which only defines JSON and XML variants.
+    pub fn problem_content_type(&self) -> ContentType {
+        match self {
+            Format::Json => ContentType::new("application", "problem+json"),
+            Format::Xml => ContentType::new("application", "problem+xml"),
+            _ => self.content_type(),
+        }
+    }
+
+    /// Name of the data limit applied to bodies of this format.
+    pub fn limit_name(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Cbor => "cbor",
+            Format::MessagePack => "msgpack",
+            Format::Xml => "xml",
+        }
+    }
+
+    fn matches(&self, media_type: &MediaType) -> bool {
+        let (top, sub) = (media_type.top(), media_type.sub());
+
+        match self {
+            Format::Json => {
+                top == "application" && (sub == "json" || sub.as_str().ends_with("+json"))
+            }
+            Format::Cbor => {
+                top == "application" && (sub == "cbor" || sub.as_str().ends_with("+cbor"))
+            }
+            Format::MessagePack => {
+                top == "application"
+                    && (sub == "msgpack" || sub == "x-msgpack" || sub == "vnd.msgpack")
+            }
+            Format::Xml => {
+                (top == "application" || top == "text")
+                    && (sub == "xml" || sub.as_str().ends_with("+xml"))
+            }
+        }
+    }
+
+    pub fn from_media_type(media_type: &MediaType) -> Option<Format> {
+        Format::ALL
+            .into_iter()
+            .find(|format| format.matches(media_type))
+    }
+
+    /// Picks the format preferred by the `Accept` header of the request, JSON when any is
+    /// accepted and `None` when none of the accepted representations is supported.
+    pub fn negotiate(req: &Request<'_>) -> Option<Format> {
+        let Some(accept) = req.accept() else {
+            return Some(Format::Json);
+        };
+
+        let mut accepted: Vec<_> = accept
+            .iter()
+            .filter(|media_type| media_type.weight_or(1.0) > 0.0)
+            .collect();
+
+        accepted.sort_by(|a, b| b.weight_or(1.0).total_cmp(&a.weight_or(1.0)));
+
+        accepted.into_iter().find_map(|media_type| {
+            let media_type = media_type.media_type();
+
+            if media_type.top() == "*"
+                || (media_type.top() == "application" && media_type.sub() == "*")
+            {
+                Some(Format::Json)
+            } else {
+                Format::from_media_type(media_type)
+            }
+        })
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, FormatError> {
+        match self {
+            Format::Xml => self.serialize_with_root(XML_ROOT, &XmlBody { item: value }),
+            _ => self.serialize_with_root(XML_ROOT, value),
+        }
+    }
+
+    pub fn serialize_problem<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, FormatError> {
+        self.serialize_with_root(XML_PROBLEM_ROOT, value)
+    }
+
+    fn serialize_with_root<T: Serialize>(
+        &self,
+        root: &str,
+        value: &T,
+    ) -> Result<Vec<u8>, FormatError> {
+        match self {
+            Format::Json => Ok(serde_json::to_vec(value)?),
+            Format::Cbor => cbor4ii::serde::to_vec(Vec::new(), value)
+                .map_err(|error| FormatError::Cbor(error.to_string())),
+            Format::MessagePack => rmp_serde::to_vec_named(value)
+                .map_err(|error| FormatError::MessagePack(error.to_string())),
+            Format::Xml => quick_xml::se::to_string_with_root(root, value)
+                .map(String::into_bytes)
+                .map_err(|error| FormatError::Xml(error.to_string())),
+        }
+    }
+
+    pub fn deserialize<'a, T: Deserialize<'a>>(&self, bytes: &'a [u8]) -> Result<T, FormatError> {
+        match self {
+            Format::Json => Ok(serde_json::from_slice(bytes)?),
+            Format::Cbor => cbor4ii::serde::from_slice(bytes).map_err(|error| match error {
+                cbor4ii::serde::DecodeError::Custom(message) => FormatError::Data(message.into()),
+                error => FormatError::Cbor(error.to_string()),
+            }),
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|error| match error {
+                rmp_serde::decode::Error::Syntax(message) => FormatError::Data(message),
+                error => FormatError::MessagePack(error.to_string()),
+            }),
+            Format::Xml => {
+                let string = std::str::from_utf8(bytes)
+                    .map_err(|error| FormatError::Xml(error.to_string()))?;
+
+                quick_xml::de::from_str(string).map_err(|error| match error {
+                    quick_xml::DeError::Custom(message) => FormatError::Data(message),
+                    error => FormatError::Xml(error.to_string()),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct XmlBody<'a, T> {
+    item: &'a T,
+}
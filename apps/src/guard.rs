@@ -1,15 +1,32 @@
 use garde::Validate;
 use std::collections::HashMap;
 
+use contexts::users::application::authenticate_api_key::{
+    UserAuthenticateApiKey, UserAuthenticateApiKeyErrors,
+};
+use contexts::users::application::authenticate_token::{
+    UserAuthenticateToken, UserAuthenticateTokenErrors,
+};
+use contexts::users::domain::users::api_key::ApiKeyScope;
 use rocket::data::{FromData, Limits, Outcome};
-use rocket::http::Status;
-use rocket::request::local_cache;
+use rocket::http::{ContentType, Method, Status};
+use rocket::request::{self, local_cache, FromRequest};
 use rocket::{Data, Request};
 use serde::Deserialize;
-use serde_json::error::Category;
 use serde_json::json;
 use thiserror::Error;
 
+use crate::fairings::RequestId;
+use crate::formats::{Format, FormatError};
+use crate::Inject;
+
+/// Scheme of the `Authorization` header carrying an access token.
+const BEARER_SCHEME: &str = "Bearer ";
+/// Scheme of the `Authorization` header carrying an API key.
+const API_KEY_SCHEME: &str = "ApiKey ";
+/// Header carrying an API key, for clients that can't set the `Authorization` one.
+const API_KEY_HEADER: &str = "X-API-Key";
+
 #[derive(Debug, Error)]
 pub enum JsonValidationError {
     #[error("Validation failed")]
@@ -22,7 +39,7 @@ pub enum JsonValidationError {
     #[error("Parsing failed")]
     Parse {
         #[from]
-        source: serde_json::Error,
+        source: FormatError,
     },
     #[error("Payload too large")]
     TooLarge,
@@ -31,13 +48,18 @@ pub enum JsonValidationError {
         #[from]
         source: std::io::Error,
     },
+    #[error("Unsupported media type")]
+    UnsupportedMediaType,
+    #[error("Validation context unavailable")]
+    MissingContext,
 }
 
 pub enum JsonGuardErrors<'a> {
     ValidationError(&'a garde::Report),
     EmptyBody,
-    ParseError(&'a serde_json::Error),
+    ParseError(&'a FormatError),
     IO(&'a std::io::Error),
+    UnsupportedMediaType(Vec<ContentType>),
 }
 
 impl<'a> JsonGuardErrors<'a> {
@@ -68,73 +90,215 @@ impl<'a> JsonGuardErrors<'a> {
             JsonGuardErrors::IO(io) => {
                 extensions.insert("io_error".to_string(), json!(io.kind().to_string()));
             }
+            JsonGuardErrors::UnsupportedMediaType(supported) => {
+                let supported: Vec<_> = supported.iter().map(ToString::to_string).collect();
+                extensions.insert("supported_media_types".to_string(), json!(supported));
+            }
         }
 
         extensions
     }
 }
 
+/// Context the body guards validate a body with, built from the request.
+pub trait ValidationContext: Sized {
+    fn from_request(req: &Request<'_>) -> Option<Self>;
+}
+
+impl ValidationContext for () {
+    fn from_request(_: &Request<'_>) -> Option<Self> {
+        Some(())
+    }
+}
+
+/// Body guard accepting only JSON, regardless of the `Content-Type` of the request.
 #[derive(Debug)]
 pub struct Json<T>(pub T);
 
 impl<T> Json<T> {
+    #[allow(dead_code)]
     pub fn into_inner(self) -> T {
         self.0
     }
 }
 
 #[rocket::async_trait]
-impl<'r, T: Deserialize<'r> + Validate<Context = impl Default>> FromData<'r> for Json<T> {
+impl<'r, T: Deserialize<'r> + Validate<Context = impl ValidationContext>> FromData<'r> for Json<T> {
     type Error = JsonValidationError;
 
     async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
-        let limit = req.limits().get("json").unwrap_or(Limits::JSON);
+        from_data(req, data, Format::Json).await.map(Json)
+    }
+}
 
-        let string = match data.open(limit).into_string().await {
-            Err(error) => {
-                req.local_cache(|| {
-                    Some(JsonGuardErrors::IO(&error).get_problem_detail_extensions())
-                });
-                return Outcome::Error((
-                    Status::InternalServerError,
-                    JsonValidationError::IO { source: error },
-                ));
-            }
-            Ok(cap_string) if cap_string.is_complete() => cap_string.into_inner(),
-            Ok(_) => {
-                return Outcome::Error((Status::PayloadTooLarge, JsonValidationError::TooLarge));
-            }
-        };
+/// Body guard reading any of the supported formats, picked through the `Content-Type` of the
+/// request and defaulting to JSON when it's missing.
+#[derive(Debug)]
+pub struct Body<T>(pub T);
 
-        let string = local_cache!(req, string);
+impl<T> Body<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
 
-        if string.is_empty() {
-            req.local_cache(|| Some(JsonGuardErrors::EmptyBody.get_problem_detail_extensions()));
-            return Outcome::Error((Status::BadRequest, JsonValidationError::EmptyBody));
-        }
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r> + Validate<Context = impl ValidationContext>> FromData<'r> for Body<T> {
+    type Error = JsonValidationError;
 
-        match serde_json::from_str::<T>(string) {
-            Err(error) => match error.classify() {
-                Category::Data => {
-                    req.local_cache(|| {
-                        Some(JsonGuardErrors::ParseError(&error).get_problem_detail_extensions())
-                    });
-                    Outcome::Error((
-                        Status::UnprocessableEntity,
-                        JsonValidationError::Parse { source: error },
-                    ))
-                }
-                _ => {
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let format = match req.content_type() {
+            None => Format::Json,
+            Some(content_type) => match Format::from_media_type(content_type.media_type()) {
+                Some(format) => format,
+                None => {
+                    let supported = Format::ALL.iter().map(Format::content_type).collect();
                     req.local_cache(|| {
-                        Some(JsonGuardErrors::ParseError(&error).get_problem_detail_extensions())
+                        Some(
+                            JsonGuardErrors::UnsupportedMediaType(supported)
+                                .get_problem_detail_extensions(),
+                        )
                     });
-                    Outcome::Error((
-                        Status::BadRequest,
-                        JsonValidationError::Parse { source: error },
-                    ))
+                    return Outcome::Error((
+                        Status::UnsupportedMediaType,
+                        JsonValidationError::UnsupportedMediaType,
+                    ));
                 }
             },
-            Ok(t) => match t.validate() {
+        };
+
+        from_data(req, data, format).await.map(Body)
+    }
+}
+
+/// Body guard for partial updates, reading a JSON Merge Patch (RFC 7396) or a JSON Patch
+/// (RFC 6902) depending on the `Content-Type` of the request.
+#[derive(Debug)]
+pub enum Patch {
+    Merge(serde_json::Value),
+    Json(json_patch::Patch),
+}
+
+impl Patch {
+    pub fn merge_content_type() -> ContentType {
+        ContentType::new("application", "merge-patch+json")
+    }
+
+    pub fn json_content_type() -> ContentType {
+        ContentType::new("application", "json-patch+json")
+    }
+
+    /// Applies the patch over the document, leaving it untouched when any operation fails.
+    pub fn apply(&self, document: &mut serde_json::Value) -> Result<(), json_patch::PatchError> {
+        match self {
+            Patch::Merge(patch) => {
+                json_patch::merge(document, patch);
+                Ok(())
+            }
+            Patch::Json(patch) => json_patch::patch(document, patch),
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+#[serde(transparent)]
+struct MergePatchBody(#[garde(skip)] serde_json::Value);
+
+#[derive(Deserialize, Validate)]
+#[serde(transparent)]
+struct JsonPatchBody(#[garde(skip)] json_patch::Patch);
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for Patch {
+    type Error = JsonValidationError;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        match req.content_type() {
+            Some(content_type) if *content_type == Patch::merge_content_type() => {
+                Json::<MergePatchBody>::from_data(req, data)
+                    .await
+                    .map(|Json(body)| Patch::Merge(body.0))
+            }
+            Some(content_type) if *content_type == Patch::json_content_type() => {
+                Json::<JsonPatchBody>::from_data(req, data)
+                    .await
+                    .map(|Json(body)| Patch::Json(body.0))
+            }
+            _ => {
+                let supported = vec![Patch::merge_content_type(), Patch::json_content_type()];
+                req.local_cache(|| {
+                    Some(
+                        JsonGuardErrors::UnsupportedMediaType(supported)
+                            .get_problem_detail_extensions(),
+                    )
+                });
+                Outcome::Error((
+                    Status::UnsupportedMediaType,
+                    JsonValidationError::UnsupportedMediaType,
+                ))
+            }
+        }
+    }
+}
+
+async fn from_data<'r, T>(
+    req: &'r Request<'_>,
+    data: Data<'r>,
+    format: Format,
+) -> Outcome<'r, T, JsonValidationError>
+where
+    T: Deserialize<'r> + Validate,
+    T::Context: ValidationContext,
+{
+    let limit = req
+        .limits()
+        .get(format.limit_name())
+        .unwrap_or(Limits::JSON);
+
+    let bytes = match data.open(limit).into_bytes().await {
+        Err(error) => {
+            req.local_cache(|| Some(JsonGuardErrors::IO(&error).get_problem_detail_extensions()));
+            return Outcome::Error((
+                Status::InternalServerError,
+                JsonValidationError::IO { source: error },
+            ));
+        }
+        Ok(capped) if capped.is_complete() => capped.into_inner(),
+        Ok(_) => {
+            return Outcome::Error((Status::PayloadTooLarge, JsonValidationError::TooLarge));
+        }
+    };
+
+    let bytes = local_cache!(req, bytes);
+
+    if bytes.is_empty() {
+        req.local_cache(|| Some(JsonGuardErrors::EmptyBody.get_problem_detail_extensions()));
+        return Outcome::Error((Status::BadRequest, JsonValidationError::EmptyBody));
+    }
+
+    match format.deserialize::<T>(bytes) {
+        Err(error) => {
+            req.local_cache(|| {
+                Some(JsonGuardErrors::ParseError(&error).get_problem_detail_extensions())
+            });
+
+            let status = if error.is_data() {
+                Status::UnprocessableEntity
+            } else {
+                Status::BadRequest
+            };
+
+            Outcome::Error((status, JsonValidationError::Parse { source: error }))
+        }
+        Ok(t) => {
+            let Some(context) = T::Context::from_request(req) else {
+                return Outcome::Error((
+                    Status::InternalServerError,
+                    JsonValidationError::MissingContext,
+                ));
+            };
+
+            match t.validate_with(&context) {
                 Err(error) => {
                     req.local_cache(|| {
                         Some(
@@ -147,8 +311,298 @@ impl<'r, T: Deserialize<'r> + Validate<Context = impl Default>> FromData<'r> for
                         JsonValidationError::Validation { source: error },
                     ))
                 }
-                Ok(_) => Outcome::Success(Json(t)),
-            },
+                Ok(_) => Outcome::Success(t),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum AuthenticationError {
+    #[error("The request has no credentials, send an access token or an API key")]
+    MissingToken,
+    #[error("{0}")]
+    Rejected(String),
+    #[error("The API key lacks the {0} scope this request needs")]
+    InsufficientScope(&'static str),
+    #[error("API keys can't be used for this request, it needs an access token")]
+    SessionRequired,
+    #[error("The request is restricted to administrators")]
+    AdminRequired,
+    #[error("The request is restricted to the user itself and administrators")]
+    OwnerOrAdminRequired,
+    #[error("The server has found an unexpected situation")]
+    InternalServerError,
+}
+
+/// Credentials sent with a request, in the `Authorization` header or the API key one.
+enum Credentials<'r> {
+    AccessToken(&'r str),
+    ApiKey(&'r str),
+}
+
+impl<'r> Credentials<'r> {
+    fn of(req: &'r Request<'_>) -> Option<Self> {
+        if let Some(header) = req.headers().get_one("Authorization") {
+            if let Some(token) = header.strip_prefix(BEARER_SCHEME) {
+                return Some(Credentials::AccessToken(token.trim()));
+            }
+
+            if let Some(key) = header.strip_prefix(API_KEY_SCHEME) {
+                return Some(Credentials::ApiKey(key.trim()));
+            }
         }
+
+        req.headers()
+            .get_one(API_KEY_HEADER)
+            .map(|key| Credentials::ApiKey(key.trim()))
+    }
+}
+
+/// Request guard of the routes restricted to logged in users, reading the access token of the
+/// `Authorization: Bearer` header or the API key of the `Authorization: ApiKey` or `X-API-Key`
+/// ones. API keys need the `read` scope for safe requests and the `write` one for the others.
+/// Requests without valid credentials are answered by the 401 and 403 catchers.
+#[derive(Debug)]
+pub struct Authenticated {
+    user_id: String,
+    session_id: Option<String>,
+    admin: bool,
+}
+
+impl Authenticated {
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Whether the request can manage the given user, being that user or an administrator
+    /// logged in with an access token.
+    pub fn can_manage(&self, user_id: &str) -> bool {
+        self.admin || self.user_id.eq_ignore_ascii_case(user_id)
+    }
+
+    /// Session of the access token, missing when authenticated with an API key.
+    pub fn get_session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+}
+
+fn reject<T>(
+    req: &Request<'_>,
+    status: Status,
+    error: AuthenticationError,
+) -> request::Outcome<T, AuthenticationError> {
+    req.local_cache(|| Some(error.clone()));
+
+    request::Outcome::Error((status, error))
+}
+
+/// Rejects the request as a server error, logging the source of the error when there is one.
+fn internal_error<T>(
+    req: &Request<'_>,
+    source: Option<anyhow::Error>,
+) -> request::Outcome<T, AuthenticationError> {
+    if let Some(source) = source {
+        log::error!(
+            "[{}] {} {}: {:#}",
+            RequestId::of(req),
+            req.method(),
+            req.uri(),
+            source
+        );
+    }
+
+    reject(
+        req,
+        Status::InternalServerError,
+        AuthenticationError::InternalServerError,
+    )
+}
+
+/// Whether the user is listed in the [`AdminConfig`].
+fn is_admin(req: &Request<'_>, user_id: &str) -> bool {
+    req.rocket()
+        .state::<AdminConfig>()
+        .is_some_and(|admins| admins.users.iter().any(|admin| admin == user_id))
+}
+
+/// Scope an API key needs for the request, requests that change nothing only need to read.
+fn required_scope(method: Method) -> ApiKeyScope {
+    match method {
+        Method::Get | Method::Head | Method::Options => ApiKeyScope::Read,
+        _ => ApiKeyScope::Write,
+    }
+}
+
+impl Authenticated {
+    async fn with_access_token(
+        req: &Request<'_>,
+        token: &str,
+    ) -> request::Outcome<Self, AuthenticationError> {
+        let request::Outcome::Success(authenticate_service) =
+            req.guard::<Inject<'_, dyn UserAuthenticateToken>>().await
+        else {
+            return internal_error(req, None);
+        };
+
+        match authenticate_service.authenticate(token) {
+            Ok(token) => request::Outcome::Success(Authenticated {
+                user_id: token.get_user_id().to_owned(),
+                session_id: Some(token.get_session_id().to_owned()),
+                admin: is_admin(req, token.get_user_id()),
+            }),
+            Err(UserAuthenticateTokenErrors::InternalServerError { source }) => {
+                internal_error(req, source)
+            }
+            Err(error) => reject(
+                req,
+                Status::Unauthorized,
+                AuthenticationError::Rejected(error.to_string()),
+            ),
+        }
+    }
+
+    async fn with_api_key(
+        req: &Request<'_>,
+        key: &str,
+    ) -> request::Outcome<Self, AuthenticationError> {
+        let request::Outcome::Success(authenticate_service) =
+            req.guard::<Inject<'_, dyn UserAuthenticateApiKey>>().await
+        else {
+            return internal_error(req, None);
+        };
+
+        let api_key = match authenticate_service.authenticate(key) {
+            Ok(api_key) => api_key,
+            Err(UserAuthenticateApiKeyErrors::InternalServerError { source }) => {
+                return internal_error(req, source)
+            }
+            Err(error) => {
+                return reject(
+                    req,
+                    Status::Unauthorized,
+                    AuthenticationError::Rejected(error.to_string()),
+                )
+            }
+        };
+
+        let scope = required_scope(req.method());
+        if !api_key.allows(scope) {
+            return reject(
+                req,
+                Status::Forbidden,
+                AuthenticationError::InsufficientScope(scope.get()),
+            );
+        }
+
+        request::Outcome::Success(Authenticated {
+            user_id: api_key.get_user_id().to_owned(),
+            session_id: None,
+            admin: false,
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Authenticated {
+    type Error = AuthenticationError;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        match Credentials::of(req) {
+            Some(Credentials::AccessToken(token)) => {
+                Authenticated::with_access_token(req, token).await
+            }
+            Some(Credentials::ApiKey(key)) => Authenticated::with_api_key(req, key).await,
+            None => reject(req, Status::Unauthorized, AuthenticationError::MissingToken),
+        }
+    }
+}
+
+/// Request guard of the routes managing the credentials of the logged in user, which need an
+/// access token so an API key can't be used to get more of them.
+#[derive(Debug)]
+pub struct SessionAuthenticated {
+    user_id: String,
+}
+
+impl SessionAuthenticated {
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SessionAuthenticated {
+    type Error = AuthenticationError;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let authenticated = match req.guard::<Authenticated>().await {
+            request::Outcome::Success(authenticated) => authenticated,
+            request::Outcome::Error(error) => return request::Outcome::Error(error),
+            request::Outcome::Forward(status) => return request::Outcome::Forward(status),
+        };
+
+        if authenticated.session_id.is_none() {
+            return reject(req, Status::Forbidden, AuthenticationError::SessionRequired);
+        }
+
+        request::Outcome::Success(SessionAuthenticated {
+            user_id: authenticated.user_id,
+        })
+    }
+}
+
+/// Users allowed to administrate the others, read from the `admins` configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// Ids of the administrators.
+    pub users: Vec<String>,
+}
+
+/// Request guard of the routes administrating the users, restricted to the logged in users listed
+/// in the [`AdminConfig`]. They need an access token, an API key can't be used.
+#[derive(Debug)]
+pub struct Admin {
+    user_id: String,
+}
+
+impl Admin {
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = AuthenticationError;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let authenticated = match req.guard::<SessionAuthenticated>().await {
+            request::Outcome::Success(authenticated) => authenticated,
+            request::Outcome::Error(error) => return request::Outcome::Error(error),
+            request::Outcome::Forward(status) => return request::Outcome::Forward(status),
+        };
+
+        if !is_admin(req, &authenticated.user_id) {
+            return reject(req, Status::Forbidden, AuthenticationError::AdminRequired);
+        }
+
+        request::Outcome::Success(Admin {
+            user_id: authenticated.user_id,
+        })
+    }
+}
+
+/// The `User-Agent` header of the request, missing when the client didn't send one.
+#[derive(Debug)]
+pub struct UserAgent<'r>(pub Option<&'r str>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent<'r> {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        request::Outcome::Success(UserAgent(req.headers().get_one("User-Agent")))
     }
 }
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
 
-use rocket::http::Status;
 use rocket::Request;
 
+use crate::formats::Format;
+use crate::guard::AuthenticationError;
+use crate::responders::problem_detail::problem_type::ProblemType;
 use crate::responders::problem_detail::{ProblemDetail, ProblemDetailBuilder};
 
 #[catch(400)]
 pub fn bad_request(req: &Request) -> ProblemDetail {
     let err = req.local_cache::<Option<HashMap<String, serde_json::Value>>, _>(|| None);
 
-    let mut builder = ProblemDetailBuilder::from(Status::BadRequest)
+    let mut builder = ProblemDetailBuilder::problem(ProblemType::MalformedRequest)
         .detail("The server cannot or will not process the request due to something that is perceived to be a client error");
 
     if let Some(err) = err {
@@ -19,10 +21,50 @@ pub fn bad_request(req: &Request) -> ProblemDetail {
     builder.build()
 }
 
+impl From<&AuthenticationError> for ProblemDetail {
+    fn from(value: &AuthenticationError) -> Self {
+        let problem_type = match value {
+            AuthenticationError::MissingToken | AuthenticationError::Rejected(_) => {
+                ProblemType::AuthenticationRequired
+            }
+            AuthenticationError::InsufficientScope(_) => ProblemType::InsufficientScope,
+            AuthenticationError::SessionRequired => ProblemType::SessionRequired,
+            AuthenticationError::AdminRequired => ProblemType::AdminRequired,
+            AuthenticationError::OwnerOrAdminRequired => ProblemType::OwnerOrAdminRequired,
+            AuthenticationError::InternalServerError => {
+                return ProblemDetail::internal_server_error(None)
+            }
+        };
+
+        ProblemDetailBuilder::problem(problem_type)
+            .detail(value.to_string())
+            .build()
+    }
+}
+
+/// Handles a 401 error, describing why the access token of the request was rejected.
+#[catch(401)]
+pub fn unauthorized(req: &Request) -> ProblemDetail {
+    let err = req.local_cache::<Option<AuthenticationError>, _>(|| None);
+
+    ProblemDetail::from(err.as_ref().unwrap_or(&AuthenticationError::MissingToken))
+}
+
+/// Handles a 403 error, describing why the credentials of the request aren't enough for it.
+#[catch(403)]
+pub fn forbidden(req: &Request) -> ProblemDetail {
+    let err = req.local_cache::<Option<AuthenticationError>, _>(|| None);
+
+    match err {
+        Some(err) => ProblemDetail::from(err),
+        None => ProblemDetailBuilder::problem(ProblemType::Forbidden).build(),
+    }
+}
+
 /// Handles a 404 error by returning a JSON response with an error message.
 #[catch(404)]
 pub fn not_found(req: &Request) -> ProblemDetail {
-    ProblemDetailBuilder::from(Status::NotFound)
+    ProblemDetailBuilder::problem(ProblemType::ResourceNotFound)
         .detail(format!(
             "The requested uri '{}' was not found",
             req.uri().path()
@@ -30,25 +72,51 @@ pub fn not_found(req: &Request) -> ProblemDetail {
         .build()
 }
 
+/// Handles a 406 error, answered in JSON as none of the accepted formats is supported.
+#[catch(406)]
+pub fn not_acceptable() -> ProblemDetail {
+    ProblemDetailBuilder::problem(ProblemType::NotAcceptable)
+        .detail(format!(
+            "The requested representation is not supported, supported formats are {}",
+            supported_formats()
+        ))
+        .build()
+}
+
 /// Handles a 409 error by returning a JSON response with an error message.
 #[catch(409)]
 pub fn conflict(_: &Request) -> ProblemDetail {
-    ProblemDetailBuilder::from(Status::Conflict).build()
+    ProblemDetailBuilder::problem(ProblemType::Conflict).build()
 }
 
 #[catch(413)]
 pub fn payload_too_large() -> ProblemDetail {
-    ProblemDetailBuilder::from(Status::PayloadTooLarge)
+    ProblemDetailBuilder::problem(ProblemType::PayloadTooLarge)
         .detail("Request entity is larger than limits defined by server")
         .build()
 }
 
+/// Handles a 415 error, listing the media types accepted by the route when the guard knows them.
+#[catch(415)]
+pub fn unsupported_media_type(req: &Request) -> ProblemDetail {
+    let err = req.local_cache::<Option<HashMap<String, serde_json::Value>>, _>(|| None);
+
+    let mut builder = ProblemDetailBuilder::problem(ProblemType::UnsupportedMediaType)
+        .detail("The request body format is not supported");
+
+    if let Some(err) = err {
+        builder = builder.extensions(err.clone());
+    }
+
+    builder.build()
+}
+
 /// Handles a 422 error by returning a JSON response with an error message.
 #[catch(422)]
 pub fn unprocessable_entity(req: &Request) -> ProblemDetail {
     let err = req.local_cache::<Option<HashMap<String, serde_json::Value>>, _>(|| None);
 
-    let mut builder = ProblemDetailBuilder::from(Status::UnprocessableEntity)
+    let mut builder = ProblemDetailBuilder::problem(ProblemType::ValidationFailed)
         .detail("The request was well-formed but was unable to be followed due to semantic errors");
 
     if let Some(err) = err {
@@ -62,7 +130,7 @@ pub fn unprocessable_entity(req: &Request) -> ProblemDetail {
 pub fn internal_error_server(req: &Request) -> ProblemDetail {
     let err = req.local_cache::<Option<HashMap<String, serde_json::Value>>, _>(|| None);
 
-    let mut builder = ProblemDetailBuilder::from(Status::InternalServerError)
+    let mut builder = ProblemDetailBuilder::problem(ProblemType::InternalServerError)
         .detail("The server has encountered a situation it does not know how to handle");
 
     if let Some(err) = err {
@@ -71,3 +139,11 @@ pub fn internal_error_server(req: &Request) -> ProblemDetail {
 
     builder.build()
 }
+
+fn supported_formats() -> String {
+    Format::ALL
+        .iter()
+        .map(|format| format.content_type().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
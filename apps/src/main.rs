@@ -1,36 +1,193 @@
 #[macro_use]
 extern crate rocket;
 
+use std::time::Duration;
+
 use rocket::{Build, Rocket};
 
 use contexts::shared::infrastructure::dependency_container::{build_container, AppContainer};
 
-use crate::controllers::users;
+use crate::controllers::{problems, users};
+use crate::guard::AdminConfig;
 
+use contexts::shared::infrastructure::mailer::MailerConfig;
+use contexts::users::application::delete::UserDeletionConfig;
+use contexts::users::application::request_email_verification::EmailVerificationConfig;
+use contexts::users::application::request_password_reset::PasswordResetConfig;
+use contexts::users::domain::users::api_key::ApiKeyConfig;
+use contexts::users::domain::users::email_policy::EmailPolicyRules;
+use contexts::users::domain::users::login_failures::LoginThrottlingRules;
+use contexts::users::domain::users::password_policy::PasswordPolicyRules;
+use contexts::users::domain::users::two_factor::TwoFactorConfig;
+use contexts::users::domain::users::user_session::SessionConfig;
+use contexts::users::infrastructure::password_hasher_argon2::Argon2Config;
 use contexts::users::infrastructure::sqlite::container;
 
+/// Key of the Rocket configuration (`Rocket.toml` or `ROCKET_PASSWORD_POLICY`) with the rules
+/// passwords must follow.
+const PASSWORD_POLICY_CONFIG: &str = "password_policy";
+/// Key of the Rocket configuration with the email providers and the disposable domains.
+const EMAIL_POLICY_CONFIG: &str = "email_policy";
+/// Key of the Rocket configuration with the Argon2 parameters of the password hashes.
+const PASSWORD_HASHING_CONFIG: &str = "password_hashing";
+/// Key of the Rocket configuration with the lifetime and link of the password reset tokens.
+const PASSWORD_RESET_CONFIG: &str = "password_reset";
+/// Key of the Rocket configuration with the email verification tokens and the actions that need
+/// a verified email.
+const EMAIL_VERIFICATION_CONFIG: &str = "email_verification";
+/// Key of the Rocket configuration with the sender and destination of the outgoing mails.
+const MAILER_CONFIG: &str = "mailer";
+/// Key of the Rocket configuration with how long deleted users can be restored and are kept.
+const USER_DELETION_CONFIG: &str = "user_deletion";
+/// Key of the Rocket configuration with the failed logins tolerated before locking out.
+const LOGIN_THROTTLING_CONFIG: &str = "login_throttling";
+/// Key of the Rocket configuration with the lifetimes of the tokens and the key signing them.
+const SESSIONS_CONFIG: &str = "sessions";
+/// Key of the Rocket configuration with the issuer shown by authenticator apps, the key
+/// encrypting the secrets and the lifetime of the two-factor challenges.
+const TWO_FACTOR_CONFIG: &str = "two_factor";
+/// Key of the Rocket configuration with the lifetimes of the API keys.
+const API_KEYS_CONFIG: &str = "api_keys";
+/// Key of the Rocket configuration with the ids of the users allowed to administrate the others.
+const ADMINS_CONFIG: &str = "admins";
+
 pub type Inject<'r, I> = shaku_rocket::Inject<'r, AppContainer, I>;
 
 mod controllers;
+mod fairings;
+mod formats;
 mod guard;
 mod handlers;
+mod openapi;
 mod responders;
 
 #[launch]
 async fn rocket() -> Rocket<Build> {
-    rocket::build()
-        .manage(Box::new(build_container(container::build_container())))
+    let rocket = rocket::build();
+
+    let password_policy: PasswordPolicyRules = rocket
+        .figment()
+        .focus(PASSWORD_POLICY_CONFIG)
+        .extract()
+        .expect("Password policy configuration is invalid.");
+
+    let email_policy: EmailPolicyRules = rocket
+        .figment()
+        .focus(EMAIL_POLICY_CONFIG)
+        .extract()
+        .expect("Email policy configuration is invalid.");
+
+    let password_hashing: Argon2Config = rocket
+        .figment()
+        .focus(PASSWORD_HASHING_CONFIG)
+        .extract()
+        .expect("Password hashing configuration is invalid.");
+
+    let password_reset: PasswordResetConfig = rocket
+        .figment()
+        .focus(PASSWORD_RESET_CONFIG)
+        .extract()
+        .expect("Password reset configuration is invalid.");
+
+    let email_verification: EmailVerificationConfig = rocket
+        .figment()
+        .focus(EMAIL_VERIFICATION_CONFIG)
+        .extract()
+        .expect("Email verification configuration is invalid.");
+
+    let mailer: MailerConfig = rocket
+        .figment()
+        .focus(MAILER_CONFIG)
+        .extract()
+        .expect("Mailer configuration is invalid.");
+
+    let user_deletion: UserDeletionConfig = rocket
+        .figment()
+        .focus(USER_DELETION_CONFIG)
+        .extract()
+        .expect("User deletion configuration is invalid.");
+
+    let login_throttling: LoginThrottlingRules = rocket
+        .figment()
+        .focus(LOGIN_THROTTLING_CONFIG)
+        .extract()
+        .expect("Login throttling configuration is invalid.");
+
+    let sessions: SessionConfig = rocket
+        .figment()
+        .focus(SESSIONS_CONFIG)
+        .extract()
+        .expect("Sessions configuration is invalid.");
+
+    let two_factor: TwoFactorConfig = rocket
+        .figment()
+        .focus(TWO_FACTOR_CONFIG)
+        .extract()
+        .expect("Two-factor configuration is invalid.");
+
+    let api_keys: ApiKeyConfig = rocket
+        .figment()
+        .focus(API_KEYS_CONFIG)
+        .extract()
+        .expect("API keys configuration is invalid.");
+
+    let admins: AdminConfig = rocket
+        .figment()
+        .focus(ADMINS_CONFIG)
+        .extract()
+        .expect("Administrators configuration is invalid.");
+
+    let password_reset_queue = fairings::PasswordResetQueueFairing {
+        size: password_reset.queue_size.max(1),
+        cooldown: Duration::from_secs(password_reset.cooldown.into()),
+    };
+
+    // Purging continuously isn't possible, the interval is at least a minute.
+    let purge_interval = Duration::from_secs(u64::from(user_deletion.purge_interval.max(1)) * 60);
+
+    rocket
+        .manage(Box::new(build_container(
+            container::build_container(&two_factor.encryption_key),
+            password_policy,
+            email_policy,
+            password_hashing,
+            password_reset,
+            email_verification,
+            mailer,
+            user_deletion,
+            login_throttling,
+            sessions,
+            two_factor,
+            api_keys,
+        )))
+        .manage(admins)
+        .attach(fairings::UserEmailMigrationFairing)
+        .attach(password_reset_queue)
+        .attach(fairings::RequestIdFairing)
+        .attach(fairings::UserPurgeFairing {
+            interval: purge_interval,
+        })
         .register(
             "/",
             catchers![
+                handlers::unauthorized,
+                handlers::forbidden,
                 handlers::not_found,
                 handlers::bad_request,
+                handlers::not_acceptable,
                 handlers::conflict,
                 handlers::payload_too_large,
+                handlers::unsupported_media_type,
                 handlers::unprocessable_entity,
                 handlers::internal_error_server,
             ],
         )
+        .mount("/", routes![openapi::openapi])
+        .mount(
+            problems::BASE_URL,
+            routes![problems::problem_types, problems::problem_type],
+        )
+        .mount("/", openapi::docs())
         .mount(
             users::BASE_URL,
             routes![
@@ -38,8 +195,31 @@ async fn rocket() -> Rocket<Build> {
                 users::user_get,
                 users::user_get_all,
                 users::user_update,
+                users::user_patch,
+                users::user_login,
+                users::user_login_two_factor,
+                users::user_refresh,
+                users::user_sessions,
+                users::user_session_revoke,
+                users::user_sessions_revoke,
+                users::user_two_factor_enroll,
+                users::user_two_factor_confirm,
+                users::user_two_factor_reset,
+                users::user_api_key_create,
+                users::user_api_keys,
+                users::user_api_key_revoke,
+                users::user_password_reset,
+                users::user_password_reset_confirm,
+                users::user_verify_email,
                 users::user_delete,
-                users::user_criteria
+                users::user_restore,
+                users::user_suspend,
+                users::user_reactivate,
+                users::user_lock,
+                users::user_close,
+                users::user_unlock,
+                users::user_criteria,
+                users::user_search
             ],
         )
 }
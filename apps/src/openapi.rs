@@ -0,0 +1,66 @@
+use rocket::serde::json::Json;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_rapidoc::RapiDoc;
+
+use crate::controllers::users::UsersApiDoc;
+use crate::responders::problem_detail::ProblemDetail;
+
+pub const SPEC_URL: &str = "/openapi.json";
+pub const DOCS_URL: &str = "/docs";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rust Hexagonal Architecture"),
+    components(schemas(ProblemDetail)),
+    modifiers(&BearerSecurity)
+)]
+pub struct ApiDoc;
+
+/// Declares the access tokens handed out on login, sent as `Authorization: Bearer` headers, and
+/// the API keys of the users, sent as `X-API-Key` or `Authorization: ApiKey` headers.
+struct BearerSecurity;
+
+impl Modify for BearerSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-API-Key",
+                "API key of the user, also accepted as an Authorization: ApiKey header",
+            ))),
+        );
+    }
+}
+
+impl ApiDoc {
+    /// Builds the full specification out of the documentation of every controller.
+    pub fn build() -> utoipa::openapi::OpenApi {
+        let mut openapi = ApiDoc::openapi();
+
+        openapi.merge(UsersApiDoc::openapi());
+
+        openapi
+    }
+}
+
+#[get("/openapi.json")]
+pub fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::build())
+}
+
+pub fn docs() -> RapiDoc {
+    RapiDoc::new(SPEC_URL).path(DOCS_URL)
+}
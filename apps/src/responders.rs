@@ -1,56 +1,64 @@
 #[allow(dead_code)]
 use std::io::Cursor;
 
-use rocket::http::{ContentType, Status};
+use rocket::http::{Header, Status};
 use rocket::response::Responder;
 use rocket::{Request, Response};
 use serde::Serialize;
 
+use crate::fairings::RequestId;
+use crate::formats::Format;
+
 pub mod problem_detail;
 
-pub struct JsonResponse<T: Serialize> {
+/// Body serialized in the representation negotiated through the `Accept` header.
+pub struct Negotiated<T: Serialize> {
     body: T,
     status: Status,
 }
 
-impl<T: Serialize> JsonResponse<T> {
+impl<T: Serialize> Negotiated<T> {
     #[allow(dead_code)]
-    pub fn new(body: T, status: Status) -> JsonResponse<T> {
-        JsonResponse { body, status }
+    pub fn new(body: T, status: Status) -> Negotiated<T> {
+        Negotiated { body, status }
     }
 
-    pub fn ok(body: T) -> JsonResponse<T> {
-        JsonResponse {
+    pub fn ok(body: T) -> Negotiated<T> {
+        Negotiated {
             body,
             status: Status::Ok,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn created(body: T) -> JsonResponse<T> {
-        JsonResponse {
+    pub fn created(body: T) -> Negotiated<T> {
+        Negotiated {
             body,
             status: Status::Created,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn accepted(body: T) -> JsonResponse<T> {
-        JsonResponse {
+    pub fn accepted(body: T) -> Negotiated<T> {
+        Negotiated {
             body,
             status: Status::Accepted,
         }
     }
 }
 
-impl<'r, T: Serialize> Responder<'r, 'static> for JsonResponse<T> {
-    fn respond_to(self, _: &'r Request<'_>) -> rocket::response::Result<'static> {
-        let json = serde_json::to_string(&self.body).unwrap();
+impl<'r, T: Serialize> Responder<'r, 'static> for Negotiated<T> {
+    fn respond_to(self, req: &'r Request<'_>) -> rocket::response::Result<'static> {
+        let format = Format::negotiate(req).ok_or(Status::NotAcceptable)?;
+
+        let body = format.serialize(&self.body).map_err(|error| {
+            log::error!("[{}] {} {}: {}", RequestId::of(req), req.method(), req.uri(), error);
+            Status::InternalServerError
+        })?;
 
         Response::build()
             .status(self.status)
-            .header(ContentType::JSON)
-            .sized_body(json.len(), Cursor::new(json))
+            .header(format.content_type())
+            .header(Header::new("Vary", "Accept"))
+            .sized_body(body.len(), Cursor::new(body))
             .ok()
     }
 }
@@ -1,15 +1,34 @@
 use std::collections::HashMap;
 use std::io::Cursor;
 
-use rocket::http::{ContentType, Status};
+use rocket::http::{Header, Status};
 use rocket::response::Responder;
 use rocket::{response, Request, Response};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::openapi::{RefOr, Schema};
+use utoipa::ToSchema;
 
+use crate::fairings::RequestId;
+use crate::formats::Format;
+use crate::responders::problem_detail::problem_type::ProblemType;
+
+pub mod problem_type;
+
+/// Problem of a request, boxed as it's the error of every handler and far larger than their
+/// responses.
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
-pub struct ProblemDetail {
+#[serde(transparent)]
+pub struct ProblemDetail(Box<Problem>);
+
+/// Problem Details for HTTP APIs as defined by RFC 9457.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, ToSchema)]
+#[schema(as = ProblemDetail)]
+struct Problem {
     #[serde(rename = "type")]
+    #[schema(example = "/problems/user-not-found")]
     r#type: String,
+    #[schema(value_type = u16, example = 404)]
     status: Status,
     title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -17,22 +36,53 @@ pub struct ProblemDetail {
     #[serde(skip_serializing_if = "Option::is_none")]
     instance: Option<String>,
     #[serde(skip_serializing_if = "HashMap::is_empty", flatten)]
+    #[schema(value_type = Object)]
     extensions: HashMap<String, serde_json::Value>,
+    /// Error chain behind the problem, only logged server side.
+    #[serde(skip)]
+    error: Option<String>,
+    /// Seconds the client should wait before retrying, sent as the `Retry-After` header.
+    #[serde(skip)]
+    retry_after: Option<u64>,
+}
+
+impl<'s> ToSchema<'s> for ProblemDetail {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        Problem::schema()
+    }
 }
 
 impl From<Status> for ProblemDetail {
     fn from(status: Status) -> ProblemDetail {
-        ProblemDetail {
+        ProblemDetail(Box::new(Problem {
             r#type: String::from("about:blank"),
             status,
             title: String::from(status.reason_lossy()),
             detail: None,
             instance: None,
             extensions: HashMap::new(),
-        }
+            error: None,
+            retry_after: None,
+        }))
     }
 }
 
+impl ProblemDetail {
+    /// Generic internal server error, the source is logged with the correlation id of the
+    /// request instead of being sent to the client.
+    pub fn internal_server_error(source: Option<anyhow::Error>) -> ProblemDetail {
+        let mut builder = ProblemDetailBuilder::problem(ProblemType::InternalServerError).detail(
+            "The server has found an unexpected situation, \
+             if it persists contact support with the correlation id",
+        );
+
+        if let Some(source) = source {
+            builder = builder.error(&source);
+        }
+
+        builder.build()
+    }
+}
 
 pub struct ProblemDetailBuilder {
     r#type: Option<String>,
@@ -41,6 +91,8 @@ pub struct ProblemDetailBuilder {
     detail: Option<String>,
     instance: Option<String>,
     extensions: HashMap<String, serde_json::Value>,
+    error: Option<String>,
+    retry_after: Option<u64>,
 }
 
 impl Default for ProblemDetailBuilder {
@@ -54,6 +106,8 @@ impl Default for ProblemDetailBuilder {
             detail: None,
             instance: None,
             extensions: HashMap::new(),
+            error: None,
+            retry_after: None,
         }
     }
 }
@@ -67,10 +121,17 @@ impl ProblemDetailBuilder {
             detail: None,
             instance: None,
             extensions: HashMap::new(),
+            error: None,
+            retry_after: None,
         }
     }
 
-    #[allow(dead_code)]
+    pub fn problem(problem_type: ProblemType) -> ProblemDetailBuilder {
+        ProblemDetailBuilder::from(problem_type.status())
+            .r#type(problem_type.uri())
+            .title(problem_type.title())
+    }
+
     pub fn r#type<T: Into<String>>(mut self, r#type: T) -> Self {
         self.r#type = Some(r#type.into());
         self
@@ -82,7 +143,6 @@ impl ProblemDetailBuilder {
         self
     }
 
-    #[allow(dead_code)]
     pub fn title<T: Into<String>>(mut self, title: T) -> Self {
         self.title = title.into();
         self
@@ -113,26 +173,67 @@ impl ProblemDetailBuilder {
         self
     }
 
+    pub fn error(mut self, error: &anyhow::Error) -> Self {
+        self.error = Some(format!("{error:#}"));
+        self
+    }
+
+    pub fn retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+
     pub fn build(self) -> ProblemDetail {
-        ProblemDetail {
+        ProblemDetail(Box::new(Problem {
             r#type: self.r#type.unwrap_or(String::from("about:blank")),
             status: self.status,
             title: self.title,
             detail: self.detail,
             instance: self.instance,
             extensions: self.extensions,
-        }
+            error: self.error,
+            retry_after: self.retry_after,
+        }))
     }
 }
 
 impl<'r> Responder<'r, 'static> for ProblemDetail {
-    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
-        let json = serde_json::to_string(&self).unwrap();
+    fn respond_to(mut self, req: &'r Request<'_>) -> response::Result<'static> {
+        let request_id = RequestId::of(req);
+
+        if self.0.instance.is_none() {
+            self.0.instance = Some(format!("{}#{}", req.uri().path(), request_id));
+        }
+
+        self.0
+            .extensions
+            .insert(String::from("correlation_id"), json!(request_id.get()));
+
+        if self.0.status.class().is_server_error() {
+            let error = self.0.error.as_deref().unwrap_or(self.0.title.as_str());
+
+            log::error!("[{}] {} {}: {}", request_id, req.method(), req.uri(), error);
+        }
+
+        // Problems are still answered when the client accepts none of the formats
+        let format = Format::negotiate(req).unwrap_or(Format::Json);
+
+        let (format, body) = match format.serialize_problem(&self) {
+            Ok(body) => (format, body),
+            Err(_) => (Format::Json, serde_json::to_vec(&self).unwrap()),
+        };
+
+        let mut response = Response::build();
+
+        if let Some(retry_after) = self.0.retry_after {
+            response.header(Header::new("Retry-After", retry_after.to_string()));
+        }
 
-        Response::build()
-            .status(self.status)
-            .header(ContentType::new("application", "problem+json"))
-            .sized_body(json.len(), Cursor::new(json))
+        response
+            .status(self.0.status)
+            .header(format.problem_content_type())
+            .header(Header::new("Vary", "Accept"))
+            .sized_body(body.len(), Cursor::new(body))
             .ok()
     }
 }
@@ -0,0 +1,382 @@
+use rocket::http::Status;
+
+pub const BASE_URL: &str = "/problems";
+
+/// Registry of the problem types the API can answer with.
+///
+/// Each problem type has a stable URI, clients should branch on it instead of the `detail`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProblemType {
+    MalformedRequest,
+    ValidationFailed,
+    PayloadTooLarge,
+    NotAcceptable,
+    UnsupportedMediaType,
+    ResourceNotFound,
+    Forbidden,
+    Conflict,
+    InvalidUserId,
+    UserAlreadyExists,
+    EmailAlreadyTaken,
+    UserNotFound,
+    RestorePeriodOver,
+    UserIdMismatch,
+    InvalidStatusTransition,
+    InvalidCredentials,
+    AuthenticationRequired,
+    InvalidRefreshToken,
+    SessionNotFound,
+    AdminRequired,
+    OwnerOrAdminRequired,
+    InsufficientScope,
+    SessionRequired,
+    ApiKeyNotFound,
+    AccountUnavailable,
+    AccountLockedOut,
+    TooManyLoginAttempts,
+    TwoFactorAlreadyEnabled,
+    NoPendingTwoFactor,
+    InvalidTwoFactorCode,
+    InvalidTwoFactorChallenge,
+    InvalidResetToken,
+    InvalidVerificationToken,
+    EmailNotVerified,
+    InvalidPatch,
+    InvalidCriteria,
+    InvalidCriteriaField,
+    EmptySearchQuery,
+    InternalServerError,
+}
+
+impl ProblemType {
+    pub const ALL: [ProblemType; 39] = [
+        ProblemType::MalformedRequest,
+        ProblemType::ValidationFailed,
+        ProblemType::PayloadTooLarge,
+        ProblemType::NotAcceptable,
+        ProblemType::UnsupportedMediaType,
+        ProblemType::ResourceNotFound,
+        ProblemType::Forbidden,
+        ProblemType::Conflict,
+        ProblemType::InvalidUserId,
+        ProblemType::UserAlreadyExists,
+        ProblemType::EmailAlreadyTaken,
+        ProblemType::UserNotFound,
+        ProblemType::RestorePeriodOver,
+        ProblemType::UserIdMismatch,
+        ProblemType::InvalidStatusTransition,
+        ProblemType::InvalidCredentials,
+        ProblemType::AuthenticationRequired,
+        ProblemType::InvalidRefreshToken,
+        ProblemType::SessionNotFound,
+        ProblemType::AdminRequired,
+        ProblemType::OwnerOrAdminRequired,
+        ProblemType::InsufficientScope,
+        ProblemType::SessionRequired,
+        ProblemType::ApiKeyNotFound,
+        ProblemType::AccountUnavailable,
+        ProblemType::AccountLockedOut,
+        ProblemType::TooManyLoginAttempts,
+        ProblemType::TwoFactorAlreadyEnabled,
+        ProblemType::NoPendingTwoFactor,
+        ProblemType::InvalidTwoFactorCode,
+        ProblemType::InvalidTwoFactorChallenge,
+        ProblemType::InvalidResetToken,
+        ProblemType::InvalidVerificationToken,
+        ProblemType::EmailNotVerified,
+        ProblemType::InvalidPatch,
+        ProblemType::InvalidCriteria,
+        ProblemType::InvalidCriteriaField,
+        ProblemType::EmptySearchQuery,
+        ProblemType::InternalServerError,
+    ];
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            ProblemType::MalformedRequest => "malformed-request",
+            ProblemType::ValidationFailed => "validation-failed",
+            ProblemType::PayloadTooLarge => "payload-too-large",
+            ProblemType::NotAcceptable => "not-acceptable",
+            ProblemType::UnsupportedMediaType => "unsupported-media-type",
+            ProblemType::ResourceNotFound => "resource-not-found",
+            ProblemType::Forbidden => "forbidden",
+            ProblemType::Conflict => "conflict",
+            ProblemType::InvalidUserId => "invalid-user-id",
+            ProblemType::UserAlreadyExists => "user-already-exists",
+            ProblemType::EmailAlreadyTaken => "email-already-taken",
+            ProblemType::UserNotFound => "user-not-found",
+            ProblemType::RestorePeriodOver => "restore-period-over",
+            ProblemType::UserIdMismatch => "user-id-mismatch",
+            ProblemType::InvalidStatusTransition => "invalid-status-transition",
+            ProblemType::InvalidCredentials => "invalid-credentials",
+            ProblemType::AuthenticationRequired => "authentication-required",
+            ProblemType::InvalidRefreshToken => "invalid-refresh-token",
+            ProblemType::SessionNotFound => "session-not-found",
+            ProblemType::AdminRequired => "admin-required",
+            ProblemType::OwnerOrAdminRequired => "owner-or-admin-required",
+            ProblemType::InsufficientScope => "insufficient-scope",
+            ProblemType::SessionRequired => "session-required",
+            ProblemType::ApiKeyNotFound => "api-key-not-found",
+            ProblemType::AccountUnavailable => "account-unavailable",
+            ProblemType::AccountLockedOut => "account-locked-out",
+            ProblemType::TooManyLoginAttempts => "too-many-login-attempts",
+            ProblemType::TwoFactorAlreadyEnabled => "two-factor-already-enabled",
+            ProblemType::NoPendingTwoFactor => "no-pending-two-factor",
+            ProblemType::InvalidTwoFactorCode => "invalid-two-factor-code",
+            ProblemType::InvalidTwoFactorChallenge => "invalid-two-factor-challenge",
+            ProblemType::InvalidResetToken => "invalid-reset-token",
+            ProblemType::InvalidVerificationToken => "invalid-verification-token",
+            ProblemType::EmailNotVerified => "email-not-verified",
+            ProblemType::InvalidPatch => "invalid-patch",
+            ProblemType::InvalidCriteria => "invalid-criteria",
+            ProblemType::InvalidCriteriaField => "invalid-criteria-field",
+            ProblemType::EmptySearchQuery => "empty-search-query",
+            ProblemType::InternalServerError => "internal-server-error",
+        }
+    }
+
+    pub fn uri(&self) -> String {
+        format!("{}/{}", BASE_URL, self.slug())
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ProblemType::MalformedRequest => Status::BadRequest,
+            ProblemType::ValidationFailed => Status::UnprocessableEntity,
+            ProblemType::PayloadTooLarge => Status::PayloadTooLarge,
+            ProblemType::NotAcceptable => Status::NotAcceptable,
+            ProblemType::UnsupportedMediaType => Status::UnsupportedMediaType,
+            ProblemType::ResourceNotFound => Status::NotFound,
+            ProblemType::Forbidden => Status::Forbidden,
+            ProblemType::Conflict => Status::Conflict,
+            ProblemType::InvalidUserId => Status::UnprocessableEntity,
+            ProblemType::UserAlreadyExists => Status::Conflict,
+            ProblemType::EmailAlreadyTaken => Status::Conflict,
+            ProblemType::UserNotFound => Status::NotFound,
+            ProblemType::RestorePeriodOver => Status::Gone,
+            ProblemType::UserIdMismatch => Status::UnprocessableEntity,
+            ProblemType::InvalidStatusTransition => Status::Conflict,
+            ProblemType::InvalidCredentials => Status::Unauthorized,
+            ProblemType::AuthenticationRequired => Status::Unauthorized,
+            ProblemType::InvalidRefreshToken => Status::Unauthorized,
+            ProblemType::SessionNotFound => Status::NotFound,
+            ProblemType::AdminRequired => Status::Forbidden,
+            ProblemType::OwnerOrAdminRequired => Status::Forbidden,
+            ProblemType::InsufficientScope => Status::Forbidden,
+            ProblemType::SessionRequired => Status::Forbidden,
+            ProblemType::ApiKeyNotFound => Status::NotFound,
+            ProblemType::AccountUnavailable => Status::Forbidden,
+            ProblemType::AccountLockedOut => Status::Locked,
+            ProblemType::TooManyLoginAttempts => Status::TooManyRequests,
+            ProblemType::TwoFactorAlreadyEnabled => Status::Conflict,
+            ProblemType::NoPendingTwoFactor => Status::Conflict,
+            ProblemType::InvalidTwoFactorCode => Status::UnprocessableEntity,
+            ProblemType::InvalidTwoFactorChallenge => Status::Unauthorized,
+            ProblemType::InvalidResetToken => Status::BadRequest,
+            ProblemType::InvalidVerificationToken => Status::BadRequest,
+            ProblemType::EmailNotVerified => Status::Forbidden,
+            ProblemType::InvalidPatch => Status::UnprocessableEntity,
+            ProblemType::InvalidCriteria => Status::UnprocessableEntity,
+            ProblemType::InvalidCriteriaField => Status::UnprocessableEntity,
+            ProblemType::EmptySearchQuery => Status::UnprocessableEntity,
+            ProblemType::InternalServerError => Status::InternalServerError,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ProblemType::MalformedRequest => "Malformed request",
+            ProblemType::ValidationFailed => "Validation failed",
+            ProblemType::PayloadTooLarge => "Payload too large",
+            ProblemType::NotAcceptable => "Not acceptable",
+            ProblemType::UnsupportedMediaType => "Unsupported media type",
+            ProblemType::ResourceNotFound => "Resource not found",
+            ProblemType::Forbidden => "Forbidden",
+            ProblemType::Conflict => "Conflict",
+            ProblemType::InvalidUserId => "Invalid user id",
+            ProblemType::UserAlreadyExists => "User already exists",
+            ProblemType::EmailAlreadyTaken => "Email already taken",
+            ProblemType::UserNotFound => "User not found",
+            ProblemType::RestorePeriodOver => "Restore period over",
+            ProblemType::UserIdMismatch => "User id mismatch",
+            ProblemType::InvalidStatusTransition => "Invalid status transition",
+            ProblemType::InvalidCredentials => "Invalid credentials",
+            ProblemType::AuthenticationRequired => "Authentication required",
+            ProblemType::InvalidRefreshToken => "Invalid refresh token",
+            ProblemType::SessionNotFound => "Session not found",
+            ProblemType::AdminRequired => "Administrator required",
+            ProblemType::OwnerOrAdminRequired => "Owner or administrator required",
+            ProblemType::InsufficientScope => "Insufficient scope",
+            ProblemType::SessionRequired => "Session required",
+            ProblemType::ApiKeyNotFound => "API key not found",
+            ProblemType::AccountUnavailable => "Account unavailable",
+            ProblemType::AccountLockedOut => "Account locked out",
+            ProblemType::TooManyLoginAttempts => "Too many login attempts",
+            ProblemType::TwoFactorAlreadyEnabled => "Two-factor already enabled",
+            ProblemType::NoPendingTwoFactor => "No pending two-factor",
+            ProblemType::InvalidTwoFactorCode => "Invalid two-factor code",
+            ProblemType::InvalidTwoFactorChallenge => "Invalid two-factor challenge",
+            ProblemType::InvalidResetToken => "Invalid reset token",
+            ProblemType::InvalidVerificationToken => "Invalid verification token",
+            ProblemType::EmailNotVerified => "Email not verified",
+            ProblemType::InvalidPatch => "Invalid patch",
+            ProblemType::InvalidCriteria => "Invalid criteria",
+            ProblemType::InvalidCriteriaField => "Invalid criteria field",
+            ProblemType::EmptySearchQuery => "Empty search query",
+            ProblemType::InternalServerError => "Internal server error",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ProblemType::MalformedRequest => {
+                "The body of the request is empty or isn't syntactically valid, \
+                 the parse_error extension describes what couldn't be parsed."
+            }
+            ProblemType::ValidationFailed => {
+                "The request is well-formed but some of its fields hold invalid values, \
+                 the validation_errors extension lists every invalid field."
+            }
+            ProblemType::PayloadTooLarge => {
+                "The body of the request is larger than the limits defined by the server."
+            }
+            ProblemType::NotAcceptable => {
+                "None of the representations in the Accept header is supported, responses can be \
+                 sent as JSON, CBOR, MessagePack or XML."
+            }
+            ProblemType::UnsupportedMediaType => {
+                "The Content-Type of the request body isn't supported by the endpoint, \
+                 the supported_media_types extension lists the accepted ones."
+            }
+            ProblemType::ResourceNotFound => "Nothing is served at the URI of the request.",
+            ProblemType::Forbidden => {
+                "The request isn't allowed, for a reason none of the more specific problem types \
+                 describes."
+            }
+            ProblemType::Conflict => {
+                "The request conflicts with the current state of the resource, \
+                 for a reason none of the more specific problem types describes."
+            }
+            ProblemType::InvalidUserId => {
+                "The user id isn't a valid UUID, only version 7 UUIDs are accepted."
+            }
+            ProblemType::UserAlreadyExists => "A user with the same id is already registered.",
+            ProblemType::EmailAlreadyTaken => {
+                "Another user has the same email, emails are compared ignoring case."
+            }
+            ProblemType::UserNotFound => {
+                "There is no user with the requested id, deleted users are missing until restored."
+            }
+            ProblemType::RestorePeriodOver => {
+                "The user was deleted longer ago than the restore period, \
+                 it can't be restored anymore and will be purged."
+            }
+            ProblemType::UserIdMismatch => {
+                "The id in the body of the request differs from the id of the user in the path."
+            }
+            ProblemType::InvalidStatusTransition => {
+                "The user can't move to the requested status from its current one, suspended \
+                 and locked users are only reactivated and closed users stay closed."
+            }
+            ProblemType::InvalidCredentials => {
+                "There is no user with the email or the password doesn't match, \
+                 which of them is wrong isn't disclosed."
+            }
+            ProblemType::AuthenticationRequired => {
+                "The request has no access token nor API key, or it's invalid, expired, its \
+                 session ended or the API key was revoked. A new access token is handed out by \
+                 logging in or refreshing the session."
+            }
+            ProblemType::InvalidRefreshToken => {
+                "The refresh token doesn't exist, its session expired or was revoked, or it was \
+                 already used, which revokes its whole session. The user has to log in again."
+            }
+            ProblemType::SessionNotFound => {
+                "The logged in user has no active session with the requested id."
+            }
+            ProblemType::AdminRequired => {
+                "The request administrates the users, it needs the access token of a logged in \
+                 user listed as an administrator in the configuration of the server."
+            }
+            ProblemType::OwnerOrAdminRequired => {
+                "The request changes a user other than the logged in one, only administrators \
+                 logged in with an access token can change the other users."
+            }
+            ProblemType::InsufficientScope => {
+                "The API key of the request lacks the scope the request needs, read for safe \
+                 requests and write for the others."
+            }
+            ProblemType::SessionRequired => {
+                "The request manages the credentials of the user, it needs the access token of a \
+                 logged in user and can't be made with an API key."
+            }
+            ProblemType::ApiKeyNotFound => {
+                "The logged in user has no API key with the requested id that wasn't revoked \
+                 nor expired."
+            }
+            ProblemType::AccountUnavailable => {
+                "The credentials are right but the account is suspended, locked or closed, \
+                 it can't log in until an administrator reactivates it. Accounts are locked \
+                 after being locked out too many times in a row."
+            }
+            ProblemType::AccountLockedOut => {
+                "There were too many failed logins of the account, wrong passwords or two-factor \
+                 codes, it can't log in until the Retry-After header seconds have passed or an \
+                 administrator unlocks it."
+            }
+            ProblemType::TooManyLoginAttempts => {
+                "There were too many failed logins from the client address, across accounts, \
+                 it can't log in until the Retry-After header seconds have passed."
+            }
+            ProblemType::TwoFactorAlreadyEnabled => {
+                "The user already has two-factor authentication enabled, an administrator has to \
+                 reset it before the user can enroll again."
+            }
+            ProblemType::NoPendingTwoFactor => {
+                "The user has no two-factor enrollment waiting for confirmation, \
+                 a new secret has to be generated first."
+            }
+            ProblemType::InvalidTwoFactorCode => {
+                "The code isn't the current one of the authenticator app, was already used, \
+                 or isn't a recovery code the user has left."
+            }
+            ProblemType::InvalidTwoFactorChallenge => {
+                "The two-factor challenge of the login doesn't exist, has expired, was already \
+                 completed or got too many wrong codes. The user has to log in again."
+            }
+            ProblemType::InvalidResetToken => {
+                "The password reset token doesn't exist, has expired or was already used, \
+                 a new one has to be requested."
+            }
+            ProblemType::InvalidVerificationToken => {
+                "The email verification token doesn't exist, has expired, was already used \
+                 or was sent to an email the user no longer has."
+            }
+            ProblemType::EmailNotVerified => {
+                "The action is restricted to users who verified their email \
+                 through the link mailed to them."
+            }
+            ProblemType::InvalidPatch => {
+                "The patch can't be applied to the user, an operation failed, the patched user \
+                 misses a field or has one of the wrong type, or the id was modified."
+            }
+            ProblemType::InvalidCriteria => {
+                "The criteria has an unknown operator or order type, \
+                 or a limit or offset that isn't a positive number."
+            }
+            ProblemType::InvalidCriteriaField => {
+                "The criteria filters or orders by a field that users don't have."
+            }
+            ProblemType::EmptySearchQuery => "The search query has no terms to search for.",
+            ProblemType::InternalServerError => {
+                "The server has found an unexpected situation, retrying later may succeed."
+            }
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<ProblemType> {
+        ProblemType::ALL
+            .into_iter()
+            .find(|problem_type| problem_type.slug() == slug)
+    }
+}
@@ -1,2 +1,6 @@
 pub mod regex;
+pub mod clock;
 pub mod criteria;
+pub mod event_bus;
+pub mod mail_template;
+pub mod mailer;
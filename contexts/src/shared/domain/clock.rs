@@ -0,0 +1,7 @@
+use chrono::{DateTime, Utc};
+use shaku::Interface;
+
+/// Source of the current time, injected so it can be frozen.
+pub trait Clock: Interface {
+    fn now(&self) -> DateTime<Utc>;
+}
@@ -0,0 +1,31 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use shaku::Interface;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EventBusErrors {
+    #[error("A subscriber failed handling the event")]
+    SubscriberError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+/// Something that happened in the domain, published once it's persisted.
+pub trait DomainEvent: Any + Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Reacts to the events it's interested in, ignoring the rest.
+pub trait EventSubscriber: Send + Sync {
+    fn handle(&self, event: &dyn DomainEvent) -> Result<(), EventBusErrors>;
+}
+
+pub trait EventBus: Interface {
+    /// Tells every subscriber about the event, failing with the first subscriber that fails.
+    fn publish(&self, event: &dyn DomainEvent) -> Result<(), EventBusErrors>;
+    fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>);
+}
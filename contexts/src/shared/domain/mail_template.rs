@@ -0,0 +1,66 @@
+//! Templates of the mails, with `{{ key }}` placeholders replaced by the values given when
+//! rendering them. Values are escaped in the HTML part, unknown placeholders are kept as is.
+
+use crate::shared::domain::mailer::Mail;
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+#[derive(Debug, Clone, Copy)]
+pub struct MailTemplate {
+    pub subject: &'static str,
+    pub text: &'static str,
+    pub html: Option<&'static str>,
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+fn render(template: &str, values: &[(&str, &str)], escape: fn(&str) -> String) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(OPEN) {
+        let Some(end) = rest[start..].find(CLOSE).map(|end| start + end) else {
+            break;
+        };
+
+        rendered.push_str(&rest[..start]);
+
+        let key = rest[start + OPEN.len()..end].trim();
+        match values.iter().find(|(name, _)| *name == key) {
+            Some((_, value)) => rendered.push_str(&escape(value)),
+            None => rendered.push_str(&rest[start..end + CLOSE.len()]),
+        }
+
+        rest = &rest[end + CLOSE.len()..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+impl MailTemplate {
+    pub fn render(&self, to: &str, values: &[(&str, &str)]) -> Mail {
+        Mail {
+            to: to.to_string(),
+            subject: render(self.subject, values, str::to_string),
+            text: render(self.text, values, str::to_string),
+            html: self.html.map(|html| render(html, values, escape_html)),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use shaku::Interface;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MailerErrors {
+    #[error("The mail couldn't be sent")]
+    SendError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+/// Mail to a single recipient, with an optional HTML alternative of the text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+pub trait Mailer: Interface {
+    fn send(&self, mail: &Mail) -> Result<(), MailerErrors>;
+}
@@ -1,19 +1,36 @@
+use std::sync::LazyLock;
+
 use regex::Regex;
 
-pub fn valid_email(email: &str) -> bool {
-    let regex = Regex::new(r"^[^@ \t\r\n]+@[^@ \t\r\n]+\.[^@ \t\r\n]+$").unwrap();
+/// Rough shape of an email, quoted local parts can hold an @ or spaces but domains can't.
+// language=RegExp
+pub const EMAIL_PATTERN: &str = r"^.+@[^@ \t\r\n]+\.[^@ \t\r\n]+$";
+// language=RegExp
+pub const LOWERCASE_PATTERN: &str = r"\p{Ll}";
+// language=RegExp
+pub const UPPERCASE_PATTERN: &str = r"\p{Lu}";
+// language=RegExp
+pub const NUMBER_PATTERN: &str = r"\d";
+// language=RegExp
+pub const SYMBOL_PATTERN: &str = r"[!@#$%^&*()_+?/:;\[\]{}|<>.,]";
+
+static LOWERCASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(LOWERCASE_PATTERN).unwrap());
+static UPPERCASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(UPPERCASE_PATTERN).unwrap());
+static NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(NUMBER_PATTERN).unwrap());
+static SYMBOL: LazyLock<Regex> = LazyLock::new(|| Regex::new(SYMBOL_PATTERN).unwrap());
 
-    regex.is_match(email)
+pub fn has_lowercase(haystack: &str) -> bool {
+    LOWERCASE.is_match(haystack)
 }
 
-pub fn has_number(haystack: &str) -> bool {
-    let regex = Regex::new(r"^.*(\d).*$").unwrap();
+pub fn has_uppercase(haystack: &str) -> bool {
+    UPPERCASE.is_match(haystack)
+}
 
-    regex.is_match(haystack)
+pub fn has_number(haystack: &str) -> bool {
+    NUMBER.is_match(haystack)
 }
 
 pub fn has_symbol(haystack: &str) -> bool {
-    let regex = Regex::new(r"^.*[!@#$%^&*()_+?/:;\[\]{}|<>.,].*$").unwrap();
-
-    regex.is_match(haystack)
-}
\ No newline at end of file
+    SYMBOL.is_match(haystack)
+}
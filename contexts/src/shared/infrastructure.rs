@@ -1 +1,5 @@
+pub mod clock_fixed;
+pub mod clock_system;
 pub mod dependency_container;
+pub mod event_bus_in_memory;
+pub mod mailer;
@@ -0,0 +1,38 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::shared::domain::clock::Clock;
+
+/// Time frozen at a given instant and only moved by hand, so tests don't depend on when they run.
+#[derive(Debug)]
+pub struct ClockFixed {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ClockFixed {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        ClockFixed {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Moves the time forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self
+            .now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        *now += duration;
+    }
+}
+
+impl Clock for ClockFixed {
+    fn now(&self) -> DateTime<Utc> {
+        *self
+            .now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use shaku::Component;
+
+use crate::shared::domain::clock::Clock;
+
+/// Current time of the system the server runs on.
+#[derive(Component)]
+#[shaku(interface = Clock)]
+pub struct ClockSystem {}
+
+impl Clock for ClockSystem {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
@@ -1,16 +1,107 @@
+use crate::users::application::authenticate::{
+    UserAuthenticateService, UserAuthenticateServiceParameters,
+};
+use crate::users::application::change_status::UserChangeStatusService;
+use crate::users::application::authenticate_token::UserAuthenticateTokenService;
+use crate::users::application::authenticate_api_key::UserAuthenticateApiKeyService;
+use crate::users::application::create_api_key::{
+    UserCreateApiKeyService, UserCreateApiKeyServiceParameters,
+};
 use crate::users::application::criteria::UserCriteriaService;
 use shaku::HasComponent;
+use std::collections::HashSet;
+use std::fs;
 use std::sync::Arc;
 
-use crate::users::application::delete::UserDeleteService;
+use crate::shared::domain::event_bus::EventBus;
+use crate::shared::domain::mailer::Mailer;
+use crate::shared::infrastructure::clock_system::ClockSystem;
+use crate::shared::infrastructure::event_bus_in_memory::EventBusInMemory;
+use crate::shared::infrastructure::mailer::file_drop::{MailerFileDrop, MailerFileDropParameters};
+use crate::shared::infrastructure::mailer::in_memory::MailerInMemory;
+use crate::shared::infrastructure::mailer::smtp::MailerSmtp;
+use crate::shared::infrastructure::mailer::{MailTransport, MailerConfig};
+use crate::users::application::delete::{UserDeleteService, UserDeletionConfig};
+use crate::users::application::enroll_two_factor::{
+    UserEnrollTwoFactorService, UserEnrollTwoFactorServiceParameters,
+};
 use crate::users::application::find::UserFindService;
+use crate::users::application::list_api_keys::UserListApiKeysService;
+use crate::users::application::list_sessions::UserListSessionsService;
+use crate::users::application::purge::{UserPurgeService, UserPurgeServiceParameters};
+use crate::users::application::refresh_session::{
+    UserRefreshSessionService, UserRefreshSessionServiceParameters,
+};
 use crate::users::application::register::UserRegisterService;
+use crate::users::application::replace::UserReplaceService;
+use crate::users::application::request_email_verification::{
+    EmailVerificationConfig, UserEmailVerificationRequestService,
+    UserEmailVerificationRequestServiceParameters,
+};
+use crate::users::application::request_password_reset::{
+    PasswordResetConfig, UserPasswordResetRequestService, UserPasswordResetRequestServiceParameters,
+};
+use crate::users::application::reset_password::UserPasswordResetService;
+use crate::users::application::reset_two_factor::UserResetTwoFactorService;
+use crate::users::application::restore::{UserRestoreService, UserRestoreServiceParameters};
+use crate::users::application::revoke_api_key::UserRevokeApiKeyService;
+use crate::users::application::revoke_sessions::UserRevokeSessionsService;
+use crate::users::application::search::UserSearchService;
+use crate::users::application::start_session::{
+    UserStartSessionService, UserStartSessionServiceParameters,
+};
+use crate::users::application::two_factor_login::{
+    UserTwoFactorLoginService, UserTwoFactorLoginServiceParameters,
+};
+use crate::users::application::unlock::UserUnlockService;
 use crate::users::application::update::UserUpdateService;
+use crate::users::application::verify_email::UserEmailVerifyService;
+use crate::users::application::welcome::{UserWelcome, UserWelcomeService, UserWelcomeSubscriber};
+use crate::users::domain::users::api_key::ApiKeyConfig;
+use crate::users::domain::users::api_key_repository::ApiKeyRepository;
+use crate::users::domain::users::email_policy::{
+    EmailPolicyRules, EmailPolicyService, EmailPolicyServiceParameters,
+};
+use crate::users::domain::users::email_verification::VerifiedOnlyAction;
+use crate::users::domain::users::email_verification_token_repository::EmailVerificationTokenRepository;
+use crate::users::domain::users::login_failures::LoginThrottlingRules;
+use crate::users::domain::users::login_failures_repository::LoginFailuresRepository;
+use crate::users::domain::users::password_history_repository::PasswordHistoryRepository;
+use crate::users::domain::users::password_policy::PasswordPolicyRules;
+use crate::users::domain::users::password_reset_token_repository::PasswordResetTokenRepository;
+use crate::users::domain::users::refresh_token_repository::RefreshTokenRepository;
+use crate::users::domain::users::two_factor::TwoFactorConfig;
+use crate::users::domain::users::two_factor_challenge_repository::TwoFactorChallengeRepository;
+use crate::users::domain::users::two_factor_repository::TwoFactorRepository;
 use crate::users::domain::users::user_criteria_repository::UserCriteriaRepository;
 use crate::users::domain::users::user_repository::UserRepository;
+use crate::users::domain::users::user_search_repository::UserSearchRepository;
+use crate::users::domain::users::user_session::SessionConfig;
+use crate::users::domain::users::user_session_repository::UserSessionRepository;
+use crate::users::infrastructure::access_token_signer_jwt::{
+    signing_key, AccessTokenSignerJwt, AccessTokenSignerJwtParameters,
+};
+use crate::users::infrastructure::password_hasher_argon2::{
+    Argon2Config, PasswordHasherArgon2, PasswordHasherArgon2Parameters,
+};
+use crate::users::infrastructure::password_policy_zxcvbn::{
+    PasswordPolicyZxcvbn, PasswordPolicyZxcvbnParameters,
+};
+use crate::users::infrastructure::totp_rfc6238::{TotpRfc6238, TotpRfc6238Parameters};
 
 pub trait DatabaseModule:
-    HasComponent<dyn UserRepository> + HasComponent<dyn UserCriteriaRepository>
+    HasComponent<dyn UserRepository>
+    + HasComponent<dyn UserCriteriaRepository>
+    + HasComponent<dyn UserSearchRepository>
+    + HasComponent<dyn PasswordHistoryRepository>
+    + HasComponent<dyn PasswordResetTokenRepository>
+    + HasComponent<dyn EmailVerificationTokenRepository>
+    + HasComponent<dyn LoginFailuresRepository>
+    + HasComponent<dyn UserSessionRepository>
+    + HasComponent<dyn RefreshTokenRepository>
+    + HasComponent<dyn TwoFactorRepository>
+    + HasComponent<dyn TwoFactorChallengeRepository>
+    + HasComponent<dyn ApiKeyRepository>
 {
 }
 
@@ -20,21 +111,193 @@ shaku::module! {
             UserRegisterService,
             UserFindService,
             UserUpdateService,
+            UserReplaceService,
             UserDeleteService,
-            UserCriteriaService
+            UserRestoreService,
+            UserPurgeService,
+            UserChangeStatusService,
+            UserUnlockService,
+            UserCriteriaService,
+            UserSearchService,
+            UserAuthenticateService,
+            UserAuthenticateTokenService,
+            UserStartSessionService,
+            UserRefreshSessionService,
+            UserListSessionsService,
+            UserRevokeSessionsService,
+            UserEnrollTwoFactorService,
+            UserTwoFactorLoginService,
+            UserResetTwoFactorService,
+            UserCreateApiKeyService,
+            UserListApiKeysService,
+            UserRevokeApiKeyService,
+            UserAuthenticateApiKeyService,
+            UserPasswordResetRequestService,
+            UserPasswordResetService,
+            UserEmailVerificationRequestService,
+            UserEmailVerifyService,
+            UserWelcomeService,
+            PasswordPolicyZxcvbn,
+            EmailPolicyService,
+            PasswordHasherArgon2,
+            AccessTokenSignerJwt,
+            TotpRfc6238,
+            MailerFileDrop,
+            EventBusInMemory,
+            ClockSystem
         ],
         providers = [],
 
         use dyn DatabaseModule {
             components = [
                 dyn UserRepository,
-                dyn UserCriteriaRepository
+                dyn UserCriteriaRepository,
+                dyn UserSearchRepository,
+                dyn PasswordHistoryRepository,
+                dyn PasswordResetTokenRepository,
+                dyn EmailVerificationTokenRepository,
+                dyn LoginFailuresRepository,
+                dyn UserSessionRepository,
+                dyn RefreshTokenRepository,
+                dyn TwoFactorRepository,
+                dyn TwoFactorChallengeRepository,
+                dyn ApiKeyRepository
             ],
             providers = [],
         }
     }
 }
 
-pub fn build_container<T: DatabaseModule>(database: T) -> AppContainer {
-    AppContainer::builder(Arc::new(database)).build()
+fn load_banned_passwords(rules: &PasswordPolicyRules) -> HashSet<String> {
+    let Some(file) = &rules.banned_passwords_file else {
+        return HashSet::new();
+    };
+
+    fs::read_to_string(file)
+        .expect("Banned passwords file couldn't be read.")
+        .lines()
+        .map(str::trim)
+        .filter(|password| !password.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn load_disposable_domains(rules: &EmailPolicyRules) -> HashSet<String> {
+    let Some(file) = &rules.disposable_domains_file else {
+        return HashSet::new();
+    };
+
+    fs::read_to_string(file)
+        .expect("Disposable domains file couldn't be read.")
+        .lines()
+        .map(str::trim)
+        .filter(|domain| !domain.is_empty() && !domain.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Mailer replacing the file drop one for the other transports.
+fn mailer_override(config: &MailerConfig) -> Option<Box<dyn Mailer>> {
+    match config.transport {
+        MailTransport::Smtp => Some(Box::new(
+            MailerSmtp::new(config).expect("SMTP mailer couldn't be configured."),
+        )),
+        MailTransport::File => None,
+        MailTransport::Memory => Some(Box::new(MailerInMemory::new())),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn build_container<T: DatabaseModule>(
+    database: T,
+    password_policy: PasswordPolicyRules,
+    email_policy: EmailPolicyRules,
+    password_hashing: Argon2Config,
+    password_reset: PasswordResetConfig,
+    email_verification: EmailVerificationConfig,
+    mailer: MailerConfig,
+    user_deletion: UserDeletionConfig,
+    login_throttling: LoginThrottlingRules,
+    sessions: SessionConfig,
+    two_factor: TwoFactorConfig,
+    api_keys: ApiKeyConfig,
+) -> AppContainer {
+    let verified_only = |action| email_verification.verified_only.contains(&action);
+    let banned_passwords = load_banned_passwords(&password_policy);
+    let disposable_domains = load_disposable_domains(&email_policy);
+
+    let mailer_override = mailer_override(&mailer);
+
+    let builder = AppContainer::builder(Arc::new(database))
+        .with_component_parameters::<PasswordPolicyZxcvbn>(PasswordPolicyZxcvbnParameters {
+            rules: password_policy,
+            banned_passwords,
+        })
+        .with_component_parameters::<EmailPolicyService>(EmailPolicyServiceParameters {
+            rules: email_policy,
+            disposable_domains,
+        })
+        .with_component_parameters::<PasswordHasherArgon2>(PasswordHasherArgon2Parameters {
+            config: password_hashing,
+        })
+        .with_component_parameters::<UserPasswordResetRequestService>(
+            UserPasswordResetRequestServiceParameters {
+                config: password_reset,
+                require_verified_email: verified_only(VerifiedOnlyAction::PasswordReset),
+            },
+        )
+        .with_component_parameters::<UserAuthenticateService>(UserAuthenticateServiceParameters {
+            require_verified_email: verified_only(VerifiedOnlyAction::Login),
+            rules: login_throttling.clone(),
+        })
+        .with_component_parameters::<UserEmailVerificationRequestService>(
+            UserEmailVerificationRequestServiceParameters {
+                config: email_verification,
+            },
+        )
+        .with_component_parameters::<MailerFileDrop>(MailerFileDropParameters { config: mailer })
+        .with_component_parameters::<UserRestoreService>(UserRestoreServiceParameters {
+            config: user_deletion.clone(),
+        })
+        .with_component_parameters::<UserPurgeService>(UserPurgeServiceParameters {
+            config: user_deletion,
+        })
+        .with_component_parameters::<AccessTokenSignerJwt>(AccessTokenSignerJwtParameters {
+            signing_key: signing_key(sessions.signing_key.as_deref()),
+        })
+        .with_component_parameters::<UserStartSessionService>(UserStartSessionServiceParameters {
+            config: sessions.clone(),
+        })
+        .with_component_parameters::<UserRefreshSessionService>(
+            UserRefreshSessionServiceParameters { config: sessions },
+        )
+        .with_component_parameters::<TotpRfc6238>(TotpRfc6238Parameters {
+            issuer: two_factor.issuer.clone(),
+        })
+        .with_component_parameters::<UserEnrollTwoFactorService>(
+            UserEnrollTwoFactorServiceParameters {
+                config: two_factor.clone(),
+            },
+        )
+        .with_component_parameters::<UserTwoFactorLoginService>(
+            UserTwoFactorLoginServiceParameters {
+                config: two_factor,
+                rules: login_throttling,
+            },
+        )
+        .with_component_parameters::<UserCreateApiKeyService>(UserCreateApiKeyServiceParameters {
+            config: api_keys,
+        });
+
+    let container = match mailer_override {
+        Some(mailer) => builder.with_component_override::<dyn Mailer>(mailer),
+        None => builder,
+    }
+    .build();
+
+    let event_bus: &dyn EventBus = container.resolve_ref();
+    let welcome_service: Arc<dyn UserWelcome> = container.resolve();
+    event_bus.subscribe(Arc::new(UserWelcomeSubscriber::new(welcome_service)));
+
+    container
 }
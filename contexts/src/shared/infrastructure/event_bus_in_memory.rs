@@ -0,0 +1,33 @@
+use std::sync::{Arc, RwLock};
+
+use shaku::Component;
+
+use crate::shared::domain::event_bus::{DomainEvent, EventBus, EventBusErrors, EventSubscriber};
+
+/// Dispatches the events synchronously to the subscribers of the process.
+#[derive(Component)]
+#[shaku(interface = EventBus)]
+pub struct EventBusInMemory {
+    #[shaku(default)]
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+}
+
+impl EventBus for EventBusInMemory {
+    fn publish(&self, event: &dyn DomainEvent) -> Result<(), EventBusErrors> {
+        let subscribers = self
+            .subscribers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        subscribers
+            .iter()
+            .try_for_each(|subscriber| subscriber.handle(event))
+    }
+
+    fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(subscriber);
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::Message;
+use serde::Deserialize;
+
+use crate::shared::domain::mailer::{Mail, MailerErrors};
+
+pub mod file_drop;
+pub mod in_memory;
+pub mod smtp;
+
+/// Where the mails go.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTransport {
+    Smtp,
+    /// Dropped as `.eml` files into a directory, meant for development.
+    #[default]
+    File,
+    /// Kept in memory, meant for tests.
+    Memory,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain text connection, only meant for local relays and stand-ins.
+    None,
+    #[default]
+    StartTls,
+    Tls,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Seconds to wait for the server before giving up.
+    pub timeout: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        SmtpConfig {
+            host: "localhost".to_string(),
+            port: 587,
+            security: SmtpSecurity::default(),
+            username: None,
+            password: None,
+            timeout: 10,
+        }
+    }
+}
+
+/// Transport and sender of the outgoing mails, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MailerConfig {
+    pub transport: MailTransport,
+    pub from: String,
+    /// Directory the mails are dropped into by the file transport.
+    pub directory: PathBuf,
+    pub smtp: SmtpConfig,
+}
+
+impl Default for MailerConfig {
+    fn default() -> Self {
+        MailerConfig {
+            transport: MailTransport::default(),
+            from: "no-reply@localhost".to_string(),
+            directory: PathBuf::from("mails"),
+            smtp: SmtpConfig::default(),
+        }
+    }
+}
+
+fn send_error(error: impl Into<anyhow::Error>) -> MailerErrors {
+    MailerErrors::SendError {
+        source: error.into(),
+    }
+}
+
+/// Builds the MIME message of the mail, a multipart alternative when it has an HTML part.
+fn message(from: &str, mail: &Mail) -> Result<Message, MailerErrors> {
+    let builder = Message::builder()
+        .from(from.parse::<Mailbox>().map_err(send_error)?)
+        .to(mail.to.parse::<Mailbox>().map_err(send_error)?)
+        .subject(&mail.subject);
+
+    let message = match &mail.html {
+        Some(html) => builder.multipart(MultiPart::alternative_plain_html(
+            mail.text.clone(),
+            html.clone(),
+        )),
+        None => builder.singlepart(SinglePart::plain(mail.text.clone())),
+    };
+
+    message.map_err(send_error)
+}
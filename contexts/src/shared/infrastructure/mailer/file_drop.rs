@@ -0,0 +1,29 @@
+use std::fs;
+
+use shaku::Component;
+use uuid::Uuid;
+
+use crate::shared::domain::mailer::{Mail, Mailer, MailerErrors};
+use crate::shared::infrastructure::mailer::{message, send_error, MailerConfig};
+
+/// Writes every mail to an `.eml` file instead of delivering it, meant for development.
+#[derive(Component)]
+#[shaku(interface = Mailer)]
+pub struct MailerFileDrop {
+    config: MailerConfig,
+}
+
+impl Mailer for MailerFileDrop {
+    fn send(&self, mail: &Mail) -> Result<(), MailerErrors> {
+        let message = message(&self.config.from, mail)?;
+
+        let file = self
+            .config
+            .directory
+            .join(format!("{}.eml", Uuid::now_v7()));
+
+        fs::create_dir_all(&self.config.directory)
+            .and_then(|_| fs::write(file, message.formatted()))
+            .map_err(send_error)
+    }
+}
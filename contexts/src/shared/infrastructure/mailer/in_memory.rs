@@ -0,0 +1,34 @@
+use std::sync::Mutex;
+
+use crate::shared::domain::mailer::{Mail, Mailer, MailerErrors};
+
+/// Keeps every mail instead of delivering it, so tests can check what would have been sent.
+#[derive(Debug, Default)]
+pub struct MailerInMemory {
+    mails: Mutex<Vec<Mail>>,
+}
+
+impl MailerInMemory {
+    pub fn new() -> Self {
+        MailerInMemory::default()
+    }
+
+    /// Mails sent so far, oldest first.
+    pub fn sent(&self) -> Vec<Mail> {
+        self.mails
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl Mailer for MailerInMemory {
+    fn send(&self, mail: &Mail) -> Result<(), MailerErrors> {
+        self.mails
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(mail.clone());
+
+        Ok(())
+    }
+}
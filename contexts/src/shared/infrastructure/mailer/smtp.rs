@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+
+use crate::shared::domain::mailer::{Mail, Mailer, MailerErrors};
+use crate::shared::infrastructure::mailer::{message, send_error, MailerConfig, SmtpSecurity};
+
+/// Delivers the mails through an SMTP server.
+pub struct MailerSmtp {
+    from: String,
+    transport: SmtpTransport,
+}
+
+impl MailerSmtp {
+    pub fn new(config: &MailerConfig) -> Result<Self, MailerErrors> {
+        let smtp = &config.smtp;
+
+        let builder = match smtp.security {
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&smtp.host),
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&smtp.host).map_err(send_error)?,
+            SmtpSecurity::Tls => SmtpTransport::relay(&smtp.host).map_err(send_error)?,
+        };
+
+        let builder = builder
+            .port(smtp.port)
+            .timeout(Some(Duration::from_secs(smtp.timeout)));
+
+        let builder = match (&smtp.username, &smtp.password) {
+            (Some(username), Some(password)) => {
+                builder.credentials(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => builder,
+        };
+
+        Ok(MailerSmtp {
+            from: config.from.clone(),
+            transport: builder.build(),
+        })
+    }
+}
+
+impl Mailer for MailerSmtp {
+    fn send(&self, mail: &Mail) -> Result<(), MailerErrors> {
+        let message = message(&self.from, mail)?;
+
+        self.transport.send(&message).map_err(send_error)?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,32 @@
+pub mod authenticate;
+pub mod authenticate_api_key;
+pub mod authenticate_token;
+pub mod change_status;
+pub mod create_api_key;
 pub mod criteria;
 pub mod delete;
+pub mod enroll_two_factor;
 pub mod find;
+pub mod list_api_keys;
+pub mod list_sessions;
+pub mod mails;
+mod password_history;
+pub mod purge;
+pub mod refresh_session;
 pub mod register;
+pub mod replace;
+pub mod request_email_verification;
+pub mod request_password_reset;
+pub mod reset_password;
+pub mod reset_two_factor;
+pub mod restore;
+pub mod revoke_api_key;
+pub mod revoke_sessions;
+pub mod search;
+pub mod start_session;
+pub mod two_factor_login;
+mod unique_email;
+pub mod unlock;
 pub mod update;
+pub mod verify_email;
+pub mod welcome;
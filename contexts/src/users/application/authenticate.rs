@@ -0,0 +1,519 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::{EventBus, EventBusErrors};
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::login_failures::{LoginFailures, LoginThrottlingRules};
+use crate::users::domain::users::login_failures_repository::{
+    LoginFailuresRepository, LoginFailuresRepositoryErrors,
+};
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::two_factor_repository::{
+    TwoFactorRepository, TwoFactorRepositoryErrors,
+};
+use crate::users::domain::users::user_events::UserStatusChanged;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::{User, UserErrors};
+
+#[derive(Error, Debug)]
+pub enum UserAuthenticateErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The email or the password are not valid")]
+    InvalidCredentials,
+    #[error("The email of the user has to be verified before logging in")]
+    EmailNotVerified,
+    #[error("The account is {0}, it can't log in")]
+    AccountUnavailable(&'static str),
+    #[error("Too many failed logins from this address, retry in {0} seconds")]
+    TooManyAttempts(u64),
+    #[error("Too many failed logins of this account, retry in {0} seconds")]
+    LockedOut(u64),
+}
+
+impl From<RepositoryErrors> for UserAuthenticateErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserAuthenticateErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserAuthenticateErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<LoginFailuresRepositoryErrors> for UserAuthenticateErrors {
+    fn from(value: LoginFailuresRepositoryErrors) -> Self {
+        match value {
+            LoginFailuresRepositoryErrors::InternalServerError { source } => {
+                UserAuthenticateErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<TwoFactorRepositoryErrors> for UserAuthenticateErrors {
+    fn from(value: TwoFactorRepositoryErrors) -> Self {
+        match value {
+            TwoFactorRepositoryErrors::InternalServerError { source } => {
+                UserAuthenticateErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<EventBusErrors> for UserAuthenticateErrors {
+    fn from(value: EventBusErrors) -> Self {
+        UserAuthenticateErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+impl From<UserErrors> for UserAuthenticateErrors {
+    fn from(value: UserErrors) -> Self {
+        UserAuthenticateErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserAuthenticate: Interface {
+    /// Returns the user with the email when the password matches, rehashing it when the hash was
+    /// made with outdated parameters. Failed logins of the account and from the IP, when known,
+    /// lock them out for a while once there are too many. Failures of users with two-factor
+    /// authentication are only forgiven once they complete the login with a code.
+    fn authenticate(
+        &self,
+        email: &str,
+        password: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<User<'_>, UserAuthenticateErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserAuthenticate)]
+pub struct UserAuthenticateService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[shaku(inject)]
+    login_failures_repository: Arc<dyn LoginFailuresRepository>,
+    #[shaku(inject)]
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    /// Whether users have to verify their email before logging in.
+    require_verified_email: bool,
+    rules: LoginThrottlingRules,
+}
+
+/// Whole seconds to wait, rounded up so retrying right then succeeds.
+pub(crate) fn seconds(duration: Duration) -> u64 {
+    u64::try_from((duration.num_milliseconds() + 999) / 1000).unwrap_or_default()
+}
+
+impl UserAuthenticateService {
+    fn failures(
+        &self,
+        key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<LoginFailures, UserAuthenticateErrors> {
+        Ok(self
+            .login_failures_repository
+            .find_by(key)?
+            .unwrap_or_else(|| LoginFailures::none(key, now)))
+    }
+
+    /// Records a failed login of the account and from the IP, locking the user once it was
+    /// locked out too many times in a row.
+    fn fail(
+        &self,
+        account: Option<LoginFailures>,
+        ip: Option<LoginFailures>,
+        user: Option<User>,
+        now: DateTime<Utc>,
+    ) -> Result<(), UserAuthenticateErrors> {
+        if let Some(ip) = ip {
+            let ip = ip.failed(now, self.rules.ip_max_failures, &self.rules);
+            self.login_failures_repository.save(&ip)?;
+        }
+
+        let Some(account) = account else {
+            return Ok(());
+        };
+
+        let account = account.failed(now, self.rules.account_max_failures, &self.rules);
+        self.login_failures_repository.save(&account)?;
+
+        let locked_out_too_often = self.rules.lock_after_lockouts > 0
+            && account.get_lockouts() >= self.rules.lock_after_lockouts
+            && account.retry_after(now).is_some();
+
+        if let Some(user) = user.filter(|_| locked_out_too_often) {
+            let previous = user.get_status().clone();
+
+            // Users that can't log in anyway are left as they are.
+            if let Ok(user) = user.lock(now, None) {
+                self.user_repository.update(&user)?;
+                self.event_bus
+                    .publish(&UserStatusChanged::new(previous, &user, None))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl UserAuthenticate for UserAuthenticateService {
+    fn authenticate(
+        &self,
+        email: &str,
+        password: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<User<'_>, UserAuthenticateErrors> {
+        let now = self.clock.now();
+
+        // Locked out logins are refused before verifying the password, which is expensive.
+        let ip_failures = match ip {
+            Some(ip) => Some(self.failures(&LoginFailures::ip_key(ip), now)?),
+            None => None,
+        };
+
+        if let Some(retry_after) = ip_failures.as_ref().and_then(|ip| ip.retry_after(now)) {
+            return Err(UserAuthenticateErrors::TooManyAttempts(seconds(
+                retry_after,
+            )));
+        }
+
+        // No user can have an invalid email.
+        let Ok(email) = self.email_policy.parse(email) else {
+            self.fail(None, ip_failures, None, now)?;
+            return Err(UserAuthenticateErrors::InvalidCredentials);
+        };
+
+        let account_failures =
+            self.failures(&LoginFailures::account_key(email.get_normalized()), now)?;
+
+        if let Some(retry_after) = account_failures.retry_after(now) {
+            return Err(UserAuthenticateErrors::LockedOut(seconds(retry_after)));
+        }
+
+        // Deleted users can't log in until they're restored. A password is verified all the same,
+        // so the time taken doesn't tell which emails are registered.
+        let user = self
+            .user_repository
+            .find_by_email(email.get_normalized())?
+            .filter(|user| !user.is_deleted());
+        let Some(user) = user else {
+            self.password_hasher.verify_dummy(password);
+            self.fail(Some(account_failures), ip_failures, None, now)?;
+            return Err(UserAuthenticateErrors::InvalidCredentials);
+        };
+
+        let verified = self
+            .password_hasher
+            .verify(password, user.get_password())
+            .map_err(UserErrors::from)?;
+
+        if !verified {
+            self.fail(Some(account_failures), ip_failures, Some(user), now)?;
+            return Err(UserAuthenticateErrors::InvalidCredentials);
+        }
+
+        // Failures of the account are forgiven once its password is known, unless a code is still
+        // needed so they keep counting the wrong ones. The ones of the IP are only forgotten with
+        // time so it can't clear them logging in to its own account.
+        let two_factor = self
+            .two_factor_repository
+            .find_by(user.get_id())?
+            .is_some_and(|two_factor| two_factor.is_enabled());
+
+        if !two_factor
+            && (account_failures.get_failures() > 0 || account_failures.get_lockouts() > 0)
+        {
+            self.login_failures_repository
+                .delete_by(account_failures.get_key())?;
+        }
+
+        if !user.get_status().allows_login() {
+            return Err(UserAuthenticateErrors::AccountUnavailable(
+                user.get_status().get(),
+            ));
+        }
+
+        if self.require_verified_email && !user.get_email_verification().is_verified() {
+            return Err(UserAuthenticateErrors::EmailNotVerified);
+        }
+
+        let user = user.log_in(now);
+
+        let user = if self.password_hasher.needs_rehash(user.get_password()) {
+            user.rehash_password(password, self.password_hasher.as_ref())?
+        } else {
+            user
+        };
+
+        self.user_repository.update(&user)?;
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::users::domain::users::two_factor::TwoFactor;
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{
+        now, EmailPolicyPlain, Fixture, PasswordHasherCheap, PASSWORD, USER_ID,
+    };
+
+    const IP: Option<IpAddr> = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+
+    fn service(fixture: &Fixture, require_verified_email: bool) -> UserAuthenticateService {
+        service_with_rules(fixture, require_verified_email, rules())
+    }
+
+    fn service_with_rules(
+        fixture: &Fixture,
+        require_verified_email: bool,
+        rules: LoginThrottlingRules,
+    ) -> UserAuthenticateService {
+        UserAuthenticateService {
+            user_repository: fixture.users.clone(),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_hasher: Arc::new(PasswordHasherCheap),
+            login_failures_repository: fixture.login_failures.clone(),
+            two_factor_repository: fixture.two_factors.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+            require_verified_email,
+            rules,
+        }
+    }
+
+    fn rules() -> LoginThrottlingRules {
+        LoginThrottlingRules {
+            window: 15,
+            lockout: 5,
+            max_lockout: 30,
+            account_max_failures: 3,
+            ip_max_failures: 5,
+            lock_after_lockouts: 0,
+        }
+    }
+
+    #[test]
+    fn returns_the_user_with_the_email_and_password() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture, false);
+
+        let user = service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .unwrap();
+
+        assert_eq!(user.get_id(), USER_ID);
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        assert!(matches!(
+            service(&fixture, false).authenticate("jane@example.com", "wrong", None),
+            Err(UserAuthenticateErrors::InvalidCredentials)
+        ));
+    }
+
+    #[test]
+    fn only_lets_verified_emails_in_when_required() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture, true);
+
+        assert!(matches!(
+            service.authenticate("jane@example.com", PASSWORD, None),
+            Err(UserAuthenticateErrors::EmailNotVerified)
+        ));
+
+        fixture
+            .users
+            .update(&fixture.user(USER_ID).verify_email(fixture.clock.now()))
+            .unwrap();
+        assert!(service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn records_the_time_of_the_login() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        fixture.clock.advance(Duration::hours(1));
+
+        service(&fixture, false)
+            .authenticate("jane@example.com", PASSWORD, None)
+            .unwrap();
+
+        let audit = fixture.user(USER_ID).get_audit().clone();
+        assert_eq!(audit.get_last_login_at(), Some(fixture.clock.now()));
+        assert_eq!(audit.get_updated_at(), now());
+    }
+
+    #[test]
+    fn rejects_users_that_cant_log_in() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Locked);
+
+        assert!(matches!(
+            service(&fixture, false).authenticate("jane@example.com", PASSWORD, None),
+            Err(UserAuthenticateErrors::AccountUnavailable(
+                UserStatus::LOCKED
+            ))
+        ));
+    }
+
+    #[test]
+    fn locks_the_account_out_after_too_many_failures() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture, false);
+
+        for _ in 0..3 {
+            assert!(matches!(
+                service.authenticate("jane@example.com", "wrong", IP),
+                Err(UserAuthenticateErrors::InvalidCredentials)
+            ));
+        }
+
+        fixture.clock.advance(Duration::minutes(1));
+        assert!(matches!(
+            service.authenticate("jane@example.com", PASSWORD, IP),
+            Err(UserAuthenticateErrors::LockedOut(240))
+        ));
+
+        fixture.clock.advance(Duration::minutes(4));
+        assert!(service
+            .authenticate("jane@example.com", PASSWORD, IP)
+            .is_ok());
+    }
+
+    #[test]
+    fn counts_unknown_emails_like_registered_ones() {
+        let fixture = Fixture::default();
+        let service = service(&fixture, false);
+
+        for _ in 0..3 {
+            let _ = service.authenticate("john@example.com", PASSWORD, None);
+        }
+
+        assert!(matches!(
+            service.authenticate("john@example.com", PASSWORD, None),
+            Err(UserAuthenticateErrors::LockedOut(300))
+        ));
+    }
+
+    #[test]
+    fn locks_the_ip_out_across_accounts() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture, false);
+
+        for email in ["a", "b", "c", "d", "e"].map(|name| format!("{name}@example.com")) {
+            let _ = service.authenticate(&email, "wrong", IP);
+        }
+
+        assert!(matches!(
+            service.authenticate("jane@example.com", PASSWORD, IP),
+            Err(UserAuthenticateErrors::TooManyAttempts(300))
+        ));
+        assert!(service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn forgives_the_failures_of_the_account_on_success() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture, false);
+
+        for _ in 0..2 {
+            let _ = service.authenticate("jane@example.com", "wrong", None);
+        }
+        service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .unwrap();
+        for _ in 0..2 {
+            let _ = service.authenticate("jane@example.com", "wrong", None);
+        }
+
+        assert!(service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn keeps_the_failures_of_users_with_two_factor_until_they_send_a_code() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let (two_factor, _) = TwoFactor::enroll(USER_ID, "JBSWY3DPEHPK3PXP").confirm(1, 0, now());
+        fixture.two_factors.save(&two_factor).unwrap();
+        let service = service(&fixture, false);
+
+        for _ in 0..2 {
+            let _ = service.authenticate("jane@example.com", "wrong", None);
+        }
+        service
+            .authenticate("jane@example.com", PASSWORD, None)
+            .unwrap();
+        let _ = service.authenticate("jane@example.com", "wrong", None);
+
+        assert!(matches!(
+            service.authenticate("jane@example.com", PASSWORD, None),
+            Err(UserAuthenticateErrors::LockedOut(_))
+        ));
+    }
+
+    #[test]
+    fn locks_the_user_after_too_many_lockouts_in_a_row_when_configured() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let rules = LoginThrottlingRules {
+            lock_after_lockouts: 2,
+            ..rules()
+        };
+        let service = service_with_rules(&fixture, false, rules);
+
+        for _ in 0..3 {
+            let _ = service.authenticate("jane@example.com", "wrong", None);
+        }
+        assert_eq!(fixture.user(USER_ID).get_status(), &UserStatus::Active);
+
+        fixture.clock.advance(Duration::minutes(5));
+        for _ in 0..3 {
+            let _ = service.authenticate("jane@example.com", "wrong", None);
+        }
+
+        assert_eq!(fixture.user(USER_ID).get_status(), &UserStatus::Locked);
+        let changes = fixture.event_bus.status_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].changed_by, None);
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::api_key::ApiKey;
+use crate::users::domain::users::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryErrors};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::UserRepository;
+
+#[derive(Error, Debug)]
+pub enum UserAuthenticateApiKeyErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The API key is unknown, expired or revoked")]
+    InvalidKey,
+    #[error("The account is {0}, its API keys can't be used")]
+    AccountUnavailable(&'static str),
+}
+
+impl From<ApiKeyRepositoryErrors> for UserAuthenticateApiKeyErrors {
+    fn from(value: ApiKeyRepositoryErrors) -> Self {
+        match value {
+            ApiKeyRepositoryErrors::InternalServerError { source } => {
+                UserAuthenticateApiKeyErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserAuthenticateApiKey: Interface {
+    /// Returns the key when it's genuine, active and its user can still log in, recording it
+    /// was used unless it already was in the last minute.
+    fn authenticate(&self, value: &str) -> Result<ApiKey, UserAuthenticateApiKeyErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserAuthenticateApiKey)]
+pub struct UserAuthenticateApiKeyService {
+    #[shaku(inject)]
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserAuthenticateApiKey for UserAuthenticateApiKeyService {
+    fn authenticate(&self, value: &str) -> Result<ApiKey, UserAuthenticateApiKeyErrors> {
+        let now = self.clock.now();
+
+        let (prefix, secret) =
+            ApiKey::parse(value).ok_or(UserAuthenticateApiKeyErrors::InvalidKey)?;
+        let api_key = self
+            .api_key_repository
+            .find_by_prefix(prefix)?
+            .filter(|api_key| api_key.matches(secret) && api_key.is_active(now))
+            .ok_or(UserAuthenticateApiKeyErrors::InvalidKey)?;
+
+        // Keys act as their user, so they stop working along with its logins.
+        let id = UserID::try_from(api_key.get_user_id())
+            .map_err(|_| UserAuthenticateApiKeyErrors::InvalidKey)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .filter(|user| !user.is_deleted())
+            .ok_or(UserAuthenticateApiKeyErrors::InvalidKey)?;
+
+        if !user.get_status().allows_login() {
+            return Err(UserAuthenticateApiKeyErrors::AccountUnavailable(
+                user.get_status().get(),
+            ));
+        }
+
+        if api_key.is_use_recorded(now) {
+            return Ok(api_key);
+        }
+
+        let api_key = api_key.used(now);
+
+        self.api_key_repository.update(&api_key)?;
+
+        Ok(api_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{now, Fixture, USER_ID};
+
+    fn service(fixture: &Fixture) -> UserAuthenticateApiKeyService {
+        UserAuthenticateApiKeyService {
+            api_key_repository: fixture.api_keys.clone(),
+            user_repository: fixture.users.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    /// Saves a key of the user lasting 90 days, returning it along with its value.
+    fn api_key(fixture: &Fixture) -> (ApiKey, String) {
+        let (api_key, value) =
+            ApiKey::create(USER_ID, "CI", &["read"], Duration::days(90), now()).unwrap();
+        fixture.api_keys.save(&api_key).unwrap();
+
+        (api_key, value)
+    }
+
+    #[test]
+    fn accepts_keys_found_by_their_prefix_and_records_their_use() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (api_key, value) = api_key(&fixture);
+
+        let authenticated = service(&fixture).authenticate(&value).unwrap();
+
+        assert_eq!(authenticated.get_id(), api_key.get_id());
+        let saved = fixture.api_keys.find_by(api_key.get_id()).unwrap().unwrap();
+        assert_eq!(saved.get_last_used_at(), Some(now()));
+    }
+
+    #[test]
+    fn refuses_wrong_secrets_and_unknown_prefixes() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (api_key, value) = api_key(&fixture);
+        let (prefix, secret) = ApiKey::parse(&value).unwrap();
+
+        for value in [
+            "",
+            "malformed",
+            &format!("{prefix}.wrong-secret"),
+            &format!("000000000000.{secret}"),
+        ] {
+            let result = service(&fixture).authenticate(value);
+
+            assert!(matches!(
+                result,
+                Err(UserAuthenticateApiKeyErrors::InvalidKey)
+            ));
+        }
+
+        let saved = fixture.api_keys.find_by(api_key.get_id()).unwrap().unwrap();
+        assert_eq!(saved.get_last_used_at(), None);
+    }
+
+    #[test]
+    fn refuses_expired_keys() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (_, value) = api_key(&fixture);
+        fixture.clock.advance(Duration::days(90));
+
+        let result = service(&fixture).authenticate(&value);
+
+        assert!(matches!(
+            result,
+            Err(UserAuthenticateApiKeyErrors::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn refuses_revoked_keys() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (api_key, value) = api_key(&fixture);
+        fixture.api_keys.update(&api_key.revoke(now())).unwrap();
+
+        let result = service(&fixture).authenticate(&value);
+
+        assert!(matches!(
+            result,
+            Err(UserAuthenticateApiKeyErrors::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn refuses_keys_of_users_who_cant_log_in() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Locked);
+        let (_, value) = api_key(&fixture);
+
+        let result = service(&fixture).authenticate(&value);
+
+        assert!(matches!(
+            result,
+            Err(UserAuthenticateApiKeyErrors::AccountUnavailable("locked"))
+        ));
+    }
+
+    #[test]
+    fn records_uses_at_most_once_a_minute() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (api_key, value) = api_key(&fixture);
+        let service = service(&fixture);
+        service.authenticate(&value).unwrap();
+
+        fixture.clock.advance(Duration::seconds(59));
+        service.authenticate(&value).unwrap();
+        let saved = fixture.api_keys.find_by(api_key.get_id()).unwrap().unwrap();
+        assert_eq!(saved.get_last_used_at(), Some(now()));
+
+        fixture.clock.advance(Duration::seconds(1));
+        service.authenticate(&value).unwrap();
+        let saved = fixture.api_keys.find_by(api_key.get_id()).unwrap().unwrap();
+        assert_eq!(
+            saved.get_last_used_at(),
+            Some(now() + Duration::seconds(60))
+        );
+    }
+}
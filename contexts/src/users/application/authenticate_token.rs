@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::access_token::{
+    AccessToken, AccessTokenErrors, AccessTokenSigner,
+};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+#[derive(Error, Debug)]
+pub enum UserAuthenticateTokenErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("{0}")]
+    InvalidToken(AccessTokenErrors),
+    #[error("The session of the access token was revoked or expired")]
+    SessionEnded,
+}
+
+impl From<UserSessionRepositoryErrors> for UserAuthenticateTokenErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserAuthenticateTokenErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<AccessTokenErrors> for UserAuthenticateTokenErrors {
+    fn from(value: AccessTokenErrors) -> Self {
+        match value {
+            AccessTokenErrors::InternalServerError { source } => {
+                UserAuthenticateTokenErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserAuthenticateTokenErrors::InvalidToken(value),
+        }
+    }
+}
+
+pub trait UserAuthenticateToken: Interface {
+    /// Returns the access token when it's genuine, unexpired and its session is still active,
+    /// so revoking a session logs it out right away.
+    fn authenticate(&self, access_token: &str) -> Result<AccessToken, UserAuthenticateTokenErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserAuthenticateToken)]
+pub struct UserAuthenticateTokenService {
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    access_token_signer: Arc<dyn AccessTokenSigner>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserAuthenticateToken for UserAuthenticateTokenService {
+    fn authenticate(&self, access_token: &str) -> Result<AccessToken, UserAuthenticateTokenErrors> {
+        let access_token = self.access_token_signer.verify(access_token)?;
+        let now = self.clock.now();
+
+        if access_token.is_expired(now) {
+            return Err(AccessTokenErrors::Expired.into());
+        }
+
+        let active = self
+            .user_session_repository
+            .find_by(access_token.get_session_id())?
+            .is_some_and(|session| {
+                session.is_active(now) && session.get_user_id() == access_token.get_user_id()
+            });
+
+        if !active {
+            return Err(UserAuthenticateTokenErrors::SessionEnded);
+        }
+
+        Ok(access_token)
+    }
+}
@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::{EventBus, EventBusErrors};
+use crate::users::domain::users::user_events::UserStatusChanged;
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+use crate::users::domain::users::UserErrors;
+
+/// Transition of the status of a user driven by an administrator.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UserStatusChange<'a> {
+    Suspend { reason: &'a str },
+    Reactivate,
+    Lock,
+    Close,
+}
+
+#[derive(Error, Debug)]
+pub enum UserChangeStatusErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("User not found")]
+    NotFound,
+}
+
+impl From<RepositoryErrors> for UserChangeStatusErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserChangeStatusErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserChangeStatusErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<UserSessionRepositoryErrors> for UserChangeStatusErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserChangeStatusErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<EventBusErrors> for UserChangeStatusErrors {
+    fn from(value: EventBusErrors) -> Self {
+        UserChangeStatusErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserChangeStatus: Interface {
+    /// Moves the user to another status, when the transition is allowed from its current one. The
+    /// actor is the id of the user making the change, missing when it isn't a known user.
+    fn change_status(
+        &self,
+        id: &str,
+        change: UserStatusChange,
+        actor: Option<&str>,
+    ) -> Result<(), UserChangeStatusErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserChangeStatus)]
+pub struct UserChangeStatusService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserChangeStatus for UserChangeStatusService {
+    fn change_status(
+        &self,
+        id: &str,
+        change: UserStatusChange,
+        actor: Option<&str>,
+    ) -> Result<(), UserChangeStatusErrors> {
+        let id = UserID::try_from(id).map_err(UserErrors::from)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .ok_or(UserChangeStatusErrors::NotFound)?;
+
+        let previous = user.get_status().clone();
+        let now = self.clock.now();
+
+        let user = match change {
+            UserStatusChange::Suspend { reason } => user.suspend(reason, now, actor)?,
+            UserStatusChange::Reactivate => user.reactivate(now, actor)?,
+            UserStatusChange::Lock => user.lock(now, actor)?,
+            UserStatusChange::Close => user.close(now, actor)?,
+        };
+
+        self.user_repository.update(&user)?;
+
+        // Users that can't log in anymore are logged out everywhere.
+        if !user.get_status().allows_login() {
+            self.user_session_repository
+                .revoke_by_user(user.get_id(), now)?;
+        }
+
+        self.event_bus
+            .publish(&UserStatusChanged::new(previous, &user, actor))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::user_session::{SessionConfig, UserSession};
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    const ADMIN_ID: &str = "01a153b2-0000-7000-8000-000000000001";
+
+    fn service(fixture: &Fixture) -> UserChangeStatusService {
+        UserChangeStatusService {
+            user_repository: fixture.users.clone(),
+            user_session_repository: fixture.sessions.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    /// Fixture with a logged in user in the status, changed an hour ago.
+    fn fixture(status: UserStatus) -> Fixture {
+        let fixture = Fixture::default().with_user_in(USER_ID, "jane@example.com", status);
+        let session = UserSession::start(
+            USER_ID,
+            None,
+            None,
+            SessionConfig::default().refresh_token_lifetime(),
+            fixture.clock.now(),
+        );
+        fixture.sessions.save(&session).unwrap();
+        fixture.clock.advance(Duration::hours(1));
+
+        fixture
+    }
+
+    fn sessions(fixture: &Fixture) -> usize {
+        fixture
+            .sessions
+            .find_active_by_user(USER_ID, fixture.clock.now())
+            .unwrap()
+            .len()
+    }
+
+    #[test]
+    fn audits_who_changed_the_status_and_when() {
+        let fixture = fixture(UserStatus::Active);
+
+        service(&fixture)
+            .change_status(
+                USER_ID,
+                UserStatusChange::Suspend { reason: "Spam" },
+                Some(ADMIN_ID),
+            )
+            .unwrap();
+
+        let user = fixture.user(USER_ID);
+        assert_eq!(
+            user.get_status(),
+            &UserStatus::Suspended {
+                reason: "Spam".to_owned()
+            }
+        );
+        assert_eq!(user.get_audit().get_updated_at(), fixture.clock.now());
+        assert_eq!(user.get_audit().get_updated_by(), Some(ADMIN_ID));
+
+        let changes = fixture.event_bus.status_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, UserStatus::Active);
+        assert_eq!(changes[0].changed_by.as_deref(), Some(ADMIN_ID));
+        assert_eq!(sessions(&fixture), 0);
+    }
+
+    #[test]
+    fn reactivates_locked_users() {
+        let fixture = fixture(UserStatus::Locked);
+
+        service(&fixture)
+            .change_status(USER_ID, UserStatusChange::Reactivate, None)
+            .unwrap();
+
+        let user = fixture.user(USER_ID);
+        assert_eq!(user.get_status(), &UserStatus::Active);
+        assert_eq!(user.get_audit().get_updated_by(), None);
+        assert_eq!(sessions(&fixture), 1);
+    }
+
+    #[test]
+    fn refuses_transitions_not_allowed_from_the_current_status() {
+        let fixture = fixture(UserStatus::Closed);
+
+        let result =
+            service(&fixture).change_status(USER_ID, UserStatusChange::Reactivate, Some(ADMIN_ID));
+
+        assert!(matches!(
+            result,
+            Err(UserChangeStatusErrors::UserError { .. })
+        ));
+        assert_eq!(fixture.user(USER_ID).get_status(), &UserStatus::Closed);
+        assert!(fixture.event_bus.status_changes().is_empty());
+    }
+
+    #[test]
+    fn fails_for_unknown_users() {
+        let fixture = fixture(UserStatus::Active);
+
+        let result = service(&fixture).change_status(
+            "01a153b2-0000-7000-8000-000000000003",
+            UserStatusChange::Lock,
+            Some(ADMIN_ID),
+        );
+
+        assert!(matches!(result, Err(UserChangeStatusErrors::NotFound)));
+    }
+}
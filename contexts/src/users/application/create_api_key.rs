@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::api_key::{ApiKey, ApiKeyConfig, ApiKeyErrors};
+use crate::users::domain::users::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryErrors};
+
+#[derive(Error, Debug)]
+pub enum UserCreateApiKeyErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("API key validation error")]
+    ApiKeyError {
+        #[from]
+        source: ApiKeyErrors,
+    },
+}
+
+impl From<ApiKeyRepositoryErrors> for UserCreateApiKeyErrors {
+    fn from(value: ApiKeyRepositoryErrors) -> Self {
+        match value {
+            ApiKeyRepositoryErrors::InternalServerError { source } => {
+                UserCreateApiKeyErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserCreateApiKey: Interface {
+    /// Creates a named key for the user with the scopes, lasting the days asked for or the
+    /// default lifetime. Returns it along with its value, which is only ever shown this once.
+    fn create(
+        &self,
+        user_id: &str,
+        name: &str,
+        scopes: &[&str],
+        lifetime: Option<u32>,
+    ) -> Result<(ApiKey, String), UserCreateApiKeyErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserCreateApiKey)]
+pub struct UserCreateApiKeyService {
+    #[shaku(inject)]
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: ApiKeyConfig,
+}
+
+impl UserCreateApiKey for UserCreateApiKeyService {
+    fn create(
+        &self,
+        user_id: &str,
+        name: &str,
+        scopes: &[&str],
+        lifetime: Option<u32>,
+    ) -> Result<(ApiKey, String), UserCreateApiKeyErrors> {
+        let lifetime = self.config.lifetime(lifetime)?;
+        let (api_key, value) = ApiKey::create(user_id, name, scopes, lifetime, self.clock.now())?;
+
+        self.api_key_repository.save(&api_key)?;
+
+        Ok((api_key, value))
+    }
+}
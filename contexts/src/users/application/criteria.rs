@@ -16,6 +16,8 @@ pub enum UserCriteriaErrors {
     },
     #[error("The field {0} don't exist for user")]
     FieldNotFound(String),
+    #[error("The value {0} isn't an RFC 3339 timestamp nor a date")]
+    InvalidTimestamp(String),
 }
 
 impl From<CriteriaRepositoryErrors> for UserCriteriaErrors {
@@ -29,6 +31,9 @@ impl From<CriteriaRepositoryErrors> for UserCriteriaErrors {
             CriteriaRepositoryErrors::FieldNotFound(field) => {
                 UserCriteriaErrors::FieldNotFound(field)
             }
+            CriteriaRepositoryErrors::InvalidTimestamp(value) => {
+                UserCriteriaErrors::InvalidTimestamp(value)
+            }
         }
     }
 }
@@ -36,7 +41,8 @@ impl From<CriteriaRepositoryErrors> for UserCriteriaErrors {
 pub type Result<T> = std::result::Result<T, UserCriteriaErrors>;
 
 pub trait UserCriteria: Interface {
-    fn find_by(&self, criteria: &Criteria) -> Result<Vec<User>>;
+    /// Finds the users matching the criteria, leaving out the deleted ones unless included.
+    fn find_by(&self, criteria: &Criteria, include_deleted: bool) -> Result<Vec<User<'_>>>;
 }
 
 #[derive(Component)]
@@ -47,7 +53,7 @@ pub struct UserCriteriaService {
 }
 
 impl UserCriteria for UserCriteriaService {
-    fn find_by(&self, criteria: &Criteria) -> Result<Vec<User>> {
-        Ok(self.user_repository.find_by(criteria)?)
+    fn find_by(&self, criteria: &Criteria, include_deleted: bool) -> Result<Vec<User<'_>>> {
+        Ok(self.user_repository.find_by(criteria, include_deleted)?)
     }
 }
@@ -1,10 +1,38 @@
 use std::sync::Arc;
 
+use serde::Deserialize;
 use shaku::{Component, Interface};
 use thiserror::Error;
+use crate::shared::domain::clock::Clock;
 use crate::users::domain::users::user_id::{UserID, UserIDErrors};
 
 use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+/// How long deleted users are kept, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UserDeletionConfig {
+    /// Days a deleted user can be restored for.
+    pub restore_period: u32,
+    /// Days a deleted user is kept before being purged, counted from its deletion. A retention
+    /// shorter than the restore period cuts it short.
+    pub retention: u32,
+    /// Minutes between two purges of the users kept past their retention.
+    pub purge_interval: u32,
+}
+
+impl Default for UserDeletionConfig {
+    fn default() -> Self {
+        UserDeletionConfig {
+            restore_period: 30,
+            retention: 30,
+            purge_interval: 60,
+        }
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum UserDeleteErrors {
@@ -18,6 +46,8 @@ pub enum UserDeleteErrors {
         #[from]
         source: UserIDErrors,
     },
+    #[error("User not found")]
+    NotFound,
 }
 
 impl From<RepositoryErrors> for UserDeleteErrors {
@@ -33,8 +63,22 @@ impl From<RepositoryErrors> for UserDeleteErrors {
     }
 }
 
+impl From<UserSessionRepositoryErrors> for UserDeleteErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserDeleteErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
 pub trait UserDelete: Interface {
-    fn delete_by(&self, id: &str) -> Result<(), UserDeleteErrors>;
+    /// Deletes the user, which can be restored until its restore period is over. The actor is the
+    /// id of the user deleting it, missing when it isn't a known user.
+    fn delete_by(&self, id: &str, actor: Option<&str>) -> Result<(), UserDeleteErrors>;
 }
 
 #[derive(Component)]
@@ -42,12 +86,91 @@ pub trait UserDelete: Interface {
 pub struct UserDeleteService {
     #[shaku(inject)]
     user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
 }
 
 impl UserDelete for UserDeleteService {
-    fn delete_by(&self, id: &str) -> Result<(), UserDeleteErrors> {
-        self.user_repository.delete_by(&UserID::try_from(id)?)?;
+    fn delete_by(&self, id: &str, actor: Option<&str>) -> Result<(), UserDeleteErrors> {
+        let user = self
+            .user_repository
+            .find_by(&UserID::try_from(id)?)
+            .ok_or(UserDeleteErrors::NotFound)?;
+
+        let now = self.clock.now();
+
+        let user = user.delete(now, actor);
+
+        self.user_repository.update(&user)?;
+
+        // Deleted users are logged out everywhere, restoring them doesn't log them back in.
+        self.user_session_repository
+            .revoke_by_user(user.get_id(), now)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::domain::users::user_session::{SessionConfig, UserSession};
+    use crate::users::infrastructure::in_memory::{now, Fixture, USER_ID};
+
+    const ADMIN_ID: &str = "01a153b2-0000-7000-8000-000000000001";
+
+    fn service(fixture: &Fixture) -> UserDeleteService {
+        UserDeleteService {
+            user_repository: fixture.users.clone(),
+            user_session_repository: fixture.sessions.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    #[test]
+    fn keeps_the_deleted_user_out_of_sight() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let id = UserID::try_from(USER_ID).unwrap();
+        let session = UserSession::start(
+            USER_ID,
+            None,
+            None,
+            SessionConfig::default().refresh_token_lifetime(),
+            now(),
+        );
+        fixture.sessions.save(&session).unwrap();
+
+        service(&fixture)
+            .delete_by(USER_ID, Some(ADMIN_ID))
+            .unwrap();
+
+        assert!(fixture.users.find_by(&id).is_none());
+        let user = fixture.users.find_deleted_by(&id).unwrap().unwrap();
+        assert_eq!(user.get_audit().get_deleted_at(), Some(now()));
+        assert_eq!(user.get_audit().get_updated_by(), Some(ADMIN_ID));
+        assert!(fixture
+            .sessions
+            .find_active_by_user(USER_ID, now())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_and_deleted_users() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service(&fixture);
+
+        assert!(matches!(
+            service.delete_by("01a153b2-0000-7000-8000-000000000003", None),
+            Err(UserDeleteErrors::NotFound)
+        ));
+
+        service.delete_by(USER_ID, None).unwrap();
+        assert!(matches!(
+            service.delete_by(USER_ID, None),
+            Err(UserDeleteErrors::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::totp::{Totp, TotpErrors};
+use crate::users::domain::users::two_factor::{TwoFactor, TwoFactorConfig};
+use crate::users::domain::users::two_factor_repository::{
+    TwoFactorRepository, TwoFactorRepositoryErrors,
+};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::UserRepository;
+use crate::users::domain::users::UserErrors;
+
+#[derive(Error, Debug)]
+pub enum UserEnrollTwoFactorErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("User not found")]
+    NotFound,
+    #[error("Two-factor authentication is already enabled, it has to be reset first")]
+    AlreadyEnabled,
+    #[error("No two-factor enrollment is waiting for confirmation")]
+    NotPending,
+    #[error("The code isn't valid")]
+    InvalidCode,
+}
+
+impl From<TwoFactorRepositoryErrors> for UserEnrollTwoFactorErrors {
+    fn from(value: TwoFactorRepositoryErrors) -> Self {
+        match value {
+            TwoFactorRepositoryErrors::InternalServerError { source } => {
+                UserEnrollTwoFactorErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<TotpErrors> for UserEnrollTwoFactorErrors {
+    fn from(value: TotpErrors) -> Self {
+        UserEnrollTwoFactorErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+/// Secret to register in an authenticator app, either typed or through the provisioning URI.
+#[derive(Debug, Clone)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+pub trait UserEnrollTwoFactor: Interface {
+    /// Generates a new secret for the user, replacing the enrollment waiting for confirmation if
+    /// there is one. Logging in doesn't need a code until it's confirmed.
+    fn enroll(&self, user_id: &str) -> Result<TwoFactorEnrollment, UserEnrollTwoFactorErrors>;
+    /// Enables two-factor authentication once the user proves its authenticator app generates
+    /// the codes, returning the recovery codes which are only ever shown this once.
+    fn confirm(&self, user_id: &str, code: &str) -> Result<Vec<String>, UserEnrollTwoFactorErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserEnrollTwoFactor)]
+pub struct UserEnrollTwoFactorService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    #[shaku(inject)]
+    totp: Arc<dyn Totp>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: TwoFactorConfig,
+}
+
+impl UserEnrollTwoFactor for UserEnrollTwoFactorService {
+    fn enroll(&self, user_id: &str) -> Result<TwoFactorEnrollment, UserEnrollTwoFactorErrors> {
+        let id = UserID::try_from(user_id).map_err(UserErrors::from)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .ok_or(UserEnrollTwoFactorErrors::NotFound)?;
+
+        let enrolled = self.two_factor_repository.find_by(user.get_id())?;
+        if enrolled.is_some_and(|two_factor| two_factor.is_enabled()) {
+            return Err(UserEnrollTwoFactorErrors::AlreadyEnabled);
+        }
+
+        let secret = self.totp.generate_secret();
+        let provisioning_uri = self.totp.provisioning_uri(&secret, user.get_email())?;
+
+        self.two_factor_repository
+            .save(&TwoFactor::enroll(user.get_id(), &secret))?;
+
+        Ok(TwoFactorEnrollment {
+            secret,
+            provisioning_uri,
+        })
+    }
+
+    fn confirm(&self, user_id: &str, code: &str) -> Result<Vec<String>, UserEnrollTwoFactorErrors> {
+        let two_factor = self
+            .two_factor_repository
+            .find_by(user_id)?
+            .filter(|two_factor| !two_factor.is_enabled())
+            .ok_or(UserEnrollTwoFactorErrors::NotPending)?;
+
+        let now = self.clock.now();
+        let step = self
+            .totp
+            .verify(two_factor.get_secret(), code, now)?
+            .ok_or(UserEnrollTwoFactorErrors::InvalidCode)?;
+
+        let (two_factor, recovery_codes) =
+            two_factor.confirm(step, self.config.recovery_codes, now);
+
+        self.two_factor_repository.save(&two_factor)?;
+
+        Ok(recovery_codes)
+    }
+}
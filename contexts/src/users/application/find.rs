@@ -35,8 +35,9 @@ impl From<RepositoryErrors> for UserFindErrors {
 }
 
 pub trait UserFind: Interface {
-    fn find_by(&self, id: &str) -> Result<Option<User>, UserFindErrors>;
-    fn get_all(&self) -> Vec<User>;
+    fn find_by(&self, id: &str) -> Result<Option<User<'_>>, UserFindErrors>;
+    /// Returns every user, leaving out the deleted ones unless included.
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>>;
 }
 
 #[derive(Component)]
@@ -47,11 +48,11 @@ pub struct UserFindService {
 }
 
 impl UserFind for UserFindService {
-    fn find_by(&self, id: &str) -> Result<Option<User>, UserFindErrors> {
+    fn find_by(&self, id: &str) -> Result<Option<User<'_>>, UserFindErrors> {
         Ok(self.user_repository.find_by(&UserID::try_from(id)?))
     }
 
-    fn get_all(&self) -> Vec<User> {
-        self.user_repository.get_all()
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>> {
+        self.user_repository.get_all(include_deleted)
     }
 }
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::api_key::ApiKey;
+use crate::users::domain::users::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryErrors};
+
+#[derive(Error, Debug)]
+pub enum UserListApiKeysErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+}
+
+impl From<ApiKeyRepositoryErrors> for UserListApiKeysErrors {
+    fn from(value: ApiKeyRepositoryErrors) -> Self {
+        match value {
+            ApiKeyRepositoryErrors::InternalServerError { source } => {
+                UserListApiKeysErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserListApiKeys: Interface {
+    /// Keys of the user that weren't revoked nor expired, the latest created first.
+    fn list(&self, user_id: &str) -> Result<Vec<ApiKey>, UserListApiKeysErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserListApiKeys)]
+pub struct UserListApiKeysService {
+    #[shaku(inject)]
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserListApiKeys for UserListApiKeysService {
+    fn list(&self, user_id: &str) -> Result<Vec<ApiKey>, UserListApiKeysErrors> {
+        Ok(self
+            .api_key_repository
+            .find_active_by_user(user_id, self.clock.now())?)
+    }
+}
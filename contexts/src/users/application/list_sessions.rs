@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::user_session::UserSession;
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+#[derive(Error, Debug)]
+pub enum UserListSessionsErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+}
+
+impl From<UserSessionRepositoryErrors> for UserListSessionsErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserListSessionsErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserListSessions: Interface {
+    /// Active sessions of the user, the most recently used first.
+    fn list(&self, user_id: &str) -> Result<Vec<UserSession>, UserListSessionsErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserListSessions)]
+pub struct UserListSessionsService {
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserListSessions for UserListSessionsService {
+    fn list(&self, user_id: &str) -> Result<Vec<UserSession>, UserListSessionsErrors> {
+        Ok(self
+            .user_session_repository
+            .find_active_by_user(user_id, self.clock.now())?)
+    }
+}
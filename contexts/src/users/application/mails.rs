@@ -0,0 +1,57 @@
+//! Mails sent to the users, rendered with the values named by their placeholders.
+
+use crate::shared::domain::mail_template::MailTemplate;
+
+/// Values: `name`.
+pub const WELCOME: MailTemplate = MailTemplate {
+    subject: "Welcome, {{ name }}",
+    text: "Hi {{ name }},
+
+Your account is ready, thanks for signing up.
+",
+    html: Some(
+        "<p>Hi {{ name }},</p>
+<p>Your account is ready, thanks for signing up.</p>
+",
+    ),
+};
+
+/// Values: `name`, `link` and `lifetime` in minutes.
+pub const EMAIL_VERIFICATION: MailTemplate = MailTemplate {
+    subject: "Verify your email",
+    text: "Hi {{ name }},
+
+Use the following link to confirm this is your email, it expires in {{ lifetime }} minutes:
+
+{{ link }}
+
+If you didn't sign up, ignore this mail.
+",
+    html: Some(
+        "<p>Hi {{ name }},</p>
+<p>Use the following link to confirm this is your email, it expires in {{ lifetime }} minutes:</p>
+<p><a href=\"{{ link }}\">Verify my email</a></p>
+<p>If you didn't sign up, ignore this mail.</p>
+",
+    ),
+};
+
+/// Values: `name`, `link` and `lifetime` in minutes.
+pub const PASSWORD_RESET: MailTemplate = MailTemplate {
+    subject: "Reset your password",
+    text: "Hi {{ name }},
+
+Use the following link to choose a new password, it expires in {{ lifetime }} minutes:
+
+{{ link }}
+
+If you didn't ask to reset your password, ignore this mail.
+",
+    html: Some(
+        "<p>Hi {{ name }},</p>
+<p>Use the following link to choose a new password, it expires in {{ lifetime }} minutes:</p>
+<p><a href=\"{{ link }}\">Reset my password</a></p>
+<p>If you didn't ask to reset your password, ignore this mail.</p>
+",
+    ),
+};
@@ -0,0 +1,30 @@
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors,
+};
+use crate::users::domain::users::user_password::UserPasswordErrors;
+use crate::users::domain::users::UserErrors;
+
+/// Rejects a plain text password matching any of the last `history_size` ones of the user.
+pub(crate) fn ensure_not_reused<E>(
+    history: &dyn PasswordHistoryRepository,
+    hasher: &dyn PasswordHasher,
+    user_id: &str,
+    password: &str,
+    history_size: usize,
+) -> Result<(), E>
+where
+    E: From<PasswordHistoryRepositoryErrors> + From<UserErrors>,
+{
+    if history_size == 0 {
+        return Ok(());
+    }
+
+    for hash in history.find_last(user_id, history_size)? {
+        if hasher.verify(password, &hash).map_err(UserErrors::from)? {
+            return Err(UserErrors::from(UserPasswordErrors::Reused(history_size)).into());
+        }
+    }
+
+    Ok(())
+}
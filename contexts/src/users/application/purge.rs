@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::application::delete::UserDeletionConfig;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+
+#[derive(Error, Debug)]
+pub enum UserPurgeErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+}
+
+impl From<RepositoryErrors> for UserPurgeErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserPurgeErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserPurgeErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+pub trait UserPurge: Interface {
+    /// Removes for good the users deleted longer ago than the retention, returning how many
+    /// there were.
+    fn purge(&self) -> Result<usize, UserPurgeErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserPurge)]
+pub struct UserPurgeService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: UserDeletionConfig,
+}
+
+impl UserPurge for UserPurgeService {
+    fn purge(&self) -> Result<usize, UserPurgeErrors> {
+        let retention = Duration::days(self.config.retention.into());
+
+        Ok(self
+            .user_repository
+            .purge_deleted_before(self.clock.now() - retention)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::domain::users::user_id::UserID;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    const OTHER_USER_ID: &str = "01a153b2-0000-7000-8000-000000000003";
+    const KEPT_USER_ID: &str = "01a153b2-0000-7000-8000-000000000004";
+
+    fn service(fixture: &Fixture) -> UserPurgeService {
+        UserPurgeService {
+            user_repository: fixture.users.clone(),
+            clock: fixture.clock.clone(),
+            config: UserDeletionConfig {
+                retention: 30,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn delete(fixture: &Fixture, id: &str) {
+        let user = fixture.user(id).delete(fixture.clock.now(), None);
+        fixture.users.update(&user).unwrap();
+    }
+
+    #[test]
+    fn removes_the_users_deleted_longer_ago_than_the_retention() {
+        let fixture = Fixture::default()
+            .with_user(USER_ID, "jane@example.com")
+            .with_user(OTHER_USER_ID, "john@example.com")
+            .with_user(KEPT_USER_ID, "joe@example.com");
+        delete(&fixture, USER_ID);
+        fixture.clock.advance(Duration::seconds(1));
+        delete(&fixture, OTHER_USER_ID);
+        fixture.clock.advance(Duration::days(30));
+
+        assert_eq!(service(&fixture).purge().unwrap(), 1);
+
+        let remaining = fixture.users.get_all(true);
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.iter().all(|user| user.get_id() != USER_ID));
+        let other = UserID::try_from(OTHER_USER_ID).unwrap();
+        assert!(fixture.users.find_deleted_by(&other).unwrap().is_some());
+    }
+
+    #[test]
+    fn leaves_users_that_are_not_deleted() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        fixture.clock.advance(Duration::days(365));
+
+        assert_eq!(service(&fixture).purge().unwrap(), 0);
+        assert_eq!(fixture.users.get_all(true).len(), 1);
+    }
+}
@@ -0,0 +1,312 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::application::start_session::{issue_tokens, UserTokens};
+use crate::users::domain::users::access_token::{AccessTokenErrors, AccessTokenSigner};
+use crate::users::domain::users::refresh_token::RefreshToken;
+use crate::users::domain::users::refresh_token_repository::{
+    RefreshTokenRepository, RefreshTokenRepositoryErrors,
+};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::UserRepository;
+use crate::users::domain::users::user_session::{SessionConfig, UserSession};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+#[derive(Error, Debug)]
+pub enum UserRefreshSessionErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The refresh token is invalid or its session expired or was revoked")]
+    InvalidToken,
+    #[error("The refresh token was already used, its session was revoked")]
+    TokenReused,
+}
+
+impl From<UserSessionRepositoryErrors> for UserRefreshSessionErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserRefreshSessionErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<RefreshTokenRepositoryErrors> for UserRefreshSessionErrors {
+    fn from(value: RefreshTokenRepositoryErrors) -> Self {
+        match value {
+            RefreshTokenRepositoryErrors::InternalServerError { source } => {
+                UserRefreshSessionErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<AccessTokenErrors> for UserRefreshSessionErrors {
+    fn from(value: AccessTokenErrors) -> Self {
+        UserRefreshSessionErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserRefreshSession: Interface {
+    /// Exchanges the refresh token of a session for new tokens, rotating it. Reusing a rotated
+    /// token means it leaked, so the whole session is revoked.
+    fn refresh(
+        &self,
+        refresh_token: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<UserTokens, UserRefreshSessionErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserRefreshSession)]
+pub struct UserRefreshSessionService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    refresh_token_repository: Arc<dyn RefreshTokenRepository>,
+    #[shaku(inject)]
+    access_token_signer: Arc<dyn AccessTokenSigner>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: SessionConfig,
+}
+
+impl UserRefreshSessionService {
+    fn revoke(&self, session: UserSession) -> Result<(), UserRefreshSessionErrors> {
+        self.user_session_repository.update(&session.revoke())?;
+
+        Ok(())
+    }
+}
+
+impl UserRefreshSession for UserRefreshSessionService {
+    fn refresh(
+        &self,
+        refresh_token: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<UserTokens, UserRefreshSessionErrors> {
+        let (id, secret) =
+            RefreshToken::parse(refresh_token).ok_or(UserRefreshSessionErrors::InvalidToken)?;
+
+        let now = self.clock.now();
+
+        let token = self
+            .refresh_token_repository
+            .find_by(id)?
+            .filter(|token| token.matches(secret))
+            .ok_or(UserRefreshSessionErrors::InvalidToken)?;
+
+        let session = self
+            .user_session_repository
+            .find_by(token.get_session_id())?
+            .filter(|session| session.is_active(now))
+            .ok_or(UserRefreshSessionErrors::InvalidToken)?;
+
+        // Rotating is what consumes the token, a concurrent refresh with it is a reuse too.
+        if token.is_rotated() || !self.refresh_token_repository.rotate(token.get_id())? {
+            self.revoke(session)?;
+            return Err(UserRefreshSessionErrors::TokenReused);
+        }
+
+        // Sessions end with the user being deleted, suspended, locked or closed.
+        let can_log_in = UserID::try_from(session.get_user_id())
+            .ok()
+            .and_then(|user_id| self.user_repository.find_by(&user_id))
+            .is_some_and(|user| user.get_status().allows_login());
+
+        if !can_log_in {
+            self.revoke(session)?;
+            return Err(UserRefreshSessionErrors::InvalidToken);
+        }
+
+        let ip = ip.map(|ip| ip.to_string());
+        let session = session.used(
+            user_agent,
+            ip.as_deref(),
+            self.config.refresh_token_lifetime(),
+            now,
+        );
+
+        self.user_session_repository.update(&session)?;
+
+        issue_tokens(
+            self.access_token_signer.as_ref(),
+            self.refresh_token_repository.as_ref(),
+            &session,
+            &self.config,
+            now,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    /// Session of a user with the status, logged in a day before the refreshes.
+    fn fixture(status: UserStatus) -> (Fixture, UserTokens) {
+        let fixture = Fixture::default().with_user_in(USER_ID, "jane@example.com", status);
+        let config = SessionConfig::default();
+        let logged_in_at = fixture.clock.now();
+
+        let session = UserSession::start(
+            USER_ID,
+            Some("Firefox"),
+            None,
+            config.refresh_token_lifetime(),
+            logged_in_at,
+        );
+        fixture.sessions.save(&session).unwrap();
+
+        let tokens = issue_tokens::<UserRefreshSessionErrors>(
+            fixture.access_token_signer.as_ref(),
+            fixture.refresh_tokens.as_ref(),
+            &session,
+            &config,
+            logged_in_at,
+        )
+        .unwrap();
+
+        fixture.clock.advance(Duration::days(1));
+
+        (fixture, tokens)
+    }
+
+    fn service(fixture: &Fixture) -> UserRefreshSessionService {
+        UserRefreshSessionService {
+            user_repository: fixture.users.clone(),
+            user_session_repository: fixture.sessions.clone(),
+            refresh_token_repository: fixture.refresh_tokens.clone(),
+            access_token_signer: fixture.access_token_signer.clone(),
+            clock: fixture.clock.clone(),
+            config: SessionConfig::default(),
+        }
+    }
+
+    fn session(fixture: &Fixture, tokens: &UserTokens) -> UserSession {
+        fixture
+            .sessions
+            .find_by(&tokens.session_id)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn rotates_the_refresh_token_and_extends_the_session() {
+        let (fixture, logged_in) = fixture(UserStatus::Active);
+        let service = service(&fixture);
+
+        let tokens = service
+            .refresh(&logged_in.refresh_token, None, None)
+            .unwrap();
+
+        assert_eq!(tokens.session_id, logged_in.session_id);
+        assert_ne!(tokens.refresh_token, logged_in.refresh_token);
+        assert_eq!(
+            tokens.access_token_expires_at,
+            Utc.with_ymd_and_hms(2024, 5, 2, 12, 15, 0).unwrap()
+        );
+
+        let session = session(&fixture, &tokens);
+        assert_eq!(session.get_user_agent(), Some("Firefox"));
+        assert_eq!(
+            session.get_last_used_at(),
+            Utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            session.get_expires_at(),
+            Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+        );
+
+        service.refresh(&tokens.refresh_token, None, None).unwrap();
+    }
+
+    #[test]
+    fn revokes_the_session_when_a_rotated_token_is_reused() {
+        let (fixture, logged_in) = fixture(UserStatus::Active);
+        let service = service(&fixture);
+
+        let tokens = service
+            .refresh(&logged_in.refresh_token, None, None)
+            .unwrap();
+        let reused = service.refresh(&logged_in.refresh_token, None, None);
+
+        assert!(matches!(reused, Err(UserRefreshSessionErrors::TokenReused)));
+        assert!(session(&fixture, &tokens).is_revoked());
+
+        // Whoever refreshed first is logged out too.
+        let latest = service.refresh(&tokens.refresh_token, None, None);
+        assert!(matches!(
+            latest,
+            Err(UserRefreshSessionErrors::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn refuses_unknown_and_malformed_tokens() {
+        let (fixture, logged_in) = fixture(UserStatus::Active);
+        let service = service(&fixture);
+        let (id, _) = RefreshToken::parse(&logged_in.refresh_token).unwrap();
+
+        for token in ["", "malformed", &format!("{id}.wrong-secret")] {
+            let result = service.refresh(token, None, None);
+
+            assert!(matches!(
+                result,
+                Err(UserRefreshSessionErrors::InvalidToken)
+            ));
+        }
+
+        assert!(!session(&fixture, &logged_in).is_revoked());
+    }
+
+    #[test]
+    fn refuses_expired_sessions() {
+        let (fixture, logged_in) = fixture(UserStatus::Active);
+        fixture.clock.advance(Duration::days(30));
+
+        let result = service(&fixture).refresh(&logged_in.refresh_token, None, None);
+
+        assert!(matches!(
+            result,
+            Err(UserRefreshSessionErrors::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn ends_the_session_of_users_who_cant_log_in() {
+        let (fixture, logged_in) = fixture(UserStatus::Locked);
+
+        let result = service(&fixture).refresh(&logged_in.refresh_token, None, None);
+
+        assert!(matches!(
+            result,
+            Err(UserRefreshSessionErrors::InvalidToken)
+        ));
+        assert!(session(&fixture, &logged_in).is_revoked());
+    }
+}
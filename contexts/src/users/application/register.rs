@@ -3,13 +3,27 @@ use std::sync::Arc;
 use shaku::{Component, Interface};
 use thiserror::Error;
 
-use crate::users::domain::users::{User, UserErrors};
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::EventBus;
+use crate::users::application::request_email_verification::UserEmailVerificationRequest;
+use crate::users::application::unique_email::ensure_email_available;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors,
+};
+use crate::users::domain::users::password_policy::PasswordPolicy;
+use crate::users::domain::users::user_audit::UserAudit;
+use crate::users::domain::users::user_events::UserRegistered;
 use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::{User, UserErrors};
 
 #[derive(Error, Debug)]
 pub enum UserRegisterErrors {
     #[error("The user that is trying to register is already registered")]
     AlreadyExists,
+    #[error("The email is already taken by another user")]
+    EmailAlreadyTaken,
     #[error("The server has found an unexpected situation")]
     InternalServerError {
         #[source]
@@ -26,6 +40,7 @@ impl From<RepositoryErrors> for UserRegisterErrors {
     fn from(value: RepositoryErrors) -> Self {
         match value {
             RepositoryErrors::AlreadyExists => UserRegisterErrors::AlreadyExists,
+            RepositoryErrors::EmailAlreadyTaken => UserRegisterErrors::EmailAlreadyTaken,
             RepositoryErrors::InternalServerError { source } => {
                 UserRegisterErrors::InternalServerError {
                     source: Some(source),
@@ -35,14 +50,29 @@ impl From<RepositoryErrors> for UserRegisterErrors {
     }
 }
 
+impl From<PasswordHistoryRepositoryErrors> for UserRegisterErrors {
+    fn from(value: PasswordHistoryRepositoryErrors) -> Self {
+        match value {
+            PasswordHistoryRepositoryErrors::InternalServerError { source } => {
+                UserRegisterErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
 pub trait UserRegister: Interface {
-    fn register(
+    /// Registers a user, generating its id when none is given, and returns it. The actor is the id
+    /// of the user registering it, missing when it registers itself.
+    fn register<'a>(
         &self,
-        uuid: &str,
-        name: &str,
-        password: &str,
-        email: &str,
-    ) -> Result<(), UserRegisterErrors>;
+        uuid: Option<&'a str>,
+        name: &'a str,
+        password: &'a str,
+        email: &'a str,
+        actor: Option<&str>,
+    ) -> Result<User<'a>, UserRegisterErrors>;
 }
 
 #[derive(Component)]
@@ -50,18 +80,156 @@ pub trait UserRegister: Interface {
 pub struct UserRegisterService {
     #[shaku(inject)]
     user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    password_policy: Arc<dyn PasswordPolicy>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[shaku(inject)]
+    password_history_repository: Arc<dyn PasswordHistoryRepository>,
+    #[shaku(inject)]
+    email_verification_request_service: Arc<dyn UserEmailVerificationRequest>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
 }
 
 impl UserRegister for UserRegisterService {
-    fn register(
+    fn register<'a>(
         &self,
-        uuid: &str,
-        name: &str,
-        password: &str,
-        email: &str,
-    ) -> Result<(), UserRegisterErrors> {
-        let user = User::create(uuid, name, password, email)?;
-
-        Ok(self.user_repository.save(&user)?)
+        uuid: Option<&'a str>,
+        name: &'a str,
+        password: &'a str,
+        email: &'a str,
+        actor: Option<&str>,
+    ) -> Result<User<'a>, UserRegisterErrors> {
+        let audit = UserAudit::created(self.clock.now(), actor);
+
+        let user = match uuid {
+            Some(uuid) => User::create(
+                uuid,
+                name,
+                password,
+                email,
+                self.password_policy.as_ref(),
+                self.email_policy.as_ref(),
+                self.password_hasher.as_ref(),
+                audit,
+            )?,
+            None => User::create_with_new_id(
+                name,
+                password,
+                email,
+                self.password_policy.as_ref(),
+                self.email_policy.as_ref(),
+                self.password_hasher.as_ref(),
+                audit,
+            )?,
+        };
+
+        ensure_email_available(self.user_repository.as_ref(), &user)?;
+
+        self.user_repository.save(&user)?;
+
+        self.password_history_repository.add(
+            user.get_id(),
+            user.get_password(),
+            self.password_policy.history_size(),
+        )?;
+
+        // The user is saved already, a verification mail that can't be sent is only logged and
+        // can be asked for again.
+        if let Err(error) = self.email_verification_request_service.request(&user) {
+            log::warn!(
+                "Email verification of user {} couldn't be requested: {:?}",
+                user.get_id(),
+                error
+            );
+        }
+
+        // Subscribers, like the welcome mail, don't undo the registration when they fail.
+        if let Err(error) = self.event_bus.publish(&UserRegistered::from(&user)) {
+            log::warn!(
+                "Registration of user {} couldn't be published: {:?}",
+                user.get_id(),
+                error
+            );
+        }
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::domain::users::user_password::UserPasswordErrors;
+    use crate::users::infrastructure::in_memory::{
+        EmailPolicyPlain, Fixture, PasswordHasherCheap, PasswordPolicyMinLength, PASSWORD, USER_ID,
+    };
+
+    fn service(fixture: &Fixture) -> UserRegisterService {
+        UserRegisterService {
+            user_repository: fixture.users.clone(),
+            password_policy: Arc::new(PasswordPolicyMinLength),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_hasher: Arc::new(PasswordHasherCheap),
+            password_history_repository: fixture.password_history.clone(),
+            email_verification_request_service: fixture.email_verification_requests.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    #[test]
+    fn registers_users_with_passwords_following_the_policy() {
+        let fixture = Fixture::default();
+
+        service(&fixture)
+            .register(
+                Some(USER_ID),
+                "Jane Doe",
+                PASSWORD,
+                "jane@example.com",
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(fixture.user(USER_ID).get_name(), "Jane Doe");
+        assert_eq!(
+            fixture
+                .password_history
+                .find_last(USER_ID, 5)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(fixture.email_verification_requests.requested(), [USER_ID]);
+    }
+
+    #[test]
+    fn rejects_passwords_the_policy_refuses() {
+        let fixture = Fixture::default();
+
+        let result = service(&fixture).register(
+            Some(USER_ID),
+            "Jane Doe",
+            "passw0!",
+            "jane@example.com",
+            None,
+        );
+
+        assert!(matches!(
+            result,
+            Err(UserRegisterErrors::UserError {
+                source: UserErrors::UserPasswordError {
+                    source: UserPasswordErrors::NotLongEnough { .. }
+                }
+            })
+        ));
+        assert!(fixture.users.get_all(true).is_empty());
+        assert!(fixture.email_verification_requests.requested().is_empty());
     }
 }
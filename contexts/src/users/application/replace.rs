@@ -0,0 +1,315 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::EventBus;
+use crate::users::application::find::{UserFind, UserFindErrors};
+use crate::users::application::password_history::ensure_not_reused;
+use crate::users::application::request_email_verification::UserEmailVerificationRequest;
+use crate::users::application::unique_email::ensure_email_available;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors,
+};
+use crate::users::domain::users::password_policy::PasswordPolicy;
+use crate::users::domain::users::user_audit::UserAudit;
+use crate::users::domain::users::user_events::UserRegistered;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+use crate::users::domain::users::{User, UserErrors};
+
+#[derive(Error, Debug)]
+pub enum UserReplaceErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("The user was registered while it was being replaced")]
+    Conflict,
+    #[error("The email is already taken by another user")]
+    EmailAlreadyTaken,
+}
+
+impl From<RepositoryErrors> for UserReplaceErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::AlreadyExists => UserReplaceErrors::Conflict,
+            RepositoryErrors::EmailAlreadyTaken => UserReplaceErrors::EmailAlreadyTaken,
+            RepositoryErrors::InternalServerError { source } => {
+                UserReplaceErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<PasswordHistoryRepositoryErrors> for UserReplaceErrors {
+    fn from(value: PasswordHistoryRepositoryErrors) -> Self {
+        match value {
+            PasswordHistoryRepositoryErrors::InternalServerError { source } => {
+                UserReplaceErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<UserSessionRepositoryErrors> for UserReplaceErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserReplaceErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<UserFindErrors> for UserReplaceErrors {
+    fn from(value: UserFindErrors) -> Self {
+        match value {
+            UserFindErrors::InternalServerError { source } => {
+                UserReplaceErrors::InternalServerError { source }
+            }
+            UserFindErrors::UserIDError { source } => UserReplaceErrors::UserError {
+                source: UserErrors::UserIDError { source },
+            },
+        }
+    }
+}
+
+/// Whether replacing a user registered it or overwrote an existing one.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UserReplaced {
+    Created,
+    Replaced,
+}
+
+pub trait UserReplace: Interface {
+    /// Replaces the user with the given id, registering it when missing. The actor is the id of
+    /// the user making the change, missing when it isn't a known user.
+    fn replace(
+        &self,
+        id: &str,
+        name: &str,
+        password: &str,
+        email: &str,
+        actor: Option<&str>,
+    ) -> Result<UserReplaced, UserReplaceErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserReplace)]
+pub struct UserReplaceService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    user_find_service: Arc<dyn UserFind>,
+    #[shaku(inject)]
+    password_policy: Arc<dyn PasswordPolicy>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[shaku(inject)]
+    password_history_repository: Arc<dyn PasswordHistoryRepository>,
+    #[shaku(inject)]
+    email_verification_request_service: Arc<dyn UserEmailVerificationRequest>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserReplace for UserReplaceService {
+    fn replace(
+        &self,
+        id: &str,
+        name: &str,
+        password: &str,
+        email: &str,
+        actor: Option<&str>,
+    ) -> Result<UserReplaced, UserReplaceErrors> {
+        let current = self.user_find_service.find_by(id)?;
+
+        // Keeping the current password isn't a reuse, any other recent one is.
+        let password_changed = match &current {
+            Some(current) => !self
+                .password_hasher
+                .verify(password, current.get_password())
+                .map_err(UserErrors::from)?,
+            None => true,
+        };
+
+        // A replaced user keeps when and by whom it was created.
+        let now = self.clock.now();
+        let audit = match &current {
+            Some(current) => current.get_audit().updated(now, actor),
+            None => UserAudit::created(now, actor),
+        };
+
+        let user = User::create(
+            id,
+            name,
+            password,
+            email,
+            self.password_policy.as_ref(),
+            self.email_policy.as_ref(),
+            self.password_hasher.as_ref(),
+            audit,
+        )?;
+
+        // Only a password following the policy is compared with the previous ones.
+        if password_changed && current.is_some() {
+            ensure_not_reused::<UserReplaceErrors>(
+                self.password_history_repository.as_ref(),
+                self.password_hasher.as_ref(),
+                id,
+                password,
+                self.password_policy.history_size(),
+            )?;
+        }
+
+        let email_changed = match &current {
+            Some(current) => current.get_email() != user.get_email(),
+            None => true,
+        };
+
+        let user = match &current {
+            Some(current) => user.keep_email_verification(current).keep_status(current),
+            None => user,
+        };
+
+        if email_changed {
+            ensure_email_available(self.user_repository.as_ref(), &user)?;
+        }
+
+        let replaced = if current.is_some() {
+            self.user_repository.update(&user)?;
+            UserReplaced::Replaced
+        } else {
+            self.user_repository.save(&user)?;
+            UserReplaced::Created
+        };
+
+        if password_changed {
+            self.password_history_repository.add(
+                user.get_id(),
+                user.get_password(),
+                self.password_policy.history_size(),
+            )?;
+        }
+
+        // A new password logs the user out everywhere.
+        if password_changed && replaced == UserReplaced::Replaced {
+            self.user_session_repository
+                .revoke_by_user(user.get_id(), now)?;
+        }
+
+        if email_changed {
+            // Best-effort like on registration, the verification can be asked for again.
+            if let Err(error) = self.email_verification_request_service.request(&user) {
+                log::warn!(
+                    "Email verification of user {} couldn't be requested: {:?}",
+                    user.get_id(),
+                    error
+                );
+            }
+        }
+
+        if replaced == UserReplaced::Created {
+            // Logged and not failed, as when registering.
+            if let Err(error) = self.event_bus.publish(&UserRegistered::from(&user)) {
+                log::warn!(
+                    "Registration of user {} couldn't be published: {:?}",
+                    user.get_id(),
+                    error
+                );
+            }
+        }
+
+        Ok(replaced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::domain::users::user_password::UserPasswordErrors;
+    use crate::users::infrastructure::in_memory::{
+        EmailPolicyPlain, Fixture, PasswordHasherCheap, PasswordPolicyMinLength, PASSWORD, USER_ID,
+    };
+
+    fn service(fixture: &Fixture) -> UserReplaceService {
+        UserReplaceService {
+            user_repository: fixture.users.clone(),
+            user_find_service: fixture.user_find(),
+            password_policy: Arc::new(PasswordPolicyMinLength),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_hasher: Arc::new(PasswordHasherCheap),
+            password_history_repository: fixture.password_history.clone(),
+            email_verification_request_service: fixture.email_verification_requests.clone(),
+            user_session_repository: fixture.sessions.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    fn is_refused(result: Result<UserReplaced, UserReplaceErrors>) -> bool {
+        matches!(
+            result,
+            Err(UserReplaceErrors::UserError {
+                source: UserErrors::UserPasswordError {
+                    source: UserPasswordErrors::NotLongEnough { .. }
+                }
+            })
+        )
+    }
+
+    #[test]
+    fn rejects_passwords_the_policy_refuses_for_replaced_users() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        let result = service(&fixture).replace(
+            USER_ID,
+            "Jane Roe",
+            "passw0!",
+            "jane@example.com",
+            Some(USER_ID),
+        );
+
+        assert!(is_refused(result));
+        let user = fixture.user(USER_ID);
+        assert_eq!(user.get_name(), "Jane Doe");
+        assert!(PasswordHasherCheap
+            .verify(PASSWORD, user.get_password())
+            .unwrap());
+    }
+
+    #[test]
+    fn rejects_passwords_the_policy_refuses_for_created_users() {
+        let fixture = Fixture::default();
+
+        let result =
+            service(&fixture).replace(USER_ID, "Jane Doe", "passw0!", "jane@example.com", None);
+
+        assert!(is_refused(result));
+        assert!(fixture.users.get_all(true).is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::Duration;
+use serde::Deserialize;
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::mailer::{Mailer, MailerErrors};
+use crate::users::application::mails;
+use crate::users::domain::users::email_verification::VerifiedOnlyAction;
+use crate::users::domain::users::email_verification_token::EmailVerificationToken;
+use crate::users::domain::users::email_verification_token_repository::{
+    EmailVerificationTokenRepository, EmailVerificationTokenRepositoryErrors,
+};
+use crate::users::domain::users::User;
+
+/// Placeholder of the `link` replaced by the token.
+const TOKEN_PLACEHOLDER: &str = "{token}";
+
+/// Lifetime of the email verification tokens, where they are redeemed and which actions need a
+/// verified email, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EmailVerificationConfig {
+    /// Minutes a token can be used for.
+    pub token_lifetime: u32,
+    /// Link sent to the user, `{token}` is replaced by the token.
+    pub link: String,
+    /// Actions only users with a verified email can do.
+    pub verified_only: HashSet<VerifiedOnlyAction>,
+}
+
+impl Default for EmailVerificationConfig {
+    fn default() -> Self {
+        EmailVerificationConfig {
+            token_lifetime: 24 * 60,
+            link: format!("http://localhost:8000/users/verify-email?token={TOKEN_PLACEHOLDER}"),
+            verified_only: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum UserEmailVerificationRequestErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+impl From<EmailVerificationTokenRepositoryErrors> for UserEmailVerificationRequestErrors {
+    fn from(value: EmailVerificationTokenRepositoryErrors) -> Self {
+        match value {
+            EmailVerificationTokenRepositoryErrors::InternalServerError { source } => {
+                UserEmailVerificationRequestErrors::InternalServerError { source }
+            }
+        }
+    }
+}
+
+impl From<MailerErrors> for UserEmailVerificationRequestErrors {
+    fn from(value: MailerErrors) -> Self {
+        UserEmailVerificationRequestErrors::InternalServerError {
+            source: value.into(),
+        }
+    }
+}
+
+pub trait UserEmailVerificationRequest: Interface {
+    /// Mails a token to the current email of the user, replacing any previous one.
+    fn request(&self, user: &User) -> Result<(), UserEmailVerificationRequestErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserEmailVerificationRequest)]
+pub struct UserEmailVerificationRequestService {
+    #[shaku(inject)]
+    email_verification_token_repository: Arc<dyn EmailVerificationTokenRepository>,
+    #[shaku(inject)]
+    mailer: Arc<dyn Mailer>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: EmailVerificationConfig,
+}
+
+impl UserEmailVerificationRequest for UserEmailVerificationRequestService {
+    fn request(&self, user: &User) -> Result<(), UserEmailVerificationRequestErrors> {
+        let lifetime = Duration::minutes(self.config.token_lifetime.into());
+        let (token, value) = EmailVerificationToken::issue(
+            user.get_id(),
+            user.get_email(),
+            lifetime,
+            self.clock.now(),
+        );
+
+        self.email_verification_token_repository
+            .delete_by_user(user.get_id())?;
+        self.email_verification_token_repository.save(&token)?;
+
+        self.mailer.send(&mails::EMAIL_VERIFICATION.render(
+            user.get_email(),
+            &[
+                ("name", user.get_name()),
+                ("link", &self.config.link.replace(TOKEN_PLACEHOLDER, &value)),
+                ("lifetime", &self.config.token_lifetime.to_string()),
+            ],
+        ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{now, Fixture, USER_ID};
+
+    fn service(fixture: &Fixture) -> UserEmailVerificationRequestService {
+        UserEmailVerificationRequestService {
+            email_verification_token_repository: fixture.email_verification_tokens.clone(),
+            mailer: fixture.mailer.clone(),
+            clock: fixture.clock.clone(),
+            config: EmailVerificationConfig {
+                token_lifetime: 60,
+                link: "https://example.com/verify?token={token}".to_owned(),
+                verified_only: HashSet::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn mails_a_link_with_the_token_to_the_current_email() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        service(&fixture).request(&fixture.user(USER_ID)).unwrap();
+
+        let tokens = fixture.email_verification_tokens.saved();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].get_user_id(), USER_ID);
+        assert_eq!(tokens[0].get_email(), "jane@example.com");
+        assert_eq!(tokens[0].get_expires_at(), now() + Duration::minutes(60));
+
+        let mails = fixture.mailer.sent();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].to, "jane@example.com");
+        assert!(mails[0].text.contains(&format!(
+            "https://example.com/verify?token={}.",
+            tokens[0].get_id()
+        )));
+    }
+
+    #[test]
+    fn only_keeps_the_latest_token() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        service(&fixture).request(&fixture.user(USER_ID)).unwrap();
+        service(&fixture).request(&fixture.user(USER_ID)).unwrap();
+
+        assert_eq!(fixture.email_verification_tokens.saved().len(), 1);
+        assert_eq!(fixture.mailer.sent().len(), 2);
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use serde::Deserialize;
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::mailer::{Mailer, MailerErrors};
+use crate::users::application::mails;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::password_reset_token::PasswordResetToken;
+use crate::users::domain::users::password_reset_token_repository::{
+    PasswordResetTokenRepository, PasswordResetTokenRepositoryErrors,
+};
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+
+/// Placeholder of the `link` replaced by the token.
+const TOKEN_PLACEHOLDER: &str = "{token}";
+
+/// Lifetime of the password reset tokens and where they are redeemed, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordResetConfig {
+    /// Minutes a token can be used for.
+    pub token_lifetime: u32,
+    /// Link sent to the user, `{token}` is replaced by the token.
+    pub link: String,
+    /// Requests waiting to be handled, the ones coming while it's full are dropped.
+    pub queue_size: usize,
+    /// Seconds further requests for the same email are ignored for.
+    pub cooldown: u32,
+}
+
+impl Default for PasswordResetConfig {
+    fn default() -> Self {
+        PasswordResetConfig {
+            token_lifetime: 30,
+            link: format!("http://localhost:8000/reset-password?token={TOKEN_PLACEHOLDER}"),
+            queue_size: 100,
+            cooldown: 60,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum UserPasswordResetRequestErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+}
+
+impl From<RepositoryErrors> for UserPasswordResetRequestErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetRequestErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserPasswordResetRequestErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<PasswordResetTokenRepositoryErrors> for UserPasswordResetRequestErrors {
+    fn from(value: PasswordResetTokenRepositoryErrors) -> Self {
+        match value {
+            PasswordResetTokenRepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetRequestErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<MailerErrors> for UserPasswordResetRequestErrors {
+    fn from(value: MailerErrors) -> Self {
+        UserPasswordResetRequestErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserPasswordResetRequest: Interface {
+    /// Mails a password reset token to the user with the email, doing nothing when there is none
+    /// so callers can't tell which emails are registered.
+    fn request(&self, email: &str) -> Result<(), UserPasswordResetRequestErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserPasswordResetRequest)]
+pub struct UserPasswordResetRequestService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_reset_token_repository: Arc<dyn PasswordResetTokenRepository>,
+    #[shaku(inject)]
+    mailer: Arc<dyn Mailer>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: PasswordResetConfig,
+    /// Whether only users with a verified email can reset their password.
+    require_verified_email: bool,
+}
+
+impl UserPasswordResetRequest for UserPasswordResetRequestService {
+    fn request(&self, email: &str) -> Result<(), UserPasswordResetRequestErrors> {
+        let Ok(email) = self.email_policy.parse(email) else {
+            return Ok(());
+        };
+
+        let user = self
+            .user_repository
+            .find_by_email(email.get_normalized())?
+            .filter(|user| !user.is_deleted() && user.get_status().allows_login());
+        let Some(user) = user else {
+            return Ok(());
+        };
+
+        if self.require_verified_email && !user.get_email_verification().is_verified() {
+            return Ok(());
+        }
+
+        let lifetime = Duration::minutes(self.config.token_lifetime.into());
+        let (token, value) = PasswordResetToken::issue(user.get_id(), lifetime, self.clock.now());
+
+        // Only the latest token requested by a user can be redeemed.
+        self.password_reset_token_repository
+            .delete_by_user(user.get_id())?;
+        self.password_reset_token_repository.save(&token)?;
+
+        self.mailer.send(&mails::PASSWORD_RESET.render(
+            user.get_email(),
+            &[
+                ("name", user.get_name()),
+                ("link", &self.config.link.replace(TOKEN_PLACEHOLDER, &value)),
+                ("lifetime", &self.config.token_lifetime.to_string()),
+            ],
+        ))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{now, EmailPolicyPlain, Fixture, USER_ID};
+
+    fn service(fixture: &Fixture) -> UserPasswordResetRequestService {
+        service_requiring_verified_email(fixture, false)
+    }
+
+    fn service_requiring_verified_email(
+        fixture: &Fixture,
+        require_verified_email: bool,
+    ) -> UserPasswordResetRequestService {
+        UserPasswordResetRequestService {
+            user_repository: fixture.users.clone(),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_reset_token_repository: fixture.password_reset_tokens.clone(),
+            mailer: fixture.mailer.clone(),
+            clock: fixture.clock.clone(),
+            config: PasswordResetConfig {
+                token_lifetime: 30,
+                link: "https://example.com/reset?token={token}".to_owned(),
+                ..Default::default()
+            },
+            require_verified_email,
+        }
+    }
+
+    #[test]
+    fn mails_a_link_with_the_token_to_the_user() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        service(&fixture).request("jane@example.com").unwrap();
+
+        let tokens = fixture.password_reset_tokens.saved();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].get_user_id(), USER_ID);
+        assert_eq!(tokens[0].get_expires_at(), now() + Duration::minutes(30));
+
+        let mails = fixture.mailer.sent();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].to, "jane@example.com");
+        assert!(mails[0].text.contains(&format!(
+            "https://example.com/reset?token={}.",
+            tokens[0].get_id()
+        )));
+    }
+
+    #[test]
+    fn only_keeps_the_latest_token() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        service(&fixture).request("jane@example.com").unwrap();
+        service(&fixture).request("jane@example.com").unwrap();
+
+        assert_eq!(fixture.password_reset_tokens.saved().len(), 1);
+        assert_eq!(fixture.mailer.sent().len(), 2);
+    }
+
+    #[test]
+    fn does_nothing_for_unknown_emails() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        service(&fixture).request("john@example.com").unwrap();
+
+        assert!(fixture.password_reset_tokens.saved().is_empty());
+        assert!(fixture.mailer.sent().is_empty());
+    }
+
+    #[test]
+    fn only_mails_verified_emails_when_required() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let service = service_requiring_verified_email(&fixture, true);
+
+        service.request("jane@example.com").unwrap();
+        assert!(fixture.mailer.sent().is_empty());
+
+        fixture
+            .users
+            .update(&fixture.user(USER_ID).verify_email(fixture.clock.now()))
+            .unwrap();
+        service.request("jane@example.com").unwrap();
+        assert_eq!(fixture.mailer.sent().len(), 1);
+    }
+}
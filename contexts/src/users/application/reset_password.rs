@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::application::password_history::ensure_not_reused;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors,
+};
+use crate::users::domain::users::password_policy::PasswordPolicy;
+use crate::users::domain::users::password_reset_token::PasswordResetToken;
+use crate::users::domain::users::password_reset_token_repository::{
+    PasswordResetTokenRepository, PasswordResetTokenRepositoryErrors,
+};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+use crate::users::domain::users::UserErrors;
+
+#[derive(Error, Debug)]
+pub enum UserPasswordResetErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("The password reset token is invalid, expired or was already used")]
+    InvalidToken,
+}
+
+impl From<RepositoryErrors> for UserPasswordResetErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserPasswordResetErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<PasswordResetTokenRepositoryErrors> for UserPasswordResetErrors {
+    fn from(value: PasswordResetTokenRepositoryErrors) -> Self {
+        match value {
+            PasswordResetTokenRepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<PasswordHistoryRepositoryErrors> for UserPasswordResetErrors {
+    fn from(value: PasswordHistoryRepositoryErrors) -> Self {
+        match value {
+            PasswordHistoryRepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<UserSessionRepositoryErrors> for UserPasswordResetErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserPasswordResetErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserPasswordReset: Interface {
+    /// Sets a new password for the user the token was issued to, consuming the token and logging
+    /// the user out everywhere.
+    fn reset(&self, token: &str, password: &str) -> Result<(), UserPasswordResetErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserPasswordReset)]
+pub struct UserPasswordResetService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    password_reset_token_repository: Arc<dyn PasswordResetTokenRepository>,
+    #[shaku(inject)]
+    password_history_repository: Arc<dyn PasswordHistoryRepository>,
+    #[shaku(inject)]
+    password_policy: Arc<dyn PasswordPolicy>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserPasswordReset for UserPasswordResetService {
+    fn reset(&self, token: &str, password: &str) -> Result<(), UserPasswordResetErrors> {
+        let (id, secret) =
+            PasswordResetToken::parse(token).ok_or(UserPasswordResetErrors::InvalidToken)?;
+
+        let now = self.clock.now();
+
+        let token = self
+            .password_reset_token_repository
+            .find_by(id)?
+            .filter(|token| token.matches(secret, now))
+            .ok_or(UserPasswordResetErrors::InvalidToken)?;
+
+        let user_id = UserID::try_from(token.get_user_id()).map_err(UserErrors::from)?;
+        // Tokens sent before the user was suspended, locked or closed can't be redeemed either.
+        let Some(user) = self
+            .user_repository
+            .find_by(&user_id)
+            .filter(|user| user.get_status().allows_login())
+        else {
+            return Err(UserPasswordResetErrors::InvalidToken);
+        };
+
+        // Redeeming the token proves the user is the one changing its password.
+        let audit = user.get_audit().updated(now, Some(user.get_id()));
+
+        let user = user.update(
+            None,
+            Some(password),
+            None,
+            self.password_policy.as_ref(),
+            self.email_policy.as_ref(),
+            self.password_hasher.as_ref(),
+            audit,
+        )?;
+
+        // Only a password following the policy is compared with the previous ones.
+        ensure_not_reused::<UserPasswordResetErrors>(
+            self.password_history_repository.as_ref(),
+            self.password_hasher.as_ref(),
+            user.get_id(),
+            password,
+            self.password_policy.history_size(),
+        )?;
+
+        // Deleting the token is what consumes it, a concurrent reset loses the race here.
+        if !self.password_reset_token_repository.delete_by(id)? {
+            return Err(UserPasswordResetErrors::InvalidToken);
+        }
+
+        self.user_repository.update(&user)?;
+
+        self.password_history_repository.add(
+            user.get_id(),
+            user.get_password(),
+            self.password_policy.history_size(),
+        )?;
+
+        self.user_session_repository
+            .revoke_by_user(user.get_id(), now)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::user_password::UserPasswordErrors;
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{
+        EmailPolicyPlain, Fixture, PasswordHasherCheap, PasswordPolicyMinLength, PASSWORD, USER_ID,
+    };
+
+    fn service(fixture: &Fixture) -> UserPasswordResetService {
+        UserPasswordResetService {
+            user_repository: fixture.users.clone(),
+            password_reset_token_repository: fixture.password_reset_tokens.clone(),
+            password_history_repository: fixture.password_history.clone(),
+            password_policy: Arc::new(PasswordPolicyMinLength),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_hasher: Arc::new(PasswordHasherCheap),
+            user_session_repository: fixture.sessions.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    #[test]
+    fn rejects_passwords_the_policy_refuses_keeping_the_token() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let (token, value) =
+            PasswordResetToken::issue(USER_ID, Duration::hours(1), fixture.clock.now());
+        fixture.password_reset_tokens.save(&token).unwrap();
+
+        let result = service(&fixture).reset(&value, "passw0!");
+
+        assert!(matches!(
+            result,
+            Err(UserPasswordResetErrors::UserError {
+                source: UserErrors::UserPasswordError {
+                    source: UserPasswordErrors::NotLongEnough { .. }
+                }
+            })
+        ));
+        assert!(PasswordHasherCheap
+            .verify(PASSWORD, fixture.user(USER_ID).get_password())
+            .unwrap());
+        assert_eq!(fixture.password_reset_tokens.saved().len(), 1);
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::two_factor_repository::{
+    TwoFactorRepository, TwoFactorRepositoryErrors,
+};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::UserErrors;
+
+#[derive(Error, Debug)]
+pub enum UserResetTwoFactorErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("User not found")]
+    NotFound,
+}
+
+impl From<TwoFactorRepositoryErrors> for UserResetTwoFactorErrors {
+    fn from(value: TwoFactorRepositoryErrors) -> Self {
+        match value {
+            TwoFactorRepositoryErrors::InternalServerError { source } => {
+                UserResetTwoFactorErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<RepositoryErrors> for UserResetTwoFactorErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserResetTwoFactorErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserResetTwoFactorErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+pub trait UserResetTwoFactor: Interface {
+    /// Removes the two-factor enrollment of the user, for when it lost both its authenticator app
+    /// and its recovery codes. It logs in with its password alone until it enrolls again. The
+    /// actor is the id of the user making the change.
+    fn reset(&self, id: &str, actor: Option<&str>) -> Result<(), UserResetTwoFactorErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserResetTwoFactor)]
+pub struct UserResetTwoFactorService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserResetTwoFactor for UserResetTwoFactorService {
+    fn reset(&self, id: &str, actor: Option<&str>) -> Result<(), UserResetTwoFactorErrors> {
+        let id = UserID::try_from(id).map_err(UserErrors::from)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .ok_or(UserResetTwoFactorErrors::NotFound)?;
+
+        if !self.two_factor_repository.delete_by(user.get_id())? {
+            return Ok(());
+        }
+
+        self.user_repository
+            .update(&user.record_change(self.clock.now(), actor))?;
+
+        Ok(())
+    }
+}
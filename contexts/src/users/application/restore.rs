@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::application::delete::UserDeletionConfig;
+use crate::users::domain::users::user_id::{UserID, UserIDErrors};
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+
+#[derive(Error, Debug)]
+pub enum UserRestoreErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("UserID validation error")]
+    UserIDError {
+        #[from]
+        source: UserIDErrors,
+    },
+    #[error("No deleted user has this id")]
+    NotFound,
+    #[error("The user was deleted more than {0} days ago, it can't be restored anymore")]
+    RestorePeriodOver(u32),
+}
+
+impl From<RepositoryErrors> for UserRestoreErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserRestoreErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserRestoreErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+pub trait UserRestore: Interface {
+    /// Brings back a deleted user within its restore period. The actor is the id of the user
+    /// restoring it, missing when it isn't a known user.
+    fn restore(&self, id: &str, actor: Option<&str>) -> Result<(), UserRestoreErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserRestore)]
+pub struct UserRestoreService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: UserDeletionConfig,
+}
+
+impl UserRestore for UserRestoreService {
+    fn restore(&self, id: &str, actor: Option<&str>) -> Result<(), UserRestoreErrors> {
+        let user = self
+            .user_repository
+            .find_deleted_by(&UserID::try_from(id)?)?
+            .ok_or(UserRestoreErrors::NotFound)?;
+
+        let now = self.clock.now();
+        let restore_period = Duration::days(self.config.restore_period.into());
+
+        let restorable = user
+            .get_audit()
+            .get_deleted_at()
+            .is_some_and(|deleted_at| now <= deleted_at + restore_period);
+
+        if !restorable {
+            return Err(UserRestoreErrors::RestorePeriodOver(
+                self.config.restore_period,
+            ));
+        }
+
+        self.user_repository.update(&user.restore(now, actor))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    const ADMIN_ID: &str = "01a153b2-0000-7000-8000-000000000001";
+
+    fn service(fixture: &Fixture) -> UserRestoreService {
+        UserRestoreService {
+            user_repository: fixture.users.clone(),
+            clock: fixture.clock.clone(),
+            config: UserDeletionConfig {
+                restore_period: 30,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Fixture whose user was deleted at the current time of its clock.
+    fn deleted_user() -> Fixture {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let user = fixture.user(USER_ID).delete(fixture.clock.now(), None);
+        fixture.users.update(&user).unwrap();
+
+        fixture
+    }
+
+    #[test]
+    fn restores_the_user_until_the_end_of_the_restore_period() {
+        let fixture = deleted_user();
+        fixture.clock.advance(Duration::days(30));
+
+        service(&fixture).restore(USER_ID, Some(ADMIN_ID)).unwrap();
+
+        let user = fixture.user(USER_ID);
+        assert!(!user.is_deleted());
+        assert_eq!(user.get_audit().get_updated_at(), fixture.clock.now());
+        assert_eq!(user.get_audit().get_updated_by(), Some(ADMIN_ID));
+    }
+
+    #[test]
+    fn rejects_users_deleted_longer_ago_than_the_restore_period() {
+        let fixture = deleted_user();
+        fixture
+            .clock
+            .advance(Duration::days(30) + Duration::seconds(1));
+
+        assert!(matches!(
+            service(&fixture).restore(USER_ID, None),
+            Err(UserRestoreErrors::RestorePeriodOver(30))
+        ));
+    }
+
+    #[test]
+    fn rejects_users_that_are_not_deleted() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        assert!(matches!(
+            service(&fixture).restore(USER_ID, None),
+            Err(UserRestoreErrors::NotFound)
+        ));
+        assert!(matches!(
+            service(&fixture).restore("01a153b2-0000-7000-8000-000000000003", None),
+            Err(UserRestoreErrors::NotFound)
+        ));
+    }
+}
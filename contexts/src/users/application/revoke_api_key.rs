@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::api_key_repository::{ApiKeyRepository, ApiKeyRepositoryErrors};
+
+#[derive(Error, Debug)]
+pub enum UserRevokeApiKeyErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The user has no active API key with this id")]
+    NotFound,
+}
+
+impl From<ApiKeyRepositoryErrors> for UserRevokeApiKeyErrors {
+    fn from(value: ApiKeyRepositoryErrors) -> Self {
+        match value {
+            ApiKeyRepositoryErrors::InternalServerError { source } => {
+                UserRevokeApiKeyErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserRevokeApiKey: Interface {
+    /// Revokes one of the keys of the user, requests with it are refused right away.
+    fn revoke(&self, user_id: &str, id: &str) -> Result<(), UserRevokeApiKeyErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserRevokeApiKey)]
+pub struct UserRevokeApiKeyService {
+    #[shaku(inject)]
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserRevokeApiKey for UserRevokeApiKeyService {
+    fn revoke(&self, user_id: &str, id: &str) -> Result<(), UserRevokeApiKeyErrors> {
+        let now = self.clock.now();
+
+        // Keys of other users are as missing as unknown ones.
+        let api_key = self
+            .api_key_repository
+            .find_by(id)?
+            .filter(|api_key| api_key.get_user_id() == user_id && api_key.is_active(now))
+            .ok_or(UserRevokeApiKeyErrors::NotFound)?;
+
+        self.api_key_repository.update(&api_key.revoke(now))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::api_key::ApiKey;
+    use crate::users::infrastructure::in_memory::{now, Fixture, USER_ID};
+
+    const OTHER_USER_ID: &str = "01a153b2-0000-7000-8000-000000000003";
+
+    fn service(fixture: &Fixture) -> UserRevokeApiKeyService {
+        UserRevokeApiKeyService {
+            api_key_repository: fixture.api_keys.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    fn api_key(fixture: &Fixture) -> ApiKey {
+        let (api_key, _) =
+            ApiKey::create(USER_ID, "CI", &["write"], Duration::days(90), now()).unwrap();
+        fixture.api_keys.save(&api_key).unwrap();
+
+        api_key
+    }
+
+    #[test]
+    fn revokes_the_key_of_the_user() {
+        let fixture = Fixture::default();
+        let api_key = api_key(&fixture);
+
+        service(&fixture).revoke(USER_ID, api_key.get_id()).unwrap();
+
+        let saved = fixture.api_keys.find_by(api_key.get_id()).unwrap().unwrap();
+        assert_eq!(saved.get_revoked_at(), Some(now()));
+        assert!(fixture
+            .api_keys
+            .find_active_by_user(USER_ID, now())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn keys_of_other_users_are_not_found() {
+        let fixture = Fixture::default();
+        let api_key = api_key(&fixture);
+
+        let result = service(&fixture).revoke(OTHER_USER_ID, api_key.get_id());
+
+        assert!(matches!(result, Err(UserRevokeApiKeyErrors::NotFound)));
+        assert!(fixture
+            .api_keys
+            .find_by(api_key.get_id())
+            .unwrap()
+            .unwrap()
+            .is_active(now()));
+    }
+
+    #[test]
+    fn revoked_and_expired_keys_are_not_found() {
+        let fixture = Fixture::default();
+        let revoked = api_key(&fixture);
+        service(&fixture).revoke(USER_ID, revoked.get_id()).unwrap();
+        let expired = api_key(&fixture);
+        fixture.clock.advance(Duration::days(90));
+
+        for id in [revoked.get_id(), expired.get_id()] {
+            let result = service(&fixture).revoke(USER_ID, id);
+
+            assert!(matches!(result, Err(UserRevokeApiKeyErrors::NotFound)));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+#[derive(Error, Debug)]
+pub enum UserRevokeSessionsErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The user has no active session with this id")]
+    NotFound,
+}
+
+impl From<UserSessionRepositoryErrors> for UserRevokeSessionsErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserRevokeSessionsErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub trait UserRevokeSessions: Interface {
+    /// Logs the user out of one of its sessions, ending its refresh and access tokens.
+    fn revoke(&self, user_id: &str, session_id: &str) -> Result<(), UserRevokeSessionsErrors>;
+    /// Logs the user out everywhere, returning how many sessions were ended.
+    fn revoke_all(&self, user_id: &str) -> Result<usize, UserRevokeSessionsErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserRevokeSessions)]
+pub struct UserRevokeSessionsService {
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserRevokeSessions for UserRevokeSessionsService {
+    fn revoke(&self, user_id: &str, session_id: &str) -> Result<(), UserRevokeSessionsErrors> {
+        let now = self.clock.now();
+
+        // Sessions of other users are as missing as unknown ones.
+        let session = self
+            .user_session_repository
+            .find_by(session_id)?
+            .filter(|session| session.get_user_id() == user_id && session.is_active(now))
+            .ok_or(UserRevokeSessionsErrors::NotFound)?;
+
+        self.user_session_repository.update(&session.revoke())?;
+
+        Ok(())
+    }
+
+    fn revoke_all(&self, user_id: &str) -> Result<usize, UserRevokeSessionsErrors> {
+        Ok(self
+            .user_session_repository
+            .revoke_by_user(user_id, self.clock.now())?)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::users::domain::users::user_search_repository::{
+    SearchRepositoryErrors, UserSearchMatch, UserSearchRepository,
+};
+use shaku::{Component, Interface};
+use std::sync::Arc;
+use thiserror::Error;
+
+const MAX_SEARCH_RESULTS: u32 = 50;
+
+#[derive(Error, Debug)]
+pub enum UserSearchErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The search query can't be empty")]
+    EmptyQuery,
+}
+
+impl From<SearchRepositoryErrors> for UserSearchErrors {
+    fn from(value: SearchRepositoryErrors) -> Self {
+        match value {
+            SearchRepositoryErrors::InternalServerError { source } => {
+                UserSearchErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, UserSearchErrors>;
+
+pub trait UserSearch: Interface {
+    fn search(&self, query: &str) -> Result<Vec<UserSearchMatch<'_>>>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserSearch)]
+pub struct UserSearchService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserSearchRepository>,
+}
+
+impl UserSearch for UserSearchService {
+    fn search(&self, query: &str) -> Result<Vec<UserSearchMatch<'_>>> {
+        if query.trim().is_empty() {
+            return Err(UserSearchErrors::EmptyQuery);
+        }
+
+        Ok(self.user_repository.search(query, MAX_SEARCH_RESULTS)?)
+    }
+}
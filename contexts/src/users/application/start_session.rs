@@ -0,0 +1,153 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::domain::users::access_token::{
+    AccessToken, AccessTokenErrors, AccessTokenSigner,
+};
+use crate::users::domain::users::refresh_token::RefreshToken;
+use crate::users::domain::users::refresh_token_repository::{
+    RefreshTokenRepository, RefreshTokenRepositoryErrors,
+};
+use crate::users::domain::users::user_session::{SessionConfig, UserSession};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
+
+#[derive(Error, Debug)]
+pub enum UserStartSessionErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+}
+
+impl From<UserSessionRepositoryErrors> for UserStartSessionErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserStartSessionErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<RefreshTokenRepositoryErrors> for UserStartSessionErrors {
+    fn from(value: RefreshTokenRepositoryErrors) -> Self {
+        match value {
+            RefreshTokenRepositoryErrors::InternalServerError { source } => {
+                UserStartSessionErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<AccessTokenErrors> for UserStartSessionErrors {
+    fn from(value: AccessTokenErrors) -> Self {
+        UserStartSessionErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+/// Tokens handed out for a session, the refresh token is only ever shown this once.
+#[derive(Debug, Clone)]
+pub struct UserTokens {
+    pub session_id: String,
+    pub access_token: String,
+    pub access_token_expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+}
+
+/// Issues an access token and a new refresh token for the session.
+pub(crate) fn issue_tokens<E>(
+    signer: &dyn AccessTokenSigner,
+    refresh_token_repository: &dyn RefreshTokenRepository,
+    session: &UserSession,
+    config: &SessionConfig,
+    now: DateTime<Utc>,
+) -> Result<UserTokens, E>
+where
+    E: From<AccessTokenErrors> + From<RefreshTokenRepositoryErrors>,
+{
+    let access_token = AccessToken::issue(
+        session.get_user_id(),
+        session.get_id(),
+        config.access_token_lifetime(),
+        now,
+    );
+    let (refresh_token, refresh_token_value) = RefreshToken::issue(session.get_id(), now);
+
+    refresh_token_repository.save(&refresh_token)?;
+
+    Ok(UserTokens {
+        session_id: session.get_id().to_owned(),
+        access_token: signer.sign(&access_token)?,
+        access_token_expires_at: access_token.get_expires_at(),
+        refresh_token: refresh_token_value,
+    })
+}
+
+pub trait UserStartSession: Interface {
+    /// Starts a session for an authenticated user on the device with the user agent and IP,
+    /// when known, handing out its first tokens.
+    fn start(
+        &self,
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<UserTokens, UserStartSessionErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserStartSession)]
+pub struct UserStartSessionService {
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    refresh_token_repository: Arc<dyn RefreshTokenRepository>,
+    #[shaku(inject)]
+    access_token_signer: Arc<dyn AccessTokenSigner>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: SessionConfig,
+}
+
+impl UserStartSession for UserStartSessionService {
+    fn start(
+        &self,
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Result<UserTokens, UserStartSessionErrors> {
+        let now = self.clock.now();
+        let ip = ip.map(|ip| ip.to_string());
+
+        let session = UserSession::start(
+            user_id,
+            user_agent,
+            ip.as_deref(),
+            self.config.refresh_token_lifetime(),
+            now,
+        );
+
+        self.user_session_repository.save(&session)?;
+
+        issue_tokens(
+            self.access_token_signer.as_ref(),
+            self.refresh_token_repository.as_ref(),
+            &session,
+            &self.config,
+            now,
+        )
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::users::application::authenticate::seconds;
+use crate::users::domain::users::login_failures::{LoginFailures, LoginThrottlingRules};
+use crate::users::domain::users::login_failures_repository::{
+    LoginFailuresRepository, LoginFailuresRepositoryErrors,
+};
+use crate::users::domain::users::totp::{Totp, TotpErrors};
+use crate::users::domain::users::two_factor::{TwoFactor, TwoFactorConfig};
+use crate::users::domain::users::two_factor_challenge::TwoFactorChallenge;
+use crate::users::domain::users::two_factor_challenge_repository::{
+    TwoFactorChallengeRepository, TwoFactorChallengeRepositoryErrors,
+};
+use crate::users::domain::users::two_factor_repository::{
+    TwoFactorRepository, TwoFactorRepositoryErrors,
+};
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::UserRepository;
+use crate::users::domain::users::User;
+
+#[derive(Error, Debug)]
+pub enum UserTwoFactorLoginErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("The login is unknown, expired or was already completed, it has to start over")]
+    InvalidChallenge,
+    #[error("The code isn't valid")]
+    InvalidCode,
+    #[error("The account is {0}, it can't log in")]
+    AccountUnavailable(&'static str),
+    #[error("Too many failed logins of this account, retry in {0} seconds")]
+    LockedOut(u64),
+}
+
+impl From<TwoFactorRepositoryErrors> for UserTwoFactorLoginErrors {
+    fn from(value: TwoFactorRepositoryErrors) -> Self {
+        match value {
+            TwoFactorRepositoryErrors::InternalServerError { source } => {
+                UserTwoFactorLoginErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<TwoFactorChallengeRepositoryErrors> for UserTwoFactorLoginErrors {
+    fn from(value: TwoFactorChallengeRepositoryErrors) -> Self {
+        match value {
+            TwoFactorChallengeRepositoryErrors::InternalServerError { source } => {
+                UserTwoFactorLoginErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<LoginFailuresRepositoryErrors> for UserTwoFactorLoginErrors {
+    fn from(value: LoginFailuresRepositoryErrors) -> Self {
+        match value {
+            LoginFailuresRepositoryErrors::InternalServerError { source } => {
+                UserTwoFactorLoginErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<TotpErrors> for UserTwoFactorLoginErrors {
+    fn from(value: TotpErrors) -> Self {
+        UserTwoFactorLoginErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+/// Login waiting for a two-factor code, the token is only ever shown this once.
+#[derive(Debug, Clone)]
+pub struct TwoFactorChallengeIssued {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub trait UserTwoFactorLogin: Interface {
+    /// Challenges the user whose password was checked for a code when it has two-factor
+    /// authentication enabled, missing when the password is enough.
+    fn challenge(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<TwoFactorChallengeIssued>, UserTwoFactorLoginErrors>;
+    /// Completes the login with a code of the authenticator app or a recovery code, which are
+    /// only accepted once. The login has to start over after too many wrong codes, which count
+    /// as failed logins of the account and lock it out the same way wrong passwords do.
+    fn verify(&self, token: &str, code: &str) -> Result<User<'_>, UserTwoFactorLoginErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserTwoFactorLogin)]
+pub struct UserTwoFactorLoginService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    #[shaku(inject)]
+    two_factor_challenge_repository: Arc<dyn TwoFactorChallengeRepository>,
+    #[shaku(inject)]
+    login_failures_repository: Arc<dyn LoginFailuresRepository>,
+    #[shaku(inject)]
+    totp: Arc<dyn Totp>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+    config: TwoFactorConfig,
+    rules: LoginThrottlingRules,
+}
+
+impl UserTwoFactorLoginService {
+    /// Enrollment after using the code, missing when it isn't valid or was already used.
+    fn use_code(
+        &self,
+        two_factor: TwoFactor,
+        code: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<TwoFactor>, UserTwoFactorLoginErrors> {
+        let step = self
+            .totp
+            .verify(two_factor.get_secret(), code, now)?
+            .filter(|step| two_factor.accepts_step(*step));
+
+        Ok(match step {
+            Some(step) => Some(two_factor.used_step(step)),
+            None => two_factor.use_recovery_code(code),
+        })
+    }
+}
+
+impl UserTwoFactorLogin for UserTwoFactorLoginService {
+    fn challenge(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<TwoFactorChallengeIssued>, UserTwoFactorLoginErrors> {
+        let enabled = self
+            .two_factor_repository
+            .find_by(user_id)?
+            .is_some_and(|two_factor| two_factor.is_enabled());
+        if !enabled {
+            return Ok(None);
+        }
+
+        let (challenge, token) =
+            TwoFactorChallenge::issue(user_id, self.config.challenge_lifetime(), self.clock.now());
+
+        self.two_factor_challenge_repository.save(&challenge)?;
+
+        Ok(Some(TwoFactorChallengeIssued {
+            token,
+            expires_at: challenge.get_expires_at(),
+        }))
+    }
+
+    fn verify(&self, token: &str, code: &str) -> Result<User<'_>, UserTwoFactorLoginErrors> {
+        let now = self.clock.now();
+
+        let (id, secret) =
+            TwoFactorChallenge::parse(token).ok_or(UserTwoFactorLoginErrors::InvalidChallenge)?;
+        let challenge = self
+            .two_factor_challenge_repository
+            .find_by(id)?
+            .filter(|challenge| challenge.matches(secret, now))
+            .filter(|challenge| challenge.get_attempts() < self.config.max_attempts)
+            .ok_or(UserTwoFactorLoginErrors::InvalidChallenge)?;
+
+        // The user may have been deleted since the password was checked.
+        let id = UserID::try_from(challenge.get_user_id())
+            .map_err(|_| UserTwoFactorLoginErrors::InvalidChallenge)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .filter(|user| !user.is_deleted())
+            .ok_or(UserTwoFactorLoginErrors::InvalidChallenge)?;
+
+        // Wrong codes count against the account, so logging in again for a new challenge doesn't
+        // give more attempts.
+        let account_key = LoginFailures::account_key(user.get_normalized_email());
+        let account_failures = self
+            .login_failures_repository
+            .find_by(&account_key)?
+            .unwrap_or_else(|| LoginFailures::none(&account_key, now));
+
+        if let Some(retry_after) = account_failures.retry_after(now) {
+            return Err(UserTwoFactorLoginErrors::LockedOut(seconds(retry_after)));
+        }
+
+        // Reset meanwhile, the password alone is enough now but it was checked for the challenge.
+        let Some(two_factor) = self
+            .two_factor_repository
+            .find_by(challenge.get_user_id())?
+            .filter(|two_factor| two_factor.is_enabled())
+        else {
+            self.two_factor_challenge_repository
+                .delete_by(challenge.get_id())?;
+            return Err(UserTwoFactorLoginErrors::InvalidChallenge);
+        };
+
+        let Some(two_factor) = self.use_code(two_factor, code, now)? else {
+            if challenge.get_attempts() + 1 >= self.config.max_attempts {
+                self.two_factor_challenge_repository
+                    .delete_by(challenge.get_id())?;
+            } else {
+                self.two_factor_challenge_repository
+                    .add_attempt(challenge.get_id())?;
+            }
+
+            let account_failures =
+                account_failures.failed(now, self.rules.account_max_failures, &self.rules);
+            self.login_failures_repository.save(&account_failures)?;
+
+            return Err(UserTwoFactorLoginErrors::InvalidCode);
+        };
+
+        // Only the request deleting the challenge completes the login, so it can't be completed
+        // twice with the same code.
+        if !self
+            .two_factor_challenge_repository
+            .delete_by(challenge.get_id())?
+        {
+            return Err(UserTwoFactorLoginErrors::InvalidChallenge);
+        }
+
+        self.two_factor_repository.save(&two_factor)?;
+
+        // Failures of the account are forgiven once the user proved it has both factors.
+        if account_failures.get_failures() > 0 || account_failures.get_lockouts() > 0 {
+            self.login_failures_repository
+                .delete_by(account_failures.get_key())?;
+        }
+
+        // The user may have been suspended since the password was checked.
+        if !user.get_status().allows_login() {
+            return Err(UserTwoFactorLoginErrors::AccountUnavailable(
+                user.get_status().get(),
+            ));
+        }
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::User;
+
+/// Fails when another user than the given one has the same email, the database enforces it too
+/// but checking first keeps the rule in the domain.
+pub(crate) fn ensure_email_available(
+    user_repository: &dyn UserRepository,
+    user: &User,
+) -> Result<(), RepositoryErrors> {
+    match user_repository.find_by_email(user.get_normalized_email())? {
+        Some(other) if other.get_id() != user.get_id() => Err(RepositoryErrors::EmailAlreadyTaken),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    const OTHER_USER_ID: &str = "01a153b2-0000-7000-8000-000000000003";
+
+    #[test]
+    fn accepts_an_email_no_one_has() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let other = Fixture::default().with_user(OTHER_USER_ID, "john@example.com");
+
+        assert!(ensure_email_available(fixture.users.as_ref(), &other.user(OTHER_USER_ID)).is_ok());
+    }
+
+    #[test]
+    fn accepts_the_email_of_the_user_itself() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        assert!(ensure_email_available(fixture.users.as_ref(), &fixture.user(USER_ID)).is_ok());
+    }
+
+    #[test]
+    fn rejects_the_email_of_another_user_once_normalized() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let other = Fixture::default().with_user(OTHER_USER_ID, "Jane@Example.com");
+
+        assert!(matches!(
+            ensure_email_available(fixture.users.as_ref(), &other.user(OTHER_USER_ID)),
+            Err(RepositoryErrors::EmailAlreadyTaken)
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::{EventBus, EventBusErrors};
+use crate::users::domain::users::login_failures::LoginFailures;
+use crate::users::domain::users::login_failures_repository::{
+    LoginFailuresRepository, LoginFailuresRepositoryErrors,
+};
+use crate::users::domain::users::user_events::UserStatusChanged;
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_status::UserStatus;
+use crate::users::domain::users::UserErrors;
+
+#[derive(Error, Debug)]
+pub enum UserUnlockErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("User not found")]
+    NotFound,
+}
+
+impl From<RepositoryErrors> for UserUnlockErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserUnlockErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserUnlockErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<LoginFailuresRepositoryErrors> for UserUnlockErrors {
+    fn from(value: LoginFailuresRepositoryErrors) -> Self {
+        match value {
+            LoginFailuresRepositoryErrors::InternalServerError { source } => {
+                UserUnlockErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<EventBusErrors> for UserUnlockErrors {
+    fn from(value: EventBusErrors) -> Self {
+        UserUnlockErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserUnlock: Interface {
+    /// Forgets the failed logins of the user, lifting its lockout, and reactivates it when it was
+    /// locked. The actor is the id of the user making the change, missing when it isn't a known
+    /// user.
+    fn unlock(&self, id: &str, actor: Option<&str>) -> Result<(), UserUnlockErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserUnlock)]
+pub struct UserUnlockService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    login_failures_repository: Arc<dyn LoginFailuresRepository>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserUnlock for UserUnlockService {
+    fn unlock(&self, id: &str, actor: Option<&str>) -> Result<(), UserUnlockErrors> {
+        let id = UserID::try_from(id).map_err(UserErrors::from)?;
+        let user = self
+            .user_repository
+            .find_by(&id)
+            .ok_or(UserUnlockErrors::NotFound)?;
+
+        self.login_failures_repository
+            .delete_by(&LoginFailures::account_key(user.get_normalized_email()))?;
+
+        if *user.get_status() != UserStatus::Locked {
+            return Ok(());
+        }
+
+        let user = user.reactivate(self.clock.now(), actor)?;
+
+        self.user_repository.update(&user)?;
+
+        self.event_bus
+            .publish(&UserStatusChanged::new(UserStatus::Locked, &user, actor))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::login_failures::LoginThrottlingRules;
+    use crate::users::infrastructure::in_memory::{Fixture, USER_ID};
+
+    const ADMIN_ID: &str = "01a153b2-0000-7000-8000-000000000001";
+
+    fn service(fixture: &Fixture) -> UserUnlockService {
+        UserUnlockService {
+            user_repository: fixture.users.clone(),
+            login_failures_repository: fixture.login_failures.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    /// Locks out the account of the user.
+    fn lock_out(fixture: &Fixture) -> String {
+        let key = LoginFailures::account_key("jane@example.com");
+        let rules = LoginThrottlingRules::default();
+        let failures = (0..rules.account_max_failures).fold(
+            LoginFailures::none(&key, fixture.clock.now()),
+            |failures, _| failures.failed(fixture.clock.now(), rules.account_max_failures, &rules),
+        );
+        fixture.login_failures.save(&failures).unwrap();
+
+        key
+    }
+
+    #[test]
+    fn lifts_the_lockout_of_the_account() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Active);
+        let key = lock_out(&fixture);
+
+        service(&fixture).unlock(USER_ID, Some(ADMIN_ID)).unwrap();
+
+        assert!(fixture.login_failures.find_by(&key).unwrap().is_none());
+        assert_eq!(fixture.user(USER_ID).get_status(), &UserStatus::Active);
+        assert!(fixture.event_bus.status_changes().is_empty());
+    }
+
+    #[test]
+    fn reactivates_locked_users() {
+        let fixture =
+            Fixture::default().with_user_in(USER_ID, "jane@example.com", UserStatus::Locked);
+        fixture.clock.advance(Duration::hours(1));
+
+        service(&fixture).unlock(USER_ID, Some(ADMIN_ID)).unwrap();
+
+        let user = fixture.user(USER_ID);
+        assert_eq!(user.get_status(), &UserStatus::Active);
+        assert_eq!(user.get_audit().get_updated_at(), fixture.clock.now());
+        assert_eq!(user.get_audit().get_updated_by(), Some(ADMIN_ID));
+        let changes = fixture.event_bus.status_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, UserStatus::Locked);
+    }
+
+    #[test]
+    fn fails_for_unknown_users() {
+        let fixture = Fixture::default();
+
+        assert!(matches!(
+            service(&fixture).unlock(USER_ID, None),
+            Err(UserUnlockErrors::NotFound)
+        ));
+    }
+}
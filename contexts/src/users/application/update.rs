@@ -3,9 +3,22 @@ use std::sync::Arc;
 use shaku::{Component, Interface};
 use thiserror::Error;
 
+use crate::shared::domain::clock::Clock;
 use crate::users::application::find::{UserFind, UserFindErrors};
+use crate::users::application::password_history::ensure_not_reused;
+use crate::users::application::request_email_verification::UserEmailVerificationRequest;
+use crate::users::application::unique_email::ensure_email_available;
 use crate::users::application::update::UserUpdateErrors::NotFound;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors,
+};
+use crate::users::domain::users::password_policy::PasswordPolicy;
 use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session_repository::{
+    UserSessionRepository, UserSessionRepositoryErrors,
+};
 use crate::users::domain::users::UserErrors;
 
 #[derive(Error, Debug)]
@@ -22,6 +35,8 @@ pub enum UserUpdateErrors {
     },
     #[error("User not found")]
     NotFound,
+    #[error("The email is already taken by another user")]
+    EmailAlreadyTaken,
 }
 
 impl From<RepositoryErrors> for UserUpdateErrors {
@@ -32,11 +47,36 @@ impl From<RepositoryErrors> for UserUpdateErrors {
                     source: Some(source),
                 }
             }
+            RepositoryErrors::EmailAlreadyTaken => UserUpdateErrors::EmailAlreadyTaken,
             _ => UserUpdateErrors::InternalServerError { source: None },
         }
     }
 }
 
+impl From<PasswordHistoryRepositoryErrors> for UserUpdateErrors {
+    fn from(value: PasswordHistoryRepositoryErrors) -> Self {
+        match value {
+            PasswordHistoryRepositoryErrors::InternalServerError { source } => {
+                UserUpdateErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<UserSessionRepositoryErrors> for UserUpdateErrors {
+    fn from(value: UserSessionRepositoryErrors) -> Self {
+        match value {
+            UserSessionRepositoryErrors::InternalServerError { source } => {
+                UserUpdateErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
 impl From<UserFindErrors> for UserUpdateErrors {
     fn from(value: UserFindErrors) -> Self {
         match value {
@@ -51,12 +91,15 @@ impl From<UserFindErrors> for UserUpdateErrors {
 }
 
 pub trait UserUpdate: Interface {
+    /// Changes the given fields of the user, changing its password logs it out everywhere. The
+    /// actor is the id of the user making the change, missing when it isn't a known user.
     fn update(
         &self,
         id: &str,
         name: Option<&str>,
         password: Option<&str>,
         email: Option<&str>,
+        actor: Option<&str>,
     ) -> Result<(), UserUpdateErrors>;
 }
 
@@ -67,6 +110,20 @@ pub struct UserUpdateService {
     user_repository: Arc<dyn UserRepository>,
     #[shaku(inject)]
     user_find_service: Arc<dyn UserFind>,
+    #[shaku(inject)]
+    password_policy: Arc<dyn PasswordPolicy>,
+    #[shaku(inject)]
+    email_policy: Arc<dyn EmailPolicy>,
+    #[shaku(inject)]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[shaku(inject)]
+    password_history_repository: Arc<dyn PasswordHistoryRepository>,
+    #[shaku(inject)]
+    email_verification_request_service: Arc<dyn UserEmailVerificationRequest>,
+    #[shaku(inject)]
+    user_session_repository: Arc<dyn UserSessionRepository>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
 }
 
 impl UserUpdate for UserUpdateService {
@@ -76,17 +133,142 @@ impl UserUpdate for UserUpdateService {
         name: Option<&str>,
         password: Option<&str>,
         email: Option<&str>,
+        actor: Option<&str>,
     ) -> Result<(), UserUpdateErrors> {
         let user = self.user_find_service.find_by(id)?;
 
-        let user = if let Some(user) = user {
-            user.update(name, password, email)?
+        let (user, email_changed) = if let Some(user) = user {
+            let previous_email = user.get_email().to_owned();
+            let audit = user.get_audit().updated(self.clock.now(), actor);
+
+            let user = user.update(
+                name,
+                password,
+                email,
+                self.password_policy.as_ref(),
+                self.email_policy.as_ref(),
+                self.password_hasher.as_ref(),
+                audit,
+            )?;
+
+            // Only a password following the policy is compared with the previous ones.
+            if let Some(password) = password {
+                ensure_not_reused::<UserUpdateErrors>(
+                    self.password_history_repository.as_ref(),
+                    self.password_hasher.as_ref(),
+                    id,
+                    password,
+                    self.password_policy.history_size(),
+                )?;
+            }
+
+            // The same email written differently isn't a change.
+            let email_changed = user.get_email() != previous_email;
+
+            (user, email_changed)
         } else {
             return Err(NotFound);
         };
 
+        if email_changed {
+            ensure_email_available(self.user_repository.as_ref(), &user)?;
+        }
+
         self.user_repository.update(&user)?;
 
+        if password.is_some() {
+            self.password_history_repository.add(
+                user.get_id(),
+                user.get_password(),
+                self.password_policy.history_size(),
+            )?;
+
+            self.user_session_repository
+                .revoke_by_user(user.get_id(), self.clock.now())?;
+        }
+
+        if email_changed {
+            // Best-effort, the change is kept and the verification can be asked for again.
+            if let Err(error) = self.email_verification_request_service.request(&user) {
+                log::warn!(
+                    "Email verification of user {} couldn't be requested: {:?}",
+                    user.get_id(),
+                    error
+                );
+            }
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::domain::users::user_password::UserPasswordErrors;
+    use crate::users::infrastructure::in_memory::{
+        EmailPolicyPlain, Fixture, PasswordHasherCheap, PasswordPolicyMinLength, PASSWORD, USER_ID,
+    };
+
+    fn service(fixture: &Fixture) -> UserUpdateService {
+        UserUpdateService {
+            user_repository: fixture.users.clone(),
+            user_find_service: fixture.user_find(),
+            password_policy: Arc::new(PasswordPolicyMinLength),
+            email_policy: Arc::new(EmailPolicyPlain),
+            password_hasher: Arc::new(PasswordHasherCheap),
+            password_history_repository: fixture.password_history.clone(),
+            email_verification_request_service: fixture.email_verification_requests.clone(),
+            user_session_repository: fixture.sessions.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    #[test]
+    fn changes_passwords_following_the_policy() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+
+        service(&fixture)
+            .update(
+                USER_ID,
+                None,
+                Some("Jane's new passw0rd!"),
+                None,
+                Some(USER_ID),
+            )
+            .unwrap();
+
+        let password = fixture.user(USER_ID).get_password().to_owned();
+        assert!(PasswordHasherCheap
+            .verify("Jane's new passw0rd!", &password)
+            .unwrap());
+        assert_eq!(
+            fixture.password_history.find_last(USER_ID, 5).unwrap(),
+            [password]
+        );
+    }
+
+    #[test]
+    fn rejects_passwords_the_policy_refuses_before_comparing_them_with_previous_ones() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let weak = "passw0!";
+        fixture
+            .password_history
+            .add(USER_ID, &PasswordHasherCheap.hash(weak).unwrap(), 5)
+            .unwrap();
+
+        let result = service(&fixture).update(USER_ID, None, Some(weak), None, Some(USER_ID));
+
+        assert!(matches!(
+            result,
+            Err(UserUpdateErrors::UserError {
+                source: UserErrors::UserPasswordError {
+                    source: UserPasswordErrors::NotLongEnough { .. }
+                }
+            })
+        ));
+        assert!(PasswordHasherCheap
+            .verify(PASSWORD, fixture.user(USER_ID).get_password())
+            .unwrap());
+    }
+}
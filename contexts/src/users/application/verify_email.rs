@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+use thiserror::Error;
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::{EventBus, EventBusErrors};
+use crate::users::domain::users::email_verification_token::EmailVerificationToken;
+use crate::users::domain::users::email_verification_token_repository::{
+    EmailVerificationTokenRepository, EmailVerificationTokenRepositoryErrors,
+};
+use crate::users::domain::users::user_events::UserStatusChanged;
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::UserErrors;
+
+#[derive(Error, Debug)]
+pub enum UserEmailVerifyErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    #[error("User validation error")]
+    UserError {
+        #[from]
+        source: UserErrors,
+    },
+    #[error("The email verification token is invalid, expired or was already used")]
+    InvalidToken,
+}
+
+impl From<RepositoryErrors> for UserEmailVerifyErrors {
+    fn from(value: RepositoryErrors) -> Self {
+        match value {
+            RepositoryErrors::InternalServerError { source } => {
+                UserEmailVerifyErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+            _ => UserEmailVerifyErrors::InternalServerError { source: None },
+        }
+    }
+}
+
+impl From<EmailVerificationTokenRepositoryErrors> for UserEmailVerifyErrors {
+    fn from(value: EmailVerificationTokenRepositoryErrors) -> Self {
+        match value {
+            EmailVerificationTokenRepositoryErrors::InternalServerError { source } => {
+                UserEmailVerifyErrors::InternalServerError {
+                    source: Some(source),
+                }
+            }
+        }
+    }
+}
+
+impl From<EventBusErrors> for UserEmailVerifyErrors {
+    fn from(value: EventBusErrors) -> Self {
+        UserEmailVerifyErrors::InternalServerError {
+            source: Some(value.into()),
+        }
+    }
+}
+
+pub trait UserEmailVerify: Interface {
+    /// Marks the email the token was sent to as verified, consuming the token.
+    fn verify(&self, token: &str) -> Result<(), UserEmailVerifyErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserEmailVerify)]
+pub struct UserEmailVerifyService {
+    #[shaku(inject)]
+    user_repository: Arc<dyn UserRepository>,
+    #[shaku(inject)]
+    email_verification_token_repository: Arc<dyn EmailVerificationTokenRepository>,
+    #[shaku(inject)]
+    event_bus: Arc<dyn EventBus>,
+    #[shaku(inject)]
+    clock: Arc<dyn Clock>,
+}
+
+impl UserEmailVerify for UserEmailVerifyService {
+    fn verify(&self, token: &str) -> Result<(), UserEmailVerifyErrors> {
+        let (id, secret) =
+            EmailVerificationToken::parse(token).ok_or(UserEmailVerifyErrors::InvalidToken)?;
+
+        let now = self.clock.now();
+
+        let token = self
+            .email_verification_token_repository
+            .find_by(id)?
+            .filter(|token| token.matches(secret, now))
+            .ok_or(UserEmailVerifyErrors::InvalidToken)?;
+
+        let user_id = UserID::try_from(token.get_user_id()).map_err(UserErrors::from)?;
+
+        // The user may have changed its email after the token was sent.
+        let Some(user) = self
+            .user_repository
+            .find_by(&user_id)
+            .filter(|user| user.get_email() == token.get_email())
+        else {
+            return Err(UserEmailVerifyErrors::InvalidToken);
+        };
+
+        if !self.email_verification_token_repository.delete_by(id)? {
+            return Err(UserEmailVerifyErrors::InvalidToken);
+        }
+
+        let previous = user.get_status().clone();
+        let user = user.verify_email(now);
+
+        self.user_repository.update(&user)?;
+
+        if *user.get_status() != previous {
+            self.event_bus.publish(&UserStatusChanged::new(
+                previous,
+                &user,
+                Some(user.get_id()),
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::users::domain::users::email_verification::EmailVerification;
+    use crate::users::domain::users::user_status::UserStatus;
+    use crate::users::infrastructure::in_memory::{
+        now, EmailPolicyPlain, Fixture, PasswordHasherCheap, PasswordPolicyAcceptAll, USER_ID,
+    };
+
+    fn service(fixture: &Fixture) -> UserEmailVerifyService {
+        UserEmailVerifyService {
+            user_repository: fixture.users.clone(),
+            email_verification_token_repository: fixture.email_verification_tokens.clone(),
+            event_bus: fixture.event_bus.clone(),
+            clock: fixture.clock.clone(),
+        }
+    }
+
+    /// Saves a token sent to the email and returns its value.
+    fn send_token(fixture: &Fixture, email: &str, lifetime: Duration) -> String {
+        let (token, value) =
+            EmailVerificationToken::issue(USER_ID, email, lifetime, fixture.clock.now());
+        fixture.email_verification_tokens.save(&token).unwrap();
+
+        value
+    }
+
+    #[test]
+    fn verifies_the_email_the_token_was_sent_to() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+        fixture.clock.advance(Duration::minutes(5));
+
+        service(&fixture).verify(&token).unwrap();
+
+        let user = fixture.user(USER_ID);
+        assert_eq!(
+            user.get_email_verification(),
+            EmailVerification::Verified(fixture.clock.now())
+        );
+        assert_eq!(user.get_audit().get_updated_at(), fixture.clock.now());
+        assert_eq!(user.get_audit().get_updated_by(), Some(USER_ID));
+        assert!(fixture.email_verification_tokens.saved().is_empty());
+    }
+
+    #[test]
+    fn activates_pending_users() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+
+        service(&fixture).verify(&token).unwrap();
+
+        assert_eq!(fixture.user(USER_ID).get_status(), &UserStatus::Active);
+        let changes = fixture.event_bus.status_changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, UserStatus::Pending);
+        assert_eq!(changes[0].changed_by.as_deref(), Some(USER_ID));
+    }
+
+    #[test]
+    fn rejects_a_token_used_already() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+
+        service(&fixture).verify(&token).unwrap();
+
+        assert!(matches!(
+            service(&fixture).verify(&token),
+            Err(UserEmailVerifyErrors::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn rejects_an_expired_token() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+        fixture.clock.advance(Duration::minutes(30));
+
+        assert!(matches!(
+            service(&fixture).verify(&token),
+            Err(UserEmailVerifyErrors::InvalidToken)
+        ));
+        assert!(!fixture.user(USER_ID).get_email_verification().is_verified());
+    }
+
+    #[test]
+    fn rejects_a_token_with_a_wrong_secret() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+        let (id, _) = EmailVerificationToken::parse(&token).unwrap();
+
+        assert!(matches!(
+            service(&fixture).verify(&format!("{id}.wrong")),
+            Err(UserEmailVerifyErrors::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn a_changed_email_has_to_be_verified_again() {
+        let fixture = Fixture::default().with_user(USER_ID, "jane@example.com");
+        let token = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+        service(&fixture).verify(&token).unwrap();
+        let stale = send_token(&fixture, "jane@example.com", Duration::minutes(30));
+
+        let user = fixture
+            .user(USER_ID)
+            .update(
+                None,
+                None,
+                Some("jane.doe@example.com"),
+                &PasswordPolicyAcceptAll,
+                &EmailPolicyPlain,
+                &PasswordHasherCheap,
+                fixture
+                    .user(USER_ID)
+                    .get_audit()
+                    .updated(now(), Some(USER_ID)),
+            )
+            .unwrap();
+        fixture.users.update(&user).unwrap();
+
+        assert!(!fixture.user(USER_ID).get_email_verification().is_verified());
+        assert!(matches!(
+            service(&fixture).verify(&stale),
+            Err(UserEmailVerifyErrors::InvalidToken)
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use shaku::{Component, Interface};
+
+use crate::shared::domain::event_bus::{DomainEvent, EventBusErrors, EventSubscriber};
+use crate::shared::domain::mailer::{Mailer, MailerErrors};
+use crate::users::application::mails;
+use crate::users::domain::users::user_events::UserRegistered;
+
+pub trait UserWelcome: Interface {
+    /// Mails the welcome of a user that just registered.
+    fn welcome(&self, event: &UserRegistered) -> Result<(), MailerErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = UserWelcome)]
+pub struct UserWelcomeService {
+    #[shaku(inject)]
+    mailer: Arc<dyn Mailer>,
+}
+
+impl UserWelcome for UserWelcomeService {
+    fn welcome(&self, event: &UserRegistered) -> Result<(), MailerErrors> {
+        self.mailer
+            .send(&mails::WELCOME.render(&event.email, &[("name", &event.name)]))
+    }
+}
+
+/// Welcomes every registered user.
+pub struct UserWelcomeSubscriber {
+    welcome_service: Arc<dyn UserWelcome>,
+}
+
+impl UserWelcomeSubscriber {
+    pub fn new(welcome_service: Arc<dyn UserWelcome>) -> Self {
+        UserWelcomeSubscriber { welcome_service }
+    }
+}
+
+impl EventSubscriber for UserWelcomeSubscriber {
+    fn handle(&self, event: &dyn DomainEvent) -> Result<(), EventBusErrors> {
+        let Some(registered) = event.as_any().downcast_ref::<UserRegistered>() else {
+            return Ok(());
+        };
+
+        self.welcome_service
+            .welcome(registered)
+            .map_err(|error| EventBusErrors::SubscriberError {
+                source: error.into(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use super::*;
+    use crate::shared::infrastructure::mailer::in_memory::MailerInMemory;
+    use crate::users::infrastructure::in_memory::USER_ID;
+
+    #[derive(Debug)]
+    struct OtherEvent;
+
+    impl DomainEvent for OtherEvent {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn subscriber(mailer: Arc<MailerInMemory>) -> UserWelcomeSubscriber {
+        UserWelcomeSubscriber::new(Arc::new(UserWelcomeService { mailer }))
+    }
+
+    #[test]
+    fn mails_the_welcome_to_registered_users() {
+        let mailer = Arc::new(MailerInMemory::new());
+
+        subscriber(mailer.clone())
+            .handle(&UserRegistered {
+                id: USER_ID.to_owned(),
+                name: "Jane Doe".to_owned(),
+                email: "jane@example.com".to_owned(),
+            })
+            .unwrap();
+
+        let mails = mailer.sent();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].to, "jane@example.com");
+        assert_eq!(mails[0].subject, "Welcome, Jane Doe");
+        assert!(mails[0].text.starts_with("Hi Jane Doe,"));
+    }
+
+    #[test]
+    fn ignores_other_events() {
+        let mailer = Arc::new(MailerInMemory::new());
+
+        subscriber(mailer.clone()).handle(&OtherEvent).unwrap();
+
+        assert!(mailer.sent().is_empty());
+    }
+}
@@ -1,16 +1,51 @@
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::email_verification::EmailVerification;
+use crate::users::domain::users::user_audit::UserAudit;
 use crate::users::domain::users::user_email::{UserEmail, UserEmailErrors};
 use crate::users::domain::users::user_id::{UserID, UserIDErrors};
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_policy::PasswordPolicy;
 use crate::users::domain::users::user_name::{UserName, UserNameErrors};
 use crate::users::domain::users::user_password::{UserPassword, UserPasswordErrors};
+use crate::users::domain::users::user_status::{UserStatus, UserStatusErrors};
 
+pub mod access_token;
+pub mod api_key;
+pub mod api_key_repository;
+pub mod email_policy;
+pub mod email_verification;
+pub mod email_verification_token;
+pub mod email_verification_token_repository;
+pub mod login_failures;
+pub mod login_failures_repository;
+pub mod password_hasher;
+pub mod password_history_repository;
+pub mod password_policy;
+pub mod password_reset_token;
+pub mod password_reset_token_repository;
+pub mod refresh_token;
+pub mod refresh_token_repository;
+mod token_secret;
+pub mod totp;
+pub mod two_factor;
+pub mod two_factor_challenge;
+pub mod two_factor_challenge_repository;
+pub mod two_factor_repository;
+pub mod user_audit;
 pub mod user_criteria_repository;
+pub mod user_events;
 pub mod user_email;
 pub mod user_id;
 pub mod user_name;
 pub mod user_password;
 pub mod user_repository;
+pub mod user_search_repository;
+pub mod user_session;
+pub mod user_session_repository;
+pub mod user_status;
 
 /// Errors that can occur during user validation.
 #[derive(Error, Debug)]
@@ -42,6 +77,30 @@ pub enum UserErrors {
         #[from]
         source: UserEmailErrors,
     },
+
+    /// Represents an error that occurs when changing the status of a user.
+    #[error("Failed to change the User Status")]
+    UserStatusError {
+        #[from]
+        source: UserStatusErrors,
+    },
+
+    /// Represents every error found when validating several fields of a user at once.
+    #[error("Failed to validate {} fields of the User", .0.len())]
+    InvalidFields(Vec<UserErrors>),
+}
+
+impl UserErrors {
+    /// Returns the only error found, or every one of them when several fields are invalid.
+    fn collect(errors: Vec<UserErrors>) -> Result<(), UserErrors> {
+        let mut errors = errors;
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(UserErrors::InvalidFields(errors)),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -50,6 +109,9 @@ pub struct User<'a> {
     name: UserName<'a>,
     password: UserPassword<'a>,
     email: UserEmail<'a>,
+    email_verification: EmailVerification,
+    status: UserStatus,
+    audit: UserAudit,
 }
 
 impl<'a> User<'a> {
@@ -58,40 +120,157 @@ impl<'a> User<'a> {
         name: UserName<'a>,
         password: UserPassword<'a>,
         email: UserEmail<'a>,
+        email_verification: EmailVerification,
+        status: UserStatus,
+        audit: UserAudit,
     ) -> Self {
         User {
             id,
             name,
             password,
             email,
+            email_verification,
+            status,
+            audit,
         }
     }
 
+    /// Checks a name users can be created or renamed with.
+    pub fn validate_name(name: &str) -> Result<(), UserErrors> {
+        Ok(UserName::validate(name)?)
+    }
+
+    /// Checks a password against the password policy, for the user with the name and email.
+    pub fn validate_password(
+        password: &str,
+        name: &str,
+        email: &str,
+        password_policy: &dyn PasswordPolicy,
+    ) -> Result<(), UserErrors> {
+        Ok(password_policy.check(password, name, email)?)
+    }
+
+    /// Checks an email users can be created with or change to against the email policy.
+    pub fn validate_email(email: &str, email_policy: &dyn EmailPolicy) -> Result<(), UserErrors> {
+        let email = email_policy.parse(email)?;
+
+        Ok(email_policy.check(&email)?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         id: &'a str,
         name: &'a str,
         password: &'a str,
         email: &'a str,
+        password_policy: &dyn PasswordPolicy,
+        email_policy: &dyn EmailPolicy,
+        password_hasher: &dyn PasswordHasher,
+        audit: UserAudit,
     ) -> Result<User<'a>, UserErrors> {
+        UserErrors::collect(
+            [
+                UserID::validate(id).err().map(UserErrors::from),
+                User::validate_name(name).err(),
+                User::validate_password(password, name, email, password_policy).err(),
+                User::validate_email(email, email_policy).err(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        )?;
+
         Ok(User {
             id: UserID::try_from(id)?,
             name: UserName::try_from(name)?,
-            password: UserPassword::new(&password)?,
-            email: UserEmail::try_from(email)?,
+            password: UserPassword::new(password, password_hasher)?,
+            email: email_policy.parse(email)?,
+            email_verification: EmailVerification::Unverified,
+            status: UserStatus::Pending,
+            audit,
+        })
+
+        // TODO : Event Driven Design (Create Events)
+    }
+
+    /// Creates a user with a newly generated id.
+    pub fn create_with_new_id(
+        name: &'a str,
+        password: &'a str,
+        email: &'a str,
+        password_policy: &dyn PasswordPolicy,
+        email_policy: &dyn EmailPolicy,
+        password_hasher: &dyn PasswordHasher,
+        audit: UserAudit,
+    ) -> Result<User<'a>, UserErrors> {
+        UserErrors::collect(
+            [
+                User::validate_name(name).err(),
+                User::validate_password(password, name, email, password_policy).err(),
+                User::validate_email(email, email_policy).err(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        )?;
+
+        Ok(User {
+            id: UserID::new(),
+            name: UserName::try_from(name)?,
+            password: UserPassword::new(password, password_hasher)?,
+            email: email_policy.parse(email)?,
+            email_verification: EmailVerification::Unverified,
+            status: UserStatus::Pending,
+            audit,
         })
 
         // TODO : Event Driven Design (Create Events)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         self,
         name: Option<&'a str>,
         password: Option<&'a str>,
         email: Option<&'a str>,
+        password_policy: &dyn PasswordPolicy,
+        email_policy: &dyn EmailPolicy,
+        password_hasher: &dyn PasswordHasher,
+        audit: UserAudit,
     ) -> Result<User<'a>, UserErrors> {
+        let checked_name = name.unwrap_or(self.name.get());
+        let checked_email = email.unwrap_or(self.email.get());
+
+        // The same email written differently isn't a new one.
+        let new_email = match email.map(|email| email_policy.parse(email)) {
+            Some(Ok(email)) if email.get() == self.email.get() => None,
+            new_email => new_email,
+        }
+        .map(|email| email.and_then(|email| email_policy.check(&email).map(|()| email)))
+        .transpose();
+
+        let (new_email, email_error) = match new_email {
+            Ok(new_email) => (new_email, None),
+            Err(error) => (None, Some(error)),
+        };
+
+        UserErrors::collect(
+            [
+                name.and_then(|name| User::validate_name(name).err()),
+                password.and_then(|password| {
+                    User::validate_password(password, checked_name, checked_email, password_policy)
+                        .err()
+                }),
+                email_error.map(UserErrors::from),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        )?;
+
         let password = match password {
             None => self.password,
-            Some(password) => UserPassword::new(password)?,
+            Some(password) => UserPassword::new(password, password_hasher)?,
         };
 
         let name = match name {
@@ -99,9 +278,10 @@ impl<'a> User<'a> {
             Some(name) => UserName::try_from(name)?,
         };
 
-        let email = match email {
-            None => self.email,
-            Some(email) => UserEmail::try_from(email)?,
+        // A new email has to be verified again.
+        let (email, email_verification) = match new_email {
+            Some(email) => (email, EmailVerification::Unverified),
+            None => (self.email, self.email_verification),
         };
 
         Ok(User {
@@ -109,32 +289,234 @@ impl<'a> User<'a> {
             name,
             password,
             email,
+            email_verification,
+            status: self.status,
+            audit,
         })
 
         // TODO : Event Driven Design (Update Events)
     }
 
-    pub fn delete(&self) {
+    /// Replaces the password hash with a new one of the same password, made with the current
+    /// hashing parameters.
+    pub fn rehash_password(
+        self,
+        password: &str,
+        password_hasher: &dyn PasswordHasher,
+    ) -> Result<User<'a>, UserErrors> {
+        Ok(User {
+            password: UserPassword::new(password, password_hasher)?,
+            ..self
+        })
+    }
+
+    /// Marks the email of the user as verified from the given time on, the user proved it.
+    /// Pending users become active.
+    pub fn verify_email(self, at: DateTime<Utc>) -> User<'a> {
+        User {
+            email_verification: EmailVerification::Verified(at),
+            status: self.status.activate().unwrap_or_else(|_| self.status.clone()),
+            audit: self.audit.updated(at, Some(self.id.get())),
+            ..self
+        }
+    }
+
+    /// Suspends the user for the given reason until it's reactivated.
+    pub fn suspend(
+        self,
+        reason: &str,
+        at: DateTime<Utc>,
+        by: Option<&str>,
+    ) -> Result<User<'a>, UserErrors> {
+        let status = self.status.suspend(reason)?;
+
+        Ok(self.with_status(status, at, by))
+    }
+
+    /// Brings back a suspended or locked user.
+    pub fn reactivate(self, at: DateTime<Utc>, by: Option<&str>) -> Result<User<'a>, UserErrors> {
+        let status = self.status.reactivate()?;
+
+        Ok(self.with_status(status, at, by))
+    }
+
+    /// Locks the user out, after too many failed logins or by an administrator.
+    pub fn lock(self, at: DateTime<Utc>, by: Option<&str>) -> Result<User<'a>, UserErrors> {
+        let status = self.status.lock()?;
+
+        Ok(self.with_status(status, at, by))
+    }
+
+    /// Closes the account of the user for good, it's kept but can't be used anymore.
+    pub fn close(self, at: DateTime<Utc>, by: Option<&str>) -> Result<User<'a>, UserErrors> {
+        let status = self.status.close()?;
+
+        Ok(self.with_status(status, at, by))
+    }
+
+    fn with_status(self, status: UserStatus, at: DateTime<Utc>, by: Option<&str>) -> User<'a> {
+        User {
+            status,
+            audit: self.audit.updated(at, by),
+            ..self
+        }
+    }
+
+    /// Records a change of the user kept outside of it, like resetting its two-factor
+    /// authentication.
+    pub fn record_change(self, at: DateTime<Utc>, by: Option<&str>) -> User<'a> {
+        User {
+            audit: self.audit.updated(at, by),
+            ..self
+        }
+    }
+
+    /// Records a login of the user at the given time.
+    pub fn log_in(self, at: DateTime<Utc>) -> User<'a> {
+        User {
+            audit: self.audit.logged_in(at),
+            ..self
+        }
+    }
+
+    /// Keeps the verification of the previous state of the user when the email didn't change.
+    pub fn keep_email_verification(self, previous: &User) -> User<'a> {
+        if self.email.get() != previous.email.get() {
+            return self;
+        }
+
+        User {
+            email_verification: previous.email_verification,
+            ..self
+        }
+    }
+
+    /// Keeps the status of the previous state of the user, it only changes through its
+    /// transitions.
+    pub fn keep_status(self, previous: &User) -> User<'a> {
+        User {
+            status: previous.status.clone(),
+            ..self
+        }
+    }
+
+    /// Marks the user as deleted at the given time, it's kept until purged so it can be restored.
+    pub fn delete(self, at: DateTime<Utc>, by: Option<&str>) -> User<'a> {
+        User {
+            audit: self.audit.deleted(at, by),
+            ..self
+        }
+
         // TODO : Event Driven Design (Delete Events)
     }
 
+    /// Brings back a deleted user as it was before being deleted.
+    pub fn restore(self, at: DateTime<Utc>, by: Option<&str>) -> User<'a> {
+        User {
+            audit: self.audit.restored(at, by),
+            ..self
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.audit.get_deleted_at().is_some()
+    }
+
     pub fn get_id(&self) -> &str {
         self.id.get()
     }
 
     pub fn get_name(&self) -> &str {
-        &self.name.get()
+        self.name.get()
     }
 
     pub fn get_password(&self) -> &str {
-        &self.password.get()
+        self.password.get()
     }
 
     pub fn get_email(&self) -> &str {
-        &self.email.get()
+        self.email.get()
+    }
+
+    /// Form of the email compared to tell whether two users have the same one.
+    pub fn get_normalized_email(&self) -> &str {
+        self.email.get_normalized()
+    }
+
+    pub fn get_email_verification(&self) -> EmailVerification {
+        self.email_verification
+    }
+
+    pub fn get_status(&self) -> &UserStatus {
+        &self.status
+    }
+
+    pub fn get_audit(&self) -> &UserAudit {
+        &self.audit
     }
     
     pub fn into_inners(self) -> (String, String, String, String) {
         (self.id.into_owned(), self.name.into_owned(), self.password.into_owned(), self.email.into_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{
+        now, EmailPolicyPlain, PasswordHasherCheap, PasswordPolicyAcceptAll, PASSWORD, USER_ID,
+    };
+
+    fn create<'a>(id: &'a str, name: &'a str, email: &'a str) -> Result<User<'a>, UserErrors> {
+        User::create(
+            id,
+            name,
+            PASSWORD,
+            email,
+            &PasswordPolicyAcceptAll,
+            &EmailPolicyPlain,
+            &PasswordHasherCheap,
+            UserAudit::created(now(), None),
+        )
+    }
+
+    #[test]
+    fn reports_every_invalid_field_at_once() {
+        let result = create("not an id", "Jane", "not an email");
+
+        let Err(UserErrors::InvalidFields(errors)) = result else {
+            panic!("Expected every invalid field, got {result:?}");
+        };
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], UserErrors::UserIDError { .. }));
+        assert!(matches!(errors[1], UserErrors::UserNameError { .. }));
+        assert!(matches!(errors[2], UserErrors::UserEmailError { .. }));
+    }
+
+    #[test]
+    fn reports_a_single_invalid_field_as_is() {
+        let result = create(USER_ID, "Jane", "jane@example.com");
+
+        assert!(matches!(result, Err(UserErrors::UserNameError { .. })));
+    }
+
+    #[test]
+    fn reports_every_invalid_change_at_once() {
+        let user = create(USER_ID, "Jane Doe", "jane@example.com").unwrap();
+
+        let result = user.update(
+            Some("Jane"),
+            None,
+            Some("not an email"),
+            &PasswordPolicyAcceptAll,
+            &EmailPolicyPlain,
+            &PasswordHasherCheap,
+            UserAudit::created(now(), None),
+        );
+
+        assert!(matches!(
+            result,
+            Err(UserErrors::InvalidFields(errors)) if errors.len() == 2
+        ));
+    }
+}
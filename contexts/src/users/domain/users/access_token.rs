@@ -0,0 +1,73 @@
+use chrono::{DateTime, Duration, Utc};
+use shaku::Interface;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AccessTokenErrors {
+    #[error("The access token is malformed or its signature doesn't match")]
+    Invalid,
+    #[error("The access token expired")]
+    Expired,
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+/// Short-lived token proving a session of a user, checked without a database lookup of its own.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AccessToken {
+    user_id: String,
+    session_id: String,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl AccessToken {
+    pub fn new(
+        user_id: &str,
+        session_id: &str,
+        issued_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        AccessToken {
+            user_id: user_id.to_string(),
+            session_id: session_id.to_string(),
+            issued_at,
+            expires_at,
+        }
+    }
+
+    pub fn issue(user_id: &str, session_id: &str, lifetime: Duration, now: DateTime<Utc>) -> Self {
+        AccessToken::new(user_id, session_id, now, now + lifetime)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn get_issued_at(&self) -> DateTime<Utc> {
+        self.issued_at
+    }
+
+    pub fn get_expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
+
+pub trait AccessTokenSigner: Interface {
+    /// Encodes the token into the value sent to the user, signed so it can't be forged.
+    fn sign(&self, token: &AccessToken) -> Result<String, AccessTokenErrors>;
+    /// Decodes a value made by this signer, failing when its signature doesn't match. Expiry is
+    /// left to the caller.
+    fn verify(&self, value: &str) -> Result<AccessToken, AccessTokenErrors>;
+}
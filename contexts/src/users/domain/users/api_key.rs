@@ -0,0 +1,365 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::users::domain::users::token_secret;
+
+/// Characters of the public part of a key, identifying it in lookups and listings.
+const PREFIX_LENGTH: usize = 12;
+/// Maximum characters of the name of a key.
+const MAX_NAME_LENGTH: usize = 100;
+/// Seconds the last use of a key is recorded to, so busy keys aren't written on every request.
+const LAST_USED_PRECISION: i64 = 60;
+
+#[derive(Error, Debug)]
+pub enum ApiKeyErrors {
+    #[error("The name of the API key can't be empty nor longer than {MAX_NAME_LENGTH} characters")]
+    InvalidName,
+    #[error("Unknown API key scope {0}, the scopes are read and write")]
+    UnknownScope(String),
+    #[error("An API key needs at least one scope")]
+    MissingScope,
+    #[error("An API key can't last longer than {0} days")]
+    InvalidLifetime(u32),
+}
+
+/// Lifetimes of the API keys, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApiKeyConfig {
+    /// Days a key lasts when created without a lifetime.
+    pub default_lifetime: u32,
+    /// Maximum days a key can last.
+    pub max_lifetime: u32,
+}
+
+impl Default for ApiKeyConfig {
+    fn default() -> Self {
+        ApiKeyConfig {
+            default_lifetime: 90,
+            max_lifetime: 365,
+        }
+    }
+}
+
+impl ApiKeyConfig {
+    /// Lifetime of a new key, the default one when none is asked for.
+    pub fn lifetime(&self, days: Option<u32>) -> Result<Duration, ApiKeyErrors> {
+        let days = days.unwrap_or(self.default_lifetime);
+        if days == 0 || days > self.max_lifetime {
+            return Err(ApiKeyErrors::InvalidLifetime(self.max_lifetime));
+        }
+
+        Ok(Duration::days(days.into()))
+    }
+}
+
+/// What an API key lets its holder do, read requests need `read` and every other request needs
+/// `write`, neither implies the other.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ApiKeyScope {
+    Read,
+    Write,
+}
+
+impl ApiKeyScope {
+    pub const READ: &'static str = "read";
+    pub const WRITE: &'static str = "write";
+
+    pub fn get(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => ApiKeyScope::READ,
+            ApiKeyScope::Write => ApiKeyScope::WRITE,
+        }
+    }
+}
+
+impl TryFrom<&str> for ApiKeyScope {
+    type Error = ApiKeyErrors;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            ApiKeyScope::READ => Ok(ApiKeyScope::Read),
+            ApiKeyScope::WRITE => Ok(ApiKeyScope::Write),
+            _ => Err(ApiKeyErrors::UnknownScope(value.to_owned())),
+        }
+    }
+}
+
+impl Display for ApiKeyScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
+
+/// Personal key letting a machine client act as its user without logging in. Only the hash of
+/// its secret is kept, the key is looked up by its prefix.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    id: String,
+    user_id: String,
+    name: String,
+    prefix: String,
+    secret_hash: String,
+    scopes: Vec<ApiKeyScope>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: &str,
+        user_id: &str,
+        name: &str,
+        prefix: &str,
+        secret_hash: &str,
+        scopes: Vec<ApiKeyScope>,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+        last_used_at: Option<DateTime<Utc>>,
+        revoked_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        ApiKey {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            secret_hash: secret_hash.to_string(),
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            revoked_at,
+        }
+    }
+
+    /// Creates a key for the user, returning it along with the value to send to the user.
+    pub fn create(
+        user_id: &str,
+        name: &str,
+        scopes: &[&str],
+        lifetime: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<(Self, String), ApiKeyErrors> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(ApiKeyErrors::InvalidName);
+        }
+
+        let mut parsed = Vec::new();
+        for scope in scopes {
+            let scope = ApiKeyScope::try_from(scope.trim())?;
+            if !parsed.contains(&scope) {
+                parsed.push(scope);
+            }
+        }
+        if parsed.is_empty() {
+            return Err(ApiKeyErrors::MissingScope);
+        }
+
+        let prefix: String = token_secret::generate()
+            .chars()
+            .take(PREFIX_LENGTH)
+            .collect();
+        let secret = token_secret::generate();
+
+        let api_key = ApiKey {
+            id: Uuid::now_v7().to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            secret_hash: token_secret::hash(&secret),
+            scopes: parsed,
+            created_at: now,
+            expires_at: now + lifetime,
+            last_used_at: None,
+            revoked_at: None,
+            prefix,
+        };
+
+        let value = token_secret::join(&api_key.prefix, &secret);
+
+        Ok((api_key, value))
+    }
+
+    /// Splits the value sent to the user into the prefix of the key and its secret.
+    pub fn parse(value: &str) -> Option<(&str, &str)> {
+        token_secret::split(value)
+    }
+
+    /// Whether the secret belongs to this key, compared in constant time.
+    pub fn matches(&self, secret: &str) -> bool {
+        token_secret::matches(secret, &self.secret_hash)
+    }
+
+    /// Whether the key can still be used, neither revoked nor expired.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+
+    pub fn allows(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Whether a use now is covered by the recorded last use, which is only updated once a minute.
+    pub fn is_use_recorded(&self, now: DateTime<Utc>) -> bool {
+        self.last_used_at
+            .is_some_and(|at| now - at < Duration::seconds(LAST_USED_PRECISION))
+    }
+
+    pub fn used(self, now: DateTime<Utc>) -> Self {
+        ApiKey {
+            last_used_at: Some(now),
+            ..self
+        }
+    }
+
+    pub fn revoke(self, now: DateTime<Utc>) -> Self {
+        ApiKey {
+            revoked_at: Some(now),
+            ..self
+        }
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Public part of the key, shown to tell the keys apart.
+    pub fn get_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn get_secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+
+    pub fn get_scopes(&self) -> &[ApiKeyScope] {
+        &self.scopes
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn get_expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    pub fn get_last_used_at(&self) -> Option<DateTime<Utc>> {
+        self.last_used_at
+    }
+
+    pub fn get_revoked_at(&self) -> Option<DateTime<Utc>> {
+        self.revoked_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::{now, USER_ID};
+
+    fn create(scopes: &[&str]) -> (ApiKey, String) {
+        ApiKey::create(USER_ID, " CI ", scopes, Duration::days(90), now()).unwrap()
+    }
+
+    #[test]
+    fn keeps_only_a_hash_of_the_secret_and_hands_out_the_prefix() {
+        let (api_key, value) = create(&["read", "read"]);
+        let (prefix, secret) = ApiKey::parse(&value).unwrap();
+
+        assert_eq!(prefix, api_key.get_prefix());
+        assert_eq!(prefix.len(), PREFIX_LENGTH);
+        assert_eq!(api_key.get_secret_hash(), token_secret::hash(secret));
+        assert!(!api_key.get_secret_hash().contains(secret));
+        assert!(api_key.matches(secret));
+        assert!(!api_key.matches(&token_secret::generate()));
+
+        assert_eq!(api_key.get_name(), "CI");
+        assert_eq!(api_key.get_scopes(), [ApiKeyScope::Read]);
+        assert_eq!(api_key.get_expires_at(), now() + Duration::days(90));
+    }
+
+    #[test]
+    fn prefixes_and_secrets_differ_between_keys() {
+        let (first, first_value) = create(&["read"]);
+        let (second, second_value) = create(&["read"]);
+
+        assert_ne!(first.get_prefix(), second.get_prefix());
+        assert_ne!(first.get_secret_hash(), second.get_secret_hash());
+        assert_ne!(first_value, second_value);
+    }
+
+    #[test]
+    fn refuses_invalid_names_and_scopes() {
+        let lifetime = Duration::days(90);
+
+        assert!(matches!(
+            ApiKey::create(USER_ID, "  ", &["read"], lifetime, now()),
+            Err(ApiKeyErrors::InvalidName)
+        ));
+        assert!(matches!(
+            ApiKey::create(USER_ID, "CI", &[], lifetime, now()),
+            Err(ApiKeyErrors::MissingScope)
+        ));
+        assert!(matches!(
+            ApiKey::create(USER_ID, "CI", &["read", "admin"], lifetime, now()),
+            Err(ApiKeyErrors::UnknownScope(scope)) if scope == "admin"
+        ));
+    }
+
+    #[test]
+    fn lifetimes_default_and_are_capped() {
+        let config = ApiKeyConfig::default();
+
+        assert_eq!(config.lifetime(None).unwrap(), Duration::days(90));
+        assert_eq!(config.lifetime(Some(365)).unwrap(), Duration::days(365));
+        assert!(matches!(
+            config.lifetime(Some(366)),
+            Err(ApiKeyErrors::InvalidLifetime(365))
+        ));
+        assert!(matches!(
+            config.lifetime(Some(0)),
+            Err(ApiKeyErrors::InvalidLifetime(365))
+        ));
+    }
+
+    #[test]
+    fn keys_are_active_until_they_expire_or_are_revoked() {
+        let (api_key, _) = create(&["read"]);
+        let expires_at = api_key.get_expires_at();
+
+        assert!(api_key.is_active(expires_at - Duration::seconds(1)));
+        assert!(!api_key.is_active(expires_at));
+
+        let revoked = api_key.revoke(now());
+
+        assert_eq!(revoked.get_revoked_at(), Some(now()));
+        assert!(!revoked.is_active(now()));
+    }
+
+    #[test]
+    fn uses_are_recorded_once_a_minute() {
+        let (api_key, _) = create(&["read"]);
+        assert!(!api_key.is_use_recorded(now()));
+
+        let used = api_key.used(now());
+
+        assert!(used.is_use_recorded(now() + Duration::seconds(59)));
+        assert!(!used.is_use_recorded(now() + Duration::seconds(60)));
+    }
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::api_key::ApiKey;
+
+#[derive(Error, Debug)]
+pub enum ApiKeyRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, ApiKeyRepositoryErrors>;
+
+pub trait ApiKeyRepository: Interface {
+    fn save(&self, api_key: &ApiKey) -> Result<()>;
+    /// Saves when the key was last used and revoked.
+    fn update(&self, api_key: &ApiKey) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<ApiKey>>;
+    fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKey>>;
+    /// Finds the keys of the user that weren't revoked nor expired, the latest created first.
+    fn find_active_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<Vec<ApiKey>>;
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::iter;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use shaku::{Component, Interface};
+
+use crate::users::domain::users::user_email::UserEmailErrors::DisposableDomain;
+use crate::users::domain::users::user_email::{UserEmail, UserEmailErrors};
+
+/// Rules emails must follow, configured per deployment.
+///
+/// The emails saved are normalized again with the rules on startup, which is refused while users
+/// would end up sharing an email.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailPolicyRules {
+    /// Providers delivering differently written emails to the same mailbox.
+    pub providers: Vec<EmailProviderRules>,
+    /// File with a disposable domain per line, whose emails and the ones of its subdomains
+    /// aren't accepted. Empty lines and lines starting with `#` are skipped.
+    pub disposable_domains_file: Option<PathBuf>,
+}
+
+/// How a provider tells emails apart, beyond ignoring case.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailProviderRules {
+    /// Lowercase ASCII domains of the provider, every one of them is compared as the first.
+    pub domains: Vec<String>,
+    /// Separator of the tag ignored at the end of the local part, like the `+` of plus
+    /// addressing.
+    pub tag_separator: Option<char>,
+    /// Whether the dots of the local part are ignored.
+    pub ignore_dots: bool,
+}
+
+pub trait EmailPolicy: Interface {
+    /// Parses an email, normalizing it with the rules of its provider.
+    fn parse<'a>(&self, email: &'a str) -> Result<UserEmail<'a>, UserEmailErrors>;
+    /// Checks that an email can be given to a user, when it's new or changes.
+    fn check(&self, email: &UserEmail) -> Result<(), UserEmailErrors>;
+}
+
+#[derive(Component)]
+#[shaku(interface = EmailPolicy)]
+pub struct EmailPolicyService {
+    rules: EmailPolicyRules,
+    /// Lowercase disposable domains, loaded from the file of the rules.
+    disposable_domains: HashSet<String>,
+}
+
+impl EmailPolicyService {
+    fn provider(&self, domain: &str) -> Option<&EmailProviderRules> {
+        self.rules
+            .providers
+            .iter()
+            .find(|provider| provider.domains.iter().any(|known| known == domain))
+    }
+
+    fn normalize(&self, local_part: &str, domain: &str) -> String {
+        // Quoted local parts are compared as they are, they rarely follow the provider rules.
+        if local_part.starts_with('"') {
+            return format!("{local_part}@{domain}");
+        }
+
+        let local_part = local_part.to_lowercase();
+
+        let Some(provider) = self.provider(domain) else {
+            return format!("{local_part}@{domain}");
+        };
+
+        let mut local_part = local_part.as_str();
+        if let Some((untagged, _)) = provider
+            .tag_separator
+            .and_then(|separator| local_part.split_once(separator))
+            .filter(|(untagged, _)| !untagged.is_empty())
+        {
+            local_part = untagged;
+        }
+
+        let local_part = if provider.ignore_dots {
+            local_part.replace('.', "")
+        } else {
+            local_part.to_owned()
+        };
+
+        let domain = provider.domains.first().map_or(domain, String::as_str);
+
+        format!("{local_part}@{domain}")
+    }
+}
+
+impl EmailPolicy for EmailPolicyService {
+    fn parse<'a>(&self, email: &'a str) -> Result<UserEmail<'a>, UserEmailErrors> {
+        UserEmail::parse(email, |local_part, domain| {
+            self.normalize(local_part, domain)
+        })
+    }
+
+    fn check(&self, email: &UserEmail) -> Result<(), UserEmailErrors> {
+        let mut domains = iter::successors(Some(email.get_domain()), |domain| {
+            domain.split_once('.').map(|(_, parent)| parent)
+        });
+
+        if domains.any(|domain| self.disposable_domains.contains(domain)) {
+            return Err(DisposableDomain);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> EmailPolicyService {
+        EmailPolicyService {
+            rules: EmailPolicyRules {
+                providers: vec![EmailProviderRules {
+                    domains: vec!["gmail.com".to_owned(), "googlemail.com".to_owned()],
+                    tag_separator: Some('+'),
+                    ignore_dots: true,
+                }],
+                disposable_domains_file: None,
+            },
+            disposable_domains: HashSet::from(["mailinator.com".to_owned()]),
+        }
+    }
+
+    fn normalized(email: &str) -> String {
+        policy().parse(email).unwrap().get_normalized().to_owned()
+    }
+
+    #[test]
+    fn ignores_the_case_of_unquoted_local_parts() {
+        assert_eq!(normalized("John.Doe@Example.com"), "john.doe@example.com");
+    }
+
+    #[test]
+    fn keeps_quoted_local_parts_as_they_are() {
+        assert_eq!(
+            normalized(r#""John Doe"@Example.com"#),
+            r#""John Doe"@example.com"#
+        );
+        assert_eq!(
+            normalized(r#""John.Doe+tag"@gmail.com"#),
+            r#""John.Doe+tag"@gmail.com"#
+        );
+    }
+
+    #[test]
+    fn applies_the_rules_of_the_provider() {
+        assert_eq!(normalized("John.Doe+news@gmail.com"), "johndoe@gmail.com");
+        assert_eq!(normalized("johndoe@googlemail.com"), "johndoe@gmail.com");
+        assert_eq!(normalized("+news@gmail.com"), "+news@gmail.com");
+    }
+
+    #[test]
+    fn leaves_other_providers_alone() {
+        assert_eq!(
+            normalized("john.doe+news@example.com"),
+            "john.doe+news@example.com"
+        );
+    }
+
+    #[test]
+    fn rejects_disposable_domains_and_their_subdomains() {
+        let policy = policy();
+
+        for email in ["john@mailinator.com", "john@eu.mailinator.com"] {
+            let email = policy.parse(email).unwrap();
+            assert!(matches!(policy.check(&email), Err(DisposableDomain)));
+        }
+
+        let email = policy.parse("john@notmailinator.com").unwrap();
+        assert!(policy.check(&email).is_ok());
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Whether the user proved to own its email.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum EmailVerification {
+    #[default]
+    Unverified,
+    Verified(DateTime<Utc>),
+}
+
+impl EmailVerification {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, EmailVerification::Verified(_))
+    }
+
+    pub fn verified_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            EmailVerification::Unverified => None,
+            EmailVerification::Verified(at) => Some(*at),
+        }
+    }
+}
+
+impl From<Option<DateTime<Utc>>> for EmailVerification {
+    fn from(value: Option<DateTime<Utc>>) -> Self {
+        value.map_or(EmailVerification::Unverified, EmailVerification::Verified)
+    }
+}
+
+/// Actions that can be restricted to users with a verified email.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifiedOnlyAction {
+    Login,
+    PasswordReset,
+}
@@ -0,0 +1,24 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::email_verification_token::EmailVerificationToken;
+
+#[derive(Error, Debug)]
+pub enum EmailVerificationTokenRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, EmailVerificationTokenRepositoryErrors>;
+
+pub trait EmailVerificationTokenRepository: Interface {
+    fn save(&self, token: &EmailVerificationToken) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<EmailVerificationToken>>;
+    /// Deletes the token, returning `false` when it was already gone.
+    fn delete_by(&self, id: &str) -> Result<bool>;
+    fn delete_by_user(&self, user_id: &str) -> Result<()>;
+}
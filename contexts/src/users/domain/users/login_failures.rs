@@ -0,0 +1,253 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+/// Lockouts in a row past which the lockout stops doubling, way past any sensible maximum.
+const MAX_DOUBLINGS: u32 = 20;
+
+/// Thresholds of the failed logins tolerated before locking out, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoginThrottlingRules {
+    /// Minutes failures are remembered for, counted from the last one or the end of the lockout.
+    pub window: u32,
+    /// Minutes of the first lockout, doubled on every lockout in a row.
+    pub lockout: u32,
+    /// Maximum minutes of a lockout.
+    pub max_lockout: u32,
+    /// Failed logins of an account before it's locked out, 0 never locks it out.
+    pub account_max_failures: u32,
+    /// Failed logins from an IP before it's locked out, across accounts, 0 never locks it out.
+    pub ip_max_failures: u32,
+    /// Lockouts in a row after which the account is locked until an administrator unlocks it,
+    /// 0 never locks it. Anyone knowing the email of the account can lock it this way.
+    pub lock_after_lockouts: u32,
+}
+
+impl Default for LoginThrottlingRules {
+    fn default() -> Self {
+        LoginThrottlingRules {
+            window: 15,
+            lockout: 5,
+            max_lockout: 1440,
+            account_max_failures: 5,
+            ip_max_failures: 20,
+            lock_after_lockouts: 0,
+        }
+    }
+}
+
+/// Failed logins recorded for an account or an IP, locked out for a while once there are too
+/// many of them.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LoginFailures {
+    key: String,
+    failures: u32,
+    lockouts: u32,
+    last_failed_at: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginFailures {
+    pub fn new(
+        key: &str,
+        failures: u32,
+        lockouts: u32,
+        last_failed_at: DateTime<Utc>,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Self {
+        LoginFailures {
+            key: key.to_string(),
+            failures,
+            lockouts,
+            last_failed_at,
+            locked_until,
+        }
+    }
+
+    /// Key of the failures of the account with the given normalized email, which is counted
+    /// whether a user has it or not so locking out doesn't tell which emails are registered.
+    pub fn account_key(normalized_email: &str) -> String {
+        format!("account:{normalized_email}")
+    }
+
+    pub fn ip_key(ip: IpAddr) -> String {
+        format!("ip:{ip}")
+    }
+
+    /// No failure recorded yet for the key.
+    pub fn none(key: &str, now: DateTime<Utc>) -> Self {
+        LoginFailures::new(key, 0, 0, now, None)
+    }
+
+    /// Time left until the key can log in again, missing when it isn't locked out.
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.locked_until
+            .filter(|locked_until| *locked_until > now)
+            .map(|locked_until| locked_until - now)
+    }
+
+    /// Records a failed login, locking out the key once it reaches the maximum failures, for
+    /// a time doubling with every lockout in a row.
+    pub fn failed(
+        self,
+        at: DateTime<Utc>,
+        max_failures: u32,
+        rules: &LoginThrottlingRules,
+    ) -> LoginFailures {
+        let remembered_since = self
+            .locked_until
+            .map_or(self.last_failed_at, |until| until.max(self.last_failed_at));
+        let forgotten = at - remembered_since > Duration::minutes(rules.window.into());
+
+        let (failures, lockouts) = if forgotten {
+            (1, 0)
+        } else {
+            (self.failures + 1, self.lockouts)
+        };
+
+        if max_failures == 0 || failures < max_failures {
+            return LoginFailures {
+                failures,
+                lockouts,
+                last_failed_at: at,
+                locked_until: self.locked_until.filter(|_| !forgotten),
+                ..self
+            };
+        }
+
+        let doublings = lockouts.min(MAX_DOUBLINGS);
+        let minutes = (i64::from(rules.lockout) << doublings).min(rules.max_lockout.into());
+
+        LoginFailures {
+            failures: 0,
+            lockouts: lockouts + 1,
+            last_failed_at: at,
+            locked_until: Some(at + Duration::minutes(minutes)),
+            ..self
+        }
+    }
+
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn get_failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Lockouts in a row, forgotten along with the failures.
+    pub fn get_lockouts(&self) -> u32 {
+        self.lockouts
+    }
+
+    pub fn get_last_failed_at(&self) -> DateTime<Utc> {
+        self.last_failed_at
+    }
+
+    pub fn get_locked_until(&self) -> Option<DateTime<Utc>> {
+        self.locked_until
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const KEY: &str = "account:john@example.com";
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    fn rules() -> LoginThrottlingRules {
+        LoginThrottlingRules {
+            window: 15,
+            lockout: 5,
+            max_lockout: 30,
+            account_max_failures: 3,
+            ip_max_failures: 0,
+            lock_after_lockouts: 0,
+        }
+    }
+
+    /// Fails as many times at once as it takes to lock out.
+    fn lock_out(failures: LoginFailures, at: DateTime<Utc>) -> LoginFailures {
+        let rules = rules();
+
+        (0..rules.account_max_failures).fold(failures, |failures, _| {
+            failures.failed(at, rules.account_max_failures, &rules)
+        })
+    }
+
+    #[test]
+    fn locks_out_once_the_maximum_failures_is_reached() {
+        let rules = rules();
+        let failures = LoginFailures::none(KEY, start())
+            .failed(start(), 3, &rules)
+            .failed(start(), 3, &rules);
+
+        assert_eq!(failures.get_failures(), 2);
+        assert_eq!(failures.retry_after(start()), None);
+
+        let failures = failures.failed(start(), 3, &rules);
+
+        assert_eq!(failures.get_failures(), 0);
+        assert_eq!(failures.get_lockouts(), 1);
+        assert_eq!(failures.retry_after(start()), Some(Duration::minutes(5)));
+        assert_eq!(
+            failures.retry_after(start() + Duration::minutes(2)),
+            Some(Duration::minutes(3))
+        );
+        assert_eq!(failures.retry_after(start() + Duration::minutes(5)), None);
+    }
+
+    #[test]
+    fn doubles_the_lockouts_in_a_row_up_to_the_maximum() {
+        let first = lock_out(LoginFailures::none(KEY, start()), start());
+        let first_end = first.get_locked_until().unwrap();
+        assert_eq!(first_end - start(), Duration::minutes(5));
+
+        let second = lock_out(first, first_end);
+        let second_end = second.get_locked_until().unwrap();
+        assert_eq!(second.get_lockouts(), 2);
+        assert_eq!(second_end - first_end, Duration::minutes(10));
+
+        let third = lock_out(second, second_end);
+        let third_end = third.get_locked_until().unwrap();
+        assert_eq!(third_end - second_end, Duration::minutes(20));
+
+        let fourth = lock_out(third, third_end);
+        assert_eq!(
+            fourth.get_locked_until().unwrap() - third_end,
+            Duration::minutes(30)
+        );
+    }
+
+    #[test]
+    fn forgets_failures_and_lockouts_after_the_window() {
+        let rules = rules();
+        let locked_out = lock_out(LoginFailures::none(KEY, start()), start());
+        let later = locked_out.get_locked_until().unwrap() + Duration::minutes(16);
+
+        let failures = locked_out.failed(later, 3, &rules);
+
+        assert_eq!(failures.get_failures(), 1);
+        assert_eq!(failures.get_lockouts(), 0);
+        assert_eq!(failures.get_locked_until(), None);
+    }
+
+    #[test]
+    fn never_locks_out_without_a_maximum() {
+        let rules = rules();
+        let failures = (0..100).fold(LoginFailures::none(KEY, start()), |failures, _| {
+            failures.failed(start(), 0, &rules)
+        });
+
+        assert_eq!(failures.get_failures(), 100);
+        assert_eq!(failures.retry_after(start()), None);
+    }
+}
@@ -0,0 +1,23 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::login_failures::LoginFailures;
+
+#[derive(Error, Debug)]
+pub enum LoginFailuresRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, LoginFailuresRepositoryErrors>;
+
+pub trait LoginFailuresRepository: Interface {
+    fn find_by(&self, key: &str) -> Result<Option<LoginFailures>>;
+    /// Saves the failures of the key, replacing the ones it had.
+    fn save(&self, failures: &LoginFailures) -> Result<()>;
+    fn delete_by(&self, key: &str) -> Result<()>;
+}
@@ -0,0 +1,15 @@
+use shaku::Interface;
+
+use crate::users::domain::users::user_password::UserPasswordErrors;
+
+pub trait PasswordHasher: Interface {
+    /// Hashes a plain text password into a PHC string.
+    fn hash(&self, password: &str) -> Result<String, UserPasswordErrors>;
+    /// Checks a plain text password against a PHC string made by this or a previous hasher.
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, UserPasswordErrors>;
+    /// Spends as long as verifying the password against a hash made with the current parameters,
+    /// for when there is no hash to verify it against, so the time taken doesn't tell.
+    fn verify_dummy(&self, password: &str);
+    /// Whether the hash was made with other parameters than the ones currently configured.
+    fn needs_rehash(&self, hash: &str) -> bool;
+}
@@ -0,0 +1,21 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PasswordHistoryRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, PasswordHistoryRepositoryErrors>;
+
+pub trait PasswordHistoryRepository: Interface {
+    /// Last password hashes of the user, most recent first.
+    fn find_last(&self, user_id: &str, count: usize) -> Result<Vec<String>>;
+    /// Records a password hash of the user, keeping only the `keep` most recent ones.
+    fn add(&self, user_id: &str, password: &str, keep: usize) -> Result<()>;
+}
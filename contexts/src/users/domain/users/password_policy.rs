@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use shaku::Interface;
+
+use crate::users::domain::users::user_password::UserPasswordErrors;
+
+/// Shortest password accepted unless the deployment configures another.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Longest password accepted unless the deployment configures another, hashing is slower the
+/// longer they are.
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Rules passwords must follow, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyRules {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_number: bool,
+    pub require_symbol: bool,
+    /// File with a banned password per line, compared ignoring case.
+    pub banned_passwords_file: Option<PathBuf>,
+    /// Rejects passwords containing the name of the user or the local part of its email.
+    pub forbid_user_data: bool,
+    /// Minimum zxcvbn strength score, from 0 (any password) to 4.
+    pub min_strength: u8,
+    /// Number of previous passwords that can't be reused, 0 allows reusing any.
+    pub history_size: usize,
+}
+
+impl Default for PasswordPolicyRules {
+    fn default() -> Self {
+        PasswordPolicyRules {
+            min_length: MIN_PASSWORD_LENGTH,
+            max_length: MAX_PASSWORD_LENGTH,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_number: true,
+            require_symbol: true,
+            banned_passwords_file: None,
+            forbid_user_data: false,
+            min_strength: 0,
+            history_size: 5,
+        }
+    }
+}
+
+pub trait PasswordPolicy: Interface {
+    /// Checks a plain text password of the user with the given name and email.
+    fn check(&self, password: &str, name: &str, email: &str) -> Result<(), UserPasswordErrors>;
+    /// Number of previous passwords of a user that can't be reused.
+    fn history_size(&self) -> usize;
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::users::domain::users::token_secret;
+
+/// Single-use token allowing a user to set a new password, only the hash of its secret is kept.
+#[derive(Debug, Clone)]
+pub struct PasswordResetToken {
+    id: String,
+    user_id: String,
+    secret_hash: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    pub fn new(id: &str, user_id: &str, secret_hash: &str, expires_at: DateTime<Utc>) -> Self {
+        PasswordResetToken {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            secret_hash: secret_hash.to_string(),
+            expires_at,
+        }
+    }
+
+    /// Issues a token for the user, returning it along with the value to send to the user.
+    pub fn issue(user_id: &str, lifetime: Duration, now: DateTime<Utc>) -> (Self, String) {
+        let id = Uuid::now_v7().to_string();
+        let secret = token_secret::generate();
+
+        let token = PasswordResetToken {
+            secret_hash: token_secret::hash(&secret),
+            user_id: user_id.to_string(),
+            expires_at: now + lifetime,
+            id,
+        };
+
+        let value = token_secret::join(&token.id, &secret);
+
+        (token, value)
+    }
+
+    /// Splits the value sent to the user into the id of the token and its secret.
+    pub fn parse(value: &str) -> Option<(&str, &str)> {
+        token_secret::split(value)
+    }
+
+    /// Whether the secret belongs to this token and it hasn't expired, compared in constant time.
+    pub fn matches(&self, secret: &str, now: DateTime<Utc>) -> bool {
+        token_secret::matches(secret, &self.secret_hash) && !self.is_expired(now)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+
+    pub fn get_expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+}
@@ -0,0 +1,24 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::password_reset_token::PasswordResetToken;
+
+#[derive(Error, Debug)]
+pub enum PasswordResetTokenRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, PasswordResetTokenRepositoryErrors>;
+
+pub trait PasswordResetTokenRepository: Interface {
+    fn save(&self, token: &PasswordResetToken) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<PasswordResetToken>>;
+    /// Deletes the token, returning `false` when it was already gone.
+    fn delete_by(&self, id: &str) -> Result<bool>;
+    fn delete_by_user(&self, user_id: &str) -> Result<()>;
+}
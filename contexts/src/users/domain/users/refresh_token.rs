@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::users::domain::users::token_secret;
+
+/// Token exchanged for new tokens of a session, single-use as every refresh rotates it. Rotated
+/// tokens are kept to recognize their reuse, only the hash of their secret is kept.
+#[derive(Debug, Clone)]
+pub struct RefreshToken {
+    id: String,
+    session_id: String,
+    secret_hash: String,
+    created_at: DateTime<Utc>,
+    rotated: bool,
+}
+
+impl RefreshToken {
+    pub fn new(
+        id: &str,
+        session_id: &str,
+        secret_hash: &str,
+        created_at: DateTime<Utc>,
+        rotated: bool,
+    ) -> Self {
+        RefreshToken {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            secret_hash: secret_hash.to_string(),
+            created_at,
+            rotated,
+        }
+    }
+
+    /// Issues a token for the session, returning it along with the value to send to the user.
+    pub fn issue(session_id: &str, now: DateTime<Utc>) -> (Self, String) {
+        let id = Uuid::now_v7().to_string();
+        let secret = token_secret::generate();
+
+        let token = RefreshToken {
+            secret_hash: token_secret::hash(&secret),
+            session_id: session_id.to_string(),
+            created_at: now,
+            rotated: false,
+            id,
+        };
+
+        let value = token_secret::join(&token.id, &secret);
+
+        (token, value)
+    }
+
+    /// Splits the value sent to the user into the id of the token and its secret.
+    pub fn parse(value: &str) -> Option<(&str, &str)> {
+        token_secret::split(value)
+    }
+
+    /// Whether the secret belongs to this token, compared in constant time.
+    pub fn matches(&self, secret: &str) -> bool {
+        token_secret::matches(secret, &self.secret_hash)
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn get_secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Whether the token was already exchanged, presenting it again means it leaked.
+    pub fn is_rotated(&self) -> bool {
+        self.rotated
+    }
+}
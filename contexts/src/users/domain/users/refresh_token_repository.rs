@@ -0,0 +1,23 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::refresh_token::RefreshToken;
+
+#[derive(Error, Debug)]
+pub enum RefreshTokenRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, RefreshTokenRepositoryErrors>;
+
+pub trait RefreshTokenRepository: Interface {
+    fn save(&self, token: &RefreshToken) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<RefreshToken>>;
+    /// Marks the token as rotated, returning `false` when it already was.
+    fn rotate(&self, id: &str) -> Result<bool>;
+}
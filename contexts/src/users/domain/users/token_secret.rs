@@ -0,0 +1,86 @@
+//! Secrets of the tokens mailed to the users, which are only stored hashed.
+
+use password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Random bytes of a secret.
+const SECRET_LENGTH: usize = 32;
+/// Separates the id of a token from its secret in the value sent to the user.
+const SEPARATOR: char = '.';
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Generates a random secret, hex encoded.
+pub fn generate() -> String {
+    let mut bytes = [0u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+
+    to_hex(&bytes)
+}
+
+pub fn hash(secret: &str) -> String {
+    to_hex(&Sha256::digest(secret.as_bytes()))
+}
+
+/// Whether the secret has the hash, compared in constant time.
+pub fn matches(secret: &str, secret_hash: &str) -> bool {
+    hash(secret).as_bytes().ct_eq(secret_hash.as_bytes()).into()
+}
+
+/// Joins the id of a token and its secret into the value sent to the user.
+pub fn join(id: &str, secret: &str) -> String {
+    format!("{id}{SEPARATOR}{secret}")
+}
+
+/// Splits the value sent to the user into the id of the token and its secret.
+pub fn split(value: &str) -> Option<(&str, &str)> {
+    value.split_once(SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_secrets_are_random_hex() {
+        let secret = generate();
+
+        assert_eq!(secret.len(), SECRET_LENGTH * 2);
+        assert!(secret.chars().all(|char| char.is_ascii_hexdigit()));
+        assert_ne!(secret, generate());
+    }
+
+    #[test]
+    fn hashes_are_sha256_hex() {
+        assert_eq!(
+            hash("secret"),
+            "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b"
+        );
+    }
+
+    #[test]
+    fn secrets_match_only_their_hash() {
+        let secret = generate();
+        let secret_hash = hash(&secret);
+
+        assert!(matches(&secret, &secret_hash));
+        assert!(!matches(&generate(), &secret_hash));
+        assert!(!matches(&secret, &secret_hash[1..]));
+        assert!(!matches(&secret, &secret_hash.to_uppercase()));
+        assert!(!matches(&secret, ""));
+    }
+
+    #[test]
+    fn joined_values_split_back() {
+        let value = join("01a153b2-0000-7000-8000-000000000001", "abc123");
+
+        assert_eq!(
+            split(&value),
+            Some(("01a153b2-0000-7000-8000-000000000001", "abc123"))
+        );
+        assert_eq!(split("no-separator"), None);
+    }
+}
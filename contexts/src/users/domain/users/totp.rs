@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use shaku::Interface;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TotpErrors {
+    #[error("The two-factor secret can't be used")]
+    InvalidSecret {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+/// Time-based one-time passwords as defined by RFC 6238.
+pub trait Totp: Interface {
+    /// Generates a random secret, base32 encoded.
+    fn generate_secret(&self) -> String;
+    /// `otpauth://` URI registering the secret of the account in an authenticator app.
+    fn provisioning_uri(&self, secret: &str, account: &str) -> Result<String, TotpErrors>;
+    /// Time step of the code when it's valid at the time, a step of clock drift either way is
+    /// tolerated.
+    fn verify(
+        &self,
+        secret: &str,
+        code: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<u64>, TotpErrors>;
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Duration, Utc};
+use password_hash::rand_core::{OsRng, RngCore};
+use serde::Deserialize;
+
+use crate::users::domain::users::token_secret;
+
+/// Characters of the recovery codes, without the ones easily mistaken for each other.
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+/// Characters of each of the two halves of a recovery code.
+const RECOVERY_CODE_HALF_LENGTH: usize = 5;
+
+/// Settings of the two-factor authentication, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TwoFactorConfig {
+    /// Name of the service shown by authenticator apps, it can't contain a colon.
+    pub issuer: String,
+    /// Hex encoded 256-bit key encrypting the secrets at rest, changing it disables every
+    /// enrollment.
+    pub encryption_key: String,
+    /// Minutes a login has to be completed with a code once the password was checked.
+    pub challenge_lifetime: u32,
+    /// Wrong codes tolerated per login before it has to start over.
+    pub max_attempts: u32,
+    /// Recovery codes handed out when two-factor authentication is enabled.
+    pub recovery_codes: usize,
+}
+
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        TwoFactorConfig {
+            issuer: String::from("Rust Hexagonal Architecture"),
+            encryption_key: String::new(),
+            challenge_lifetime: 5,
+            max_attempts: 5,
+            recovery_codes: 10,
+        }
+    }
+}
+
+impl TwoFactorConfig {
+    pub fn challenge_lifetime(&self) -> Duration {
+        Duration::minutes(self.challenge_lifetime.into())
+    }
+}
+
+/// TOTP (RFC 6238) enrollment of a user, pending until a first code confirms the authenticator
+/// app holds the secret. Recovery codes replace a code once each, only their hashes are kept.
+#[derive(Debug, Clone)]
+pub struct TwoFactor {
+    user_id: String,
+    secret: String,
+    confirmed_at: Option<DateTime<Utc>>,
+    last_used_step: Option<u64>,
+    recovery_code_hashes: Vec<String>,
+}
+
+impl TwoFactor {
+    pub fn new(
+        user_id: &str,
+        secret: &str,
+        confirmed_at: Option<DateTime<Utc>>,
+        last_used_step: Option<u64>,
+        recovery_code_hashes: Vec<String>,
+    ) -> Self {
+        TwoFactor {
+            user_id: user_id.to_string(),
+            secret: secret.to_string(),
+            confirmed_at,
+            last_used_step,
+            recovery_code_hashes,
+        }
+    }
+
+    /// Pending enrollment with a new secret, shared with the authenticator app of the user.
+    pub fn enroll(user_id: &str, secret: &str) -> Self {
+        TwoFactor::new(user_id, secret, None, None, vec![])
+    }
+
+    /// Whether the enrollment was confirmed, logging in needs a code from then on.
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+
+    /// Enables the enrollment once a code was verified at the step, returning the recovery codes
+    /// to show the user, which aren't kept.
+    pub fn confirm(
+        self,
+        step: u64,
+        recovery_codes: usize,
+        now: DateTime<Utc>,
+    ) -> (Self, Vec<String>) {
+        let codes: Vec<String> = (0..recovery_codes)
+            .map(|_| generate_recovery_code())
+            .collect();
+
+        let two_factor = TwoFactor {
+            confirmed_at: Some(now),
+            last_used_step: Some(step),
+            recovery_code_hashes: codes.iter().map(|code| token_secret::hash(code)).collect(),
+            ..self
+        };
+
+        (two_factor, codes)
+    }
+
+    /// Whether a code of the step can still be used, each one is only accepted once and never
+    /// after a later one.
+    pub fn accepts_step(&self, step: u64) -> bool {
+        self.last_used_step.is_none_or(|last| step > last)
+    }
+
+    pub fn used_step(self, step: u64) -> Self {
+        TwoFactor {
+            last_used_step: Some(step),
+            ..self
+        }
+    }
+
+    /// Consumes the recovery code, missing when the user has no such code left.
+    pub fn use_recovery_code(self, code: &str) -> Option<Self> {
+        let code = normalize_recovery_code(code);
+        let position = self
+            .recovery_code_hashes
+            .iter()
+            .position(|hash| token_secret::matches(&code, hash))?;
+
+        let mut recovery_code_hashes = self.recovery_code_hashes.clone();
+        recovery_code_hashes.remove(position);
+
+        Some(TwoFactor {
+            recovery_code_hashes,
+            ..self
+        })
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Base32 encoded secret, as shared with the authenticator app.
+    pub fn get_secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub fn get_confirmed_at(&self) -> Option<DateTime<Utc>> {
+        self.confirmed_at
+    }
+
+    pub fn get_last_used_step(&self) -> Option<u64> {
+        self.last_used_step
+    }
+
+    pub fn get_recovery_code_hashes(&self) -> &[String] {
+        &self.recovery_code_hashes
+    }
+}
+
+/// Random recovery code, like `k3m9p-x2vqa`.
+fn generate_recovery_code() -> String {
+    let half = || -> String {
+        (0..RECOVERY_CODE_HALF_LENGTH)
+            .map(|_| {
+                let index = OsRng.next_u32() as usize % RECOVERY_CODE_ALPHABET.len();
+                char::from(RECOVERY_CODE_ALPHABET[index])
+            })
+            .collect()
+    };
+
+    format!("{}-{}", half(), half())
+}
+
+/// Recovery codes are accepted whatever their case and surrounding spaces.
+fn normalize_recovery_code(code: &str) -> String {
+    code.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn confirmed() -> (TwoFactor, Vec<String>) {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        TwoFactor::enroll("user", "JBSWY3DPEHPK3PXP").confirm(100, 3, now)
+    }
+
+    #[test]
+    fn confirming_enables_and_keeps_only_hashes_of_the_recovery_codes() {
+        let (two_factor, codes) = confirmed();
+
+        assert!(two_factor.is_enabled());
+        assert_eq!(two_factor.get_last_used_step(), Some(100));
+        assert_eq!(codes.len(), 3);
+        assert!(codes
+            .iter()
+            .all(|code| !two_factor.get_recovery_code_hashes().contains(code)));
+    }
+
+    #[test]
+    fn recovery_codes_are_accepted_once_whatever_their_case() {
+        let (two_factor, codes) = confirmed();
+        let code = format!(" {} ", codes[1].to_uppercase());
+
+        let two_factor = two_factor.use_recovery_code(&code).unwrap();
+
+        assert_eq!(two_factor.get_recovery_code_hashes().len(), 2);
+        assert!(two_factor.clone().use_recovery_code(&codes[1]).is_none());
+        assert!(two_factor.use_recovery_code("aaaaa-aaaaa").is_none());
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::users::domain::users::token_secret;
+
+/// Login of a user whose password was checked, waiting for a two-factor code to be completed.
+/// Single-use and only valid for a few attempts, only the hash of its secret is kept.
+#[derive(Debug, Clone)]
+pub struct TwoFactorChallenge {
+    id: String,
+    user_id: String,
+    secret_hash: String,
+    expires_at: DateTime<Utc>,
+    attempts: u32,
+}
+
+impl TwoFactorChallenge {
+    pub fn new(
+        id: &str,
+        user_id: &str,
+        secret_hash: &str,
+        expires_at: DateTime<Utc>,
+        attempts: u32,
+    ) -> Self {
+        TwoFactorChallenge {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            secret_hash: secret_hash.to_string(),
+            expires_at,
+            attempts,
+        }
+    }
+
+    /// Issues a challenge for the user, returning it along with the value to send to the user.
+    pub fn issue(user_id: &str, lifetime: Duration, now: DateTime<Utc>) -> (Self, String) {
+        let id = Uuid::now_v7().to_string();
+        let secret = token_secret::generate();
+
+        let challenge = TwoFactorChallenge {
+            secret_hash: token_secret::hash(&secret),
+            user_id: user_id.to_string(),
+            expires_at: now + lifetime,
+            attempts: 0,
+            id,
+        };
+
+        let value = token_secret::join(&challenge.id, &secret);
+
+        (challenge, value)
+    }
+
+    /// Splits the value sent to the user into the id of the challenge and its secret.
+    pub fn parse(value: &str) -> Option<(&str, &str)> {
+        token_secret::split(value)
+    }
+
+    /// Whether the secret belongs to this challenge and it hasn't expired, compared in constant
+    /// time.
+    pub fn matches(&self, secret: &str, now: DateTime<Utc>) -> bool {
+        token_secret::matches(secret, &self.secret_hash) && self.expires_at > now
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_secret_hash(&self) -> &str {
+        &self.secret_hash
+    }
+
+    pub fn get_expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    /// Wrong codes already sent for this challenge.
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+}
@@ -0,0 +1,25 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::two_factor_challenge::TwoFactorChallenge;
+
+#[derive(Error, Debug)]
+pub enum TwoFactorChallengeRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, TwoFactorChallengeRepositoryErrors>;
+
+pub trait TwoFactorChallengeRepository: Interface {
+    fn save(&self, challenge: &TwoFactorChallenge) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<TwoFactorChallenge>>;
+    /// Counts a wrong code sent for the challenge.
+    fn add_attempt(&self, id: &str) -> Result<()>;
+    /// Deletes the challenge, returning `false` when it was already gone.
+    fn delete_by(&self, id: &str) -> Result<bool>;
+}
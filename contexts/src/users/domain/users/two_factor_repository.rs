@@ -0,0 +1,24 @@
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::two_factor::TwoFactor;
+
+#[derive(Error, Debug)]
+pub enum TwoFactorRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, TwoFactorRepositoryErrors>;
+
+pub trait TwoFactorRepository: Interface {
+    fn find_by(&self, user_id: &str) -> Result<Option<TwoFactor>>;
+    /// Saves the enrollment of the user, replacing the one it had.
+    fn save(&self, two_factor: &TwoFactor) -> Result<()>;
+    /// Deletes the enrollment of the user, returning `false` when it had none.
+    fn delete_by(&self, user_id: &str) -> Result<bool>;
+}
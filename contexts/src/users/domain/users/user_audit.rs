@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+
+/// When a user was created, changed, last logged in and deleted, along with the id of the users
+/// that made the changes, missing when they weren't made by a known user.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UserAudit {
+    created_at: DateTime<Utc>,
+    created_by: Option<String>,
+    updated_at: DateTime<Utc>,
+    updated_by: Option<String>,
+    last_login_at: Option<DateTime<Utc>>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl UserAudit {
+    pub fn new(
+        created_at: DateTime<Utc>,
+        created_by: Option<String>,
+        updated_at: DateTime<Utc>,
+        updated_by: Option<String>,
+        last_login_at: Option<DateTime<Utc>>,
+        deleted_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        UserAudit {
+            created_at,
+            created_by,
+            updated_at,
+            updated_by,
+            last_login_at,
+            deleted_at,
+        }
+    }
+
+    /// Audit of a user created at the given time, which counts as its first change.
+    pub fn created(at: DateTime<Utc>, by: Option<&str>) -> Self {
+        UserAudit {
+            created_at: at,
+            created_by: by.map(str::to_owned),
+            updated_at: at,
+            updated_by: by.map(str::to_owned),
+            last_login_at: None,
+            deleted_at: None,
+        }
+    }
+
+    /// Audit of the user after being changed at the given time.
+    pub fn updated(&self, at: DateTime<Utc>, by: Option<&str>) -> Self {
+        UserAudit {
+            updated_at: at,
+            updated_by: by.map(str::to_owned),
+            ..self.clone()
+        }
+    }
+
+    /// Audit of the user after logging in at the given time, which isn't a change of the user.
+    pub fn logged_in(&self, at: DateTime<Utc>) -> Self {
+        UserAudit {
+            last_login_at: Some(at),
+            ..self.clone()
+        }
+    }
+
+    /// Audit of the user after being deleted at the given time, which counts as a change.
+    pub fn deleted(&self, at: DateTime<Utc>, by: Option<&str>) -> Self {
+        UserAudit {
+            deleted_at: Some(at),
+            ..self.updated(at, by)
+        }
+    }
+
+    /// Audit of the user after being restored at the given time, which counts as a change.
+    pub fn restored(&self, at: DateTime<Utc>, by: Option<&str>) -> Self {
+        UserAudit {
+            deleted_at: None,
+            ..self.updated(at, by)
+        }
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn get_created_by(&self) -> Option<&str> {
+        self.created_by.as_deref()
+    }
+
+    pub fn get_updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    pub fn get_updated_by(&self) -> Option<&str> {
+        self.updated_by.as_deref()
+    }
+
+    pub fn get_last_login_at(&self) -> Option<DateTime<Utc>> {
+        self.last_login_at
+    }
+
+    pub fn get_deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    const ADMIN_ID: &str = "01a153b2-0000-7000-8000-000000000001";
+
+    fn created_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn creating_counts_as_the_first_change() {
+        let audit = UserAudit::created(created_at(), Some(ADMIN_ID));
+
+        assert_eq!(audit.get_created_at(), created_at());
+        assert_eq!(audit.get_created_by(), Some(ADMIN_ID));
+        assert_eq!(audit.get_updated_at(), created_at());
+        assert_eq!(audit.get_updated_by(), Some(ADMIN_ID));
+        assert_eq!(audit.get_last_login_at(), None);
+    }
+
+    #[test]
+    fn changes_keep_the_creation() {
+        let updated_at = created_at() + Duration::days(1);
+
+        let audit = UserAudit::created(created_at(), Some(ADMIN_ID)).updated(updated_at, None);
+
+        assert_eq!(audit.get_created_at(), created_at());
+        assert_eq!(audit.get_created_by(), Some(ADMIN_ID));
+        assert_eq!(audit.get_updated_at(), updated_at);
+        assert_eq!(audit.get_updated_by(), None);
+    }
+
+    #[test]
+    fn logging_in_is_not_a_change() {
+        let logged_in_at = created_at() + Duration::hours(1);
+
+        let audit = UserAudit::created(created_at(), None).logged_in(logged_in_at);
+
+        assert_eq!(audit.get_updated_at(), created_at());
+        assert_eq!(audit.get_last_login_at(), Some(logged_in_at));
+    }
+}
@@ -13,10 +13,13 @@ pub enum CriteriaRepositoryErrors {
     },
     #[error("The field {0} don't exist for user")]
     FieldNotFound(String),
+    #[error("The value {0} isn't an RFC 3339 timestamp nor a date")]
+    InvalidTimestamp(String),
 }
 
 pub type Result<T> = result::Result<T, CriteriaRepositoryErrors>;
 
 pub trait UserCriteriaRepository: Interface {
-    fn find_by(&self, criteria: &Criteria) -> Result<Vec<User>>;
+    /// Finds the users matching the criteria, deleted users only when they're included.
+    fn find_by(&self, criteria: &Criteria, include_deleted: bool) -> Result<Vec<User<'_>>>;
 }
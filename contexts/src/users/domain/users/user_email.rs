@@ -1,54 +1,287 @@
-use crate::shared::domain::regex::valid_email;
+pub use crate::shared::domain::regex::EMAIL_PATTERN;
+use idna::AsciiDenyList;
 use std::borrow::Cow;
 use thiserror::Error;
 
-use crate::users::domain::users::user_email::UserEmailErrors::InvalidEmail;
+use crate::users::domain::users::user_email::UserEmailErrors::{
+    InvalidDomain, InvalidEmail, InvalidLocalPart, LocalPartTooLong, TooLong,
+};
+
+/// Maximum length in bytes of the local part of an email, from RFC 5321.
+pub const MAX_LOCAL_PART_LENGTH: usize = 64;
+/// Maximum length in bytes of a whole email, the 256 of an RFC 5321 path without the brackets.
+pub const MAX_EMAIL_LENGTH: usize = 254;
+/// Maximum length in bytes of a domain label, from RFC 1035.
+const MAX_LABEL_LENGTH: usize = 63;
 
 #[derive(Error, Debug)]
 pub enum UserEmailErrors {
     #[error("Invalid format of an email")]
     InvalidEmail,
+    #[error("Email of {0} bytes too long, maximum is {MAX_EMAIL_LENGTH} bytes")]
+    TooLong(usize),
+    #[error(
+        "Local part of the email of {0} bytes too long, maximum is {MAX_LOCAL_PART_LENGTH} bytes"
+    )]
+    LocalPartTooLong(usize),
+    #[error("Invalid local part of the email, before the @")]
+    InvalidLocalPart,
+    #[error("Invalid domain of the email, after the @")]
+    InvalidDomain,
+    #[error("Emails of disposable domains aren't accepted")]
+    DisposableDomain,
 }
 
+/// Email of a user, parsed following RFC 5321 and RFC 5322.
+///
+/// The local part is kept as written, since only the receiving server knows whether it's case
+/// sensitive, while the domain is lowercased and converted to punycode when internationalized.
+/// The normalized form is the one compared to tell whether two emails reach the same mailbox.
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub struct UserEmail<'a>(Cow<'a, str>);
+pub struct UserEmail<'a> {
+    address: Cow<'a, str>,
+    normalized: String,
+}
+
+impl<'a> UserEmail<'a> {
+    pub fn validate(value: &str) -> Result<(), UserEmailErrors> {
+        Self::split(value.trim()).map(|_| ())
+    }
+
+    /// Parses an email, the normalized form is built from its local part and ASCII domain.
+    pub fn parse(
+        value: &'a str,
+        normalize: impl FnOnce(&str, &str) -> String,
+    ) -> Result<UserEmail<'a>, UserEmailErrors> {
+        let value = value.trim();
+        let (local_part, domain) = Self::split(value)?;
+
+        // The domain is only converted when it isn't already lowercase ASCII.
+        let address = match &domain {
+            Cow::Borrowed(_) => Cow::Borrowed(value),
+            Cow::Owned(domain) => Cow::Owned(format!("{local_part}@{domain}")),
+        };
+
+        Ok(UserEmail {
+            normalized: normalize(local_part, &domain),
+            address,
+        })
+    }
+
+    /// Email read back from where it was saved, which was already parsed and normalized.
+    pub fn from_parsed(address: String, normalized: String) -> UserEmail<'static> {
+        UserEmail {
+            address: address.into(),
+            normalized,
+        }
+    }
 
-impl UserEmail<'_> {
-    fn validate(value: &str) -> Result<(), UserEmailErrors> {
-        if !valid_email(&value) {
-            return Err(InvalidEmail);
+    /// Splits an email into its local part and its domain converted to ASCII.
+    fn split(value: &str) -> Result<(&str, Cow<'_, str>), UserEmailErrors> {
+        // Quoted local parts can hold an @, domains can't.
+        let (local_part, domain) = value.rsplit_once('@').ok_or(InvalidEmail)?;
+
+        if local_part.len() > MAX_LOCAL_PART_LENGTH {
+            return Err(LocalPartTooLong(local_part.len()));
+        }
+
+        if !valid_local_part(local_part) {
+            return Err(InvalidLocalPart);
         }
 
-        Ok(())
+        let domain = ascii_domain(domain)?;
+
+        let length = local_part.len() + 1 + domain.len();
+        if length > MAX_EMAIL_LENGTH {
+            return Err(TooLong(length));
+        }
+
+        Ok((local_part, domain))
+    }
+
+    pub fn get(&self) -> &str {
+        self.address.as_ref()
+    }
+
+    pub fn get_local_part(&self) -> &str {
+        self.address
+            .rsplit_once('@')
+            .map(|(local_part, _)| local_part)
+            .unwrap_or_default()
+    }
+
+    /// ASCII domain of the email, lowercase and in punycode when internationalized.
+    pub fn get_domain(&self) -> &str {
+        self.address
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or_default()
+    }
+
+    /// Form of the email compared to tell whether two emails are the same.
+    pub fn get_normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    pub fn into_owned(self) -> String {
+        self.address.into_owned()
+    }
+}
+
+/// Checks a dot-atom or quoted-string local part, accepting UTF-8 as RFC 6531 does.
+fn valid_local_part(local_part: &str) -> bool {
+    if let Some(quoted) = local_part
+        .strip_prefix('"')
+        .and_then(|local_part| local_part.strip_suffix('"'))
+    {
+        return valid_quoted_string(quoted);
     }
+
+    local_part
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
 }
 
-impl<'a> TryFrom<&'a str> for UserEmail<'a> {
-    type Error = UserEmailErrors;
+fn valid_quoted_string(quoted: &str) -> bool {
+    let mut chars = quoted.chars();
 
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        Self::validate(value)?;
+    while let Some(char) = chars.next() {
+        let valid = match char {
+            '\\' => chars
+                .next()
+                .is_some_and(|escaped| matches!(escaped, ' '..='~')),
+            '"' => false,
+            _ => matches!(char, ' '..='~') || !char.is_ascii(),
+        };
 
-        Ok(UserEmail(value.into()))
+        if !valid {
+            return false;
+        }
     }
+
+    true
+}
+
+fn is_atext(char: char) -> bool {
+    char.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(char) || !char.is_ascii()
 }
 
-impl TryFrom<String> for UserEmail<'_> {
-    type Error = UserEmailErrors;
+/// Converts a domain to lowercase ASCII, with punycode labels when internationalized, and checks
+/// it's a hostname with at least two labels, domain literals aren't accepted.
+fn ascii_domain(domain: &str) -> Result<Cow<'_, str>, UserEmailErrors> {
+    let domain = idna::domain_to_ascii_cow(domain.as_bytes(), AsciiDenyList::STD3)
+        .map_err(|_| InvalidDomain)?;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        Self::validate(value.as_str())?;
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LENGTH
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    });
+    let numeric_tld = labels
+        .last()
+        .is_some_and(|tld| tld.chars().all(|char| char.is_ascii_digit()));
 
-        Ok(UserEmail(value.into()))
+    if labels.len() < 2 || !valid_labels || numeric_tld {
+        return Err(InvalidDomain);
     }
+
+    Ok(domain)
 }
 
-impl<'a> UserEmail<'a> {
-    pub fn get(&self) -> &str {
-        self.0.as_ref()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Result<UserEmail<'_>, UserEmailErrors> {
+        UserEmail::parse(value, |local_part, domain| format!("{local_part}@{domain}"))
     }
 
-    pub fn into_owned(self) -> String {
-        self.0.into_owned()
+    #[test]
+    fn parses_emails_keeping_the_local_part_and_lowercasing_the_domain() {
+        let email = parse("  John.Doe@Example.COM ").unwrap();
+
+        assert_eq!(email.get(), "John.Doe@example.com");
+        assert_eq!(email.get_local_part(), "John.Doe");
+        assert_eq!(email.get_domain(), "example.com");
+    }
+
+    #[test]
+    fn converts_internationalized_domains_to_punycode() {
+        let email = parse("jose@bücher.example").unwrap();
+
+        assert_eq!(email.get(), "jose@xn--bcher-kva.example");
+    }
+
+    #[test]
+    fn accepts_quoted_and_internationalized_local_parts() {
+        assert!(parse(r#""john doe@home"@example.com"#).is_ok());
+        assert!(parse(r#""john\"doe"@example.com"#).is_ok());
+        assert!(parse("josé@example.com").is_ok());
+        assert!(parse("john+tag!#$%&'*/=?^_`{|}~-@example.com").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_local_parts() {
+        for email in [
+            "john..doe@example.com",
+            ".john@example.com",
+            "john.@example.com",
+            "john doe@example.com",
+            r#""john"doe"@example.com"#,
+            "@example.com",
+        ] {
+            assert!(
+                matches!(parse(email), Err(InvalidLocalPart)),
+                "{email} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_domains() {
+        for email in [
+            "john@localhost",
+            "john@example..com",
+            "john@-example.com",
+            "john@example-.com",
+            "john@127.0.0.1",
+            "john@[127.0.0.1]",
+            "john@exa_mple.com",
+        ] {
+            assert!(
+                matches!(parse(email), Err(InvalidDomain)),
+                "{email} should be invalid"
+            );
+        }
+
+        assert!(matches!(parse("john.example.com"), Err(InvalidEmail)));
+    }
+
+    #[test]
+    fn rejects_emails_too_long() {
+        let local_part = "a".repeat(MAX_LOCAL_PART_LENGTH + 1);
+        assert!(matches!(
+            parse(&format!("{local_part}@example.com")),
+            Err(LocalPartTooLong(65))
+        ));
+
+        let label = "a".repeat(MAX_LABEL_LENGTH);
+        let domain = [label.as_str(); 4].join(".");
+        assert!(matches!(
+            parse(&format!("john@{domain}")),
+            Err(TooLong(260))
+        ));
+    }
+
+    #[test]
+    fn normalizes_with_the_given_rules() {
+        let email = UserEmail::parse("John@Example.com", |local_part, domain| {
+            format!("{}@{domain}", local_part.to_lowercase())
+        })
+        .unwrap();
+
+        assert_eq!(email.get_normalized(), "john@example.com");
     }
 }
@@ -0,0 +1,56 @@
+use std::any::Any;
+
+use crate::shared::domain::event_bus::DomainEvent;
+use crate::users::domain::users::user_status::UserStatus;
+use crate::users::domain::users::User;
+
+/// A new user was stored, whether it registered or was created by replacing a missing one.
+#[derive(Debug, Clone)]
+pub struct UserRegistered {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+impl From<&User<'_>> for UserRegistered {
+    fn from(value: &User) -> Self {
+        UserRegistered {
+            id: value.get_id().to_string(),
+            name: value.get_name().to_string(),
+            email: value.get_email().to_string(),
+        }
+    }
+}
+
+impl DomainEvent for UserRegistered {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The status of a user changed, through one of its allowed transitions.
+#[derive(Debug, Clone)]
+pub struct UserStatusChanged {
+    pub id: String,
+    pub previous: UserStatus,
+    pub status: UserStatus,
+    /// Id of the user that made the change, missing when it wasn't a known user.
+    pub changed_by: Option<String>,
+}
+
+impl UserStatusChanged {
+    pub fn new(previous: UserStatus, user: &User, changed_by: Option<&str>) -> Self {
+        UserStatusChanged {
+            id: user.get_id().to_string(),
+            previous,
+            status: user.get_status().clone(),
+            changed_by: changed_by.map(str::to_owned),
+        }
+    }
+}
+
+impl DomainEvent for UserStatusChanged {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -6,7 +6,8 @@ use uuid::{NoContext, Timestamp, Uuid};
 
 use crate::users::domain::users::user_id::UserIDErrors::{InvalidUuid, InvalidUuidVersion};
 
-const UUID_TIMESTAMP_RAND_VERSION: usize = 7;
+/// Only time ordered UUIDs (v7) are accepted as user ids.
+pub const UUID_TIMESTAMP_RAND_VERSION: usize = 7;
 
 #[derive(Error, Debug)]
 pub enum UserIDErrors {
@@ -23,7 +24,7 @@ pub enum UserIDErrors {
 pub struct UserID<'a>(Cow<'a, str>);
 
 impl UserID<'_> {
-    fn validate(value: &str) -> Result<(), UserIDErrors> {
+    pub fn validate(value: &str) -> Result<(), UserIDErrors> {
         let err = Uuid::parse_str(value);
 
         let uuid = match err {
@@ -50,7 +51,7 @@ impl<'a> TryFrom<&'a str> for UserID<'a> {
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         Self::validate(value)?;
 
-        Ok(UserID(Cow::Borrowed(&value)))
+        Ok(UserID(Cow::Borrowed(value)))
     }
 }
 
@@ -78,6 +79,12 @@ impl UserID<'_> {
     }
 }
 
+impl Default for UserID<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> UserID<'a> {
     pub fn get(&self) -> &str {
         self.0.as_ref()
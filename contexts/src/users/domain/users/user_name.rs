@@ -15,15 +15,23 @@ pub enum UserNameErrors {
     NotLongEnough(usize),
 }
 
-impl<'a> TryFrom<&'a str> for UserName<'a> {
-    type Error = UserNameErrors;
-
-    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+impl UserName<'_> {
+    pub fn validate(value: &str) -> Result<(), UserNameErrors> {
         let length = value.chars().count();
         if length < MIN_NAME_LENGTH {
             return Err(NotLongEnough(length));
         }
 
+        Ok(())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for UserName<'a> {
+    type Error = UserNameErrors;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
         Ok(UserName(value.into()))
     }
 }
@@ -32,10 +40,7 @@ impl TryFrom<String> for UserName<'_> {
     type Error = UserNameErrors;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let length = value.chars().count();
-        if length < MIN_NAME_LENGTH {
-            return Err(NotLongEnough(length));
-        }
+        Self::validate(value.as_str())?;
 
         Ok(UserName(value.into()))
     }
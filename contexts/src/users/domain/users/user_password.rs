@@ -1,39 +1,40 @@
-use crate::shared::domain::regex::{has_number, has_symbol};
-use argon2::Argon2;
-use password_hash::rand_core::OsRng;
-use password_hash::{PasswordHash, PasswordHasher, SaltString};
+use password_hash::PasswordHash;
 use std::borrow::Cow;
 use thiserror::Error;
 
-use crate::users::domain::users::user_password::UserPasswordErrors::{
-    Missing, NotLongEnough, PHCFormatError,
-};
+use crate::users::domain::users::password_hasher::PasswordHasher;
 
-fn hash_password_with_salt(password: &str) -> String {
-    let salt = SaltString::generate(OsRng);
-
-    Argon2::default()
-        .hash_password(password.as_bytes(), &salt)
-        .unwrap()
-        .to_string()
-}
-
-const MIN_PASSWORD_LENGTH: usize = 8;
+use crate::users::domain::users::user_password::UserPasswordErrors::PHCFormatError;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct UserPassword<'a>(Cow<'a, str>);
 
 #[derive(Error, Debug)]
 pub enum UserPasswordErrors {
-    #[error("Password of {0} is not long enough, minimum {MIN_PASSWORD_LENGTH} characters long")]
-    NotLongEnough(usize),
+    #[error("Password of {length} is not long enough, minimum {min} characters long")]
+    NotLongEnough { length: usize, min: usize },
+    #[error("Password of {length} is too long, maximum {max} characters long")]
+    TooLong { length: usize, max: usize },
     #[error("Password is missing {0}, at least one required")]
     Missing(&'static str),
+    #[error("Password is too common, choose a different one")]
+    Banned,
+    #[error("Password contains the name or email of the user")]
+    ContainsUserData,
+    #[error("Password strength of {score} is too weak, minimum is {min} out of 4")]
+    TooWeak { score: u8, min: u8 },
+    #[error("Password was used recently, it can't be any of the last {0}")]
+    Reused(usize),
     #[error("PHC Format Error, {source}")]
     PHCFormatError {
         #[source]
         source: anyhow::Error,
     },
+    #[error("Password couldn't be hashed, {source}")]
+    HashingError {
+        #[source]
+        source: anyhow::Error,
+    },
 }
 
 impl<'a> TryFrom<&'a str> for UserPassword<'a> {
@@ -67,21 +68,10 @@ impl TryFrom<String> for UserPassword<'_> {
 }
 
 impl UserPassword<'_> {
-    pub fn new(password: &str) -> Result<Self, UserPasswordErrors> {
-        let length = password.chars().count();
-        if length < MIN_PASSWORD_LENGTH {
-            return Err(NotLongEnough(length));
-        }
-
-        if !has_symbol(password) {
-            return Err(Missing("Symbols"));
-        }
-
-        if !has_number(password) {
-            return Err(Missing("Numbers"));
-        }
-
-        let password_hash = hash_password_with_salt(password);
+    /// Hashes a plain text password as it is, the user creating or changing it checks it against
+    /// the password policy first.
+    pub fn new(password: &str, hasher: &dyn PasswordHasher) -> Result<Self, UserPasswordErrors> {
+        let password_hash = hasher.hash(password)?;
 
         Ok(UserPassword(password_hash.into()))
     }
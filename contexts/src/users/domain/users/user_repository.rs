@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use shaku::Interface;
 use std::result;
 use thiserror::Error;
@@ -9,6 +10,8 @@ use crate::users::domain::users::User;
 pub enum RepositoryErrors {
     #[error("The data trying to be stored is already there")]
     AlreadyExists,
+    #[error("The email is already taken by another user")]
+    EmailAlreadyTaken,
     #[error("The server has found an unexpected situation")]
     InternalServerError {
         #[source]
@@ -18,10 +21,17 @@ pub enum RepositoryErrors {
 
 type Result<T> = result::Result<T, RepositoryErrors>;
 
+/// Stores the users, deleted ones are kept until purged but left out unless said otherwise.
 pub trait UserRepository: Interface {
     fn save(&self, user: &User) -> Result<()>;
-    fn find_by(&self, id: &UserID) -> Option<User>;
-    fn get_all(&self) -> Vec<User>;
-    fn delete_by(&self, id: &UserID) -> Result<()>;
+    fn find_by(&self, id: &UserID) -> Option<User<'_>>;
+    /// Finds the deleted user with the given id, which hasn't been purged yet.
+    fn find_deleted_by(&self, id: &UserID) -> Result<Option<User<'_>>>;
+    /// Finds the user whose email has the given normalized form, deleted users included since
+    /// they keep their email until purged.
+    fn find_by_email(&self, normalized_email: &str) -> Result<Option<User<'_>>>;
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>>;
     fn update(&self, user: &User) -> Result<()>;
+    /// Removes for good the users deleted before the given time, returning how many there were.
+    fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<usize>;
 }
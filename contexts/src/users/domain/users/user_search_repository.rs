@@ -0,0 +1,30 @@
+use crate::users::domain::users::User;
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SearchRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, SearchRepositoryErrors>;
+
+/// A user matching a full-text search, with its relevance and the matched fragments.
+#[derive(Debug)]
+pub struct UserSearchMatch<'a> {
+    pub user: User<'a>,
+    /// Relevance of the match, higher is better.
+    pub score: f64,
+    pub name_highlight: String,
+    pub email_highlight: String,
+}
+
+pub trait UserSearchRepository: Interface {
+    /// Finds the users whose name or email match the terms of the query, best matches first.
+    fn search(&self, query: &str, limit: u32) -> Result<Vec<UserSearchMatch<'_>>>;
+}
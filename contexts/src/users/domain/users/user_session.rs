@@ -0,0 +1,156 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// Lifetimes of the tokens handed out when logging in, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Minutes an access token is valid for.
+    pub access_token_lifetime: u32,
+    /// Days a session lasts without being refreshed, every refresh extends it.
+    pub refresh_token_lifetime: u32,
+    /// Secret signing the access tokens, a random one is generated on startup when missing,
+    /// so access tokens don't survive a restart.
+    pub signing_key: Option<String>,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            access_token_lifetime: 15,
+            refresh_token_lifetime: 30,
+            signing_key: None,
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn access_token_lifetime(&self) -> Duration {
+        Duration::minutes(self.access_token_lifetime.into())
+    }
+
+    pub fn refresh_token_lifetime(&self) -> Duration {
+        Duration::days(self.refresh_token_lifetime.into())
+    }
+}
+
+/// Login of a user from a device, kept alive by refreshing its tokens until it expires or is
+/// revoked. Its refresh tokens form a family, reusing a rotated one revokes the whole session.
+#[derive(Debug, Clone)]
+pub struct UserSession {
+    id: String,
+    user_id: String,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    created_at: DateTime<Utc>,
+    last_used_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    revoked: bool,
+}
+
+impl UserSession {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: &str,
+        user_id: &str,
+        user_agent: Option<String>,
+        ip: Option<String>,
+        created_at: DateTime<Utc>,
+        last_used_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+        revoked: bool,
+    ) -> Self {
+        UserSession {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            user_agent,
+            ip,
+            created_at,
+            last_used_at,
+            expires_at,
+            revoked,
+        }
+    }
+
+    /// Session of a user that just logged in.
+    pub fn start(
+        user_id: &str,
+        user_agent: Option<&str>,
+        ip: Option<&str>,
+        lifetime: Duration,
+        now: DateTime<Utc>,
+    ) -> Self {
+        UserSession::new(
+            &Uuid::now_v7().to_string(),
+            user_id,
+            user_agent.map(str::to_owned),
+            ip.map(str::to_owned),
+            now,
+            now,
+            now + lifetime,
+            false,
+        )
+    }
+
+    /// Session refreshed from the given device, extending its lifetime.
+    pub fn used(
+        self,
+        user_agent: Option<&str>,
+        ip: Option<&str>,
+        lifetime: Duration,
+        now: DateTime<Utc>,
+    ) -> Self {
+        UserSession {
+            user_agent: user_agent.map(str::to_owned).or(self.user_agent),
+            ip: ip.map(str::to_owned).or(self.ip),
+            last_used_at: now,
+            expires_at: now + lifetime,
+            ..self
+        }
+    }
+
+    pub fn revoke(self) -> Self {
+        UserSession {
+            revoked: true,
+            ..self
+        }
+    }
+
+    /// Whether the session can still be refreshed and its access tokens used.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        !self.revoked && self.expires_at > now
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn get_user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    pub fn get_ip(&self) -> Option<&str> {
+        self.ip.as_deref()
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn get_last_used_at(&self) -> DateTime<Utc> {
+        self.last_used_at
+    }
+
+    pub fn get_expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked
+    }
+}
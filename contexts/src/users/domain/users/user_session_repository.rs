@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use shaku::Interface;
+use std::result;
+use thiserror::Error;
+
+use crate::users::domain::users::user_session::UserSession;
+
+#[derive(Error, Debug)]
+pub enum UserSessionRepositoryErrors {
+    #[error("The server has found an unexpected situation")]
+    InternalServerError {
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+pub type Result<T> = result::Result<T, UserSessionRepositoryErrors>;
+
+pub trait UserSessionRepository: Interface {
+    fn save(&self, session: &UserSession) -> Result<()>;
+    fn update(&self, session: &UserSession) -> Result<()>;
+    fn find_by(&self, id: &str) -> Result<Option<UserSession>>;
+    /// Sessions of the user that are neither revoked nor expired, the most recently used first.
+    fn find_active_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<Vec<UserSession>>;
+    /// Revokes every session of the user, returning how many were still active.
+    fn revoke_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<usize>;
+}
@@ -0,0 +1,137 @@
+use std::fmt::{Display, Formatter};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UserStatusErrors {
+    #[error("The user can't become {to} while {from}")]
+    InvalidTransition {
+        from: &'static str,
+        to: &'static str,
+    },
+    #[error("Unknown user status {0}")]
+    UnknownStatus(String),
+    #[error("A reason is needed to suspend a user")]
+    MissingReason,
+}
+
+/// Stage of the lifecycle of a user account, which only changes through the allowed transitions:
+///
+/// - Pending users become active once they verify their email.
+/// - Pending, active and locked users can be suspended by an administrator, for a reason.
+/// - Pending and active users are locked after too many failed logins.
+/// - Suspended and locked users are reactivated by an administrator.
+/// - Any user but a closed one can be closed, which is final.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum UserStatus {
+    #[default]
+    Pending,
+    Active,
+    Suspended {
+        reason: String,
+    },
+    Locked,
+    Closed,
+}
+
+impl UserStatus {
+    pub const PENDING: &'static str = "pending";
+    pub const ACTIVE: &'static str = "active";
+    pub const SUSPENDED: &'static str = "suspended";
+    pub const LOCKED: &'static str = "locked";
+    pub const CLOSED: &'static str = "closed";
+
+    /// Status read back from where it was saved, the reason only matters to suspended users.
+    pub fn from_parsed(status: &str, reason: Option<String>) -> Result<Self, UserStatusErrors> {
+        match status {
+            UserStatus::PENDING => Ok(UserStatus::Pending),
+            UserStatus::ACTIVE => Ok(UserStatus::Active),
+            UserStatus::SUSPENDED => Ok(UserStatus::Suspended {
+                reason: reason.unwrap_or_default(),
+            }),
+            UserStatus::LOCKED => Ok(UserStatus::Locked),
+            UserStatus::CLOSED => Ok(UserStatus::Closed),
+            _ => Err(UserStatusErrors::UnknownStatus(status.to_owned())),
+        }
+    }
+
+    pub fn get(&self) -> &'static str {
+        match self {
+            UserStatus::Pending => UserStatus::PENDING,
+            UserStatus::Active => UserStatus::ACTIVE,
+            UserStatus::Suspended { .. } => UserStatus::SUSPENDED,
+            UserStatus::Locked => UserStatus::LOCKED,
+            UserStatus::Closed => UserStatus::CLOSED,
+        }
+    }
+
+    /// Why the user was suspended, missing unless it is.
+    pub fn get_reason(&self) -> Option<&str> {
+        match self {
+            UserStatus::Suspended { reason } => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// Whether users with this status can log in and ask for a new password.
+    pub fn allows_login(&self) -> bool {
+        matches!(self, UserStatus::Pending | UserStatus::Active)
+    }
+
+    pub fn activate(&self) -> Result<UserStatus, UserStatusErrors> {
+        match self {
+            UserStatus::Pending => Ok(UserStatus::Active),
+            _ => Err(self.invalid_transition(UserStatus::ACTIVE)),
+        }
+    }
+
+    pub fn suspend(&self, reason: &str) -> Result<UserStatus, UserStatusErrors> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(UserStatusErrors::MissingReason);
+        }
+
+        match self {
+            UserStatus::Pending | UserStatus::Active | UserStatus::Locked => {
+                Ok(UserStatus::Suspended {
+                    reason: reason.to_owned(),
+                })
+            }
+            _ => Err(self.invalid_transition(UserStatus::SUSPENDED)),
+        }
+    }
+
+    pub fn reactivate(&self) -> Result<UserStatus, UserStatusErrors> {
+        match self {
+            UserStatus::Suspended { .. } | UserStatus::Locked => Ok(UserStatus::Active),
+            _ => Err(self.invalid_transition(UserStatus::ACTIVE)),
+        }
+    }
+
+    pub fn lock(&self) -> Result<UserStatus, UserStatusErrors> {
+        match self {
+            UserStatus::Pending | UserStatus::Active => Ok(UserStatus::Locked),
+            _ => Err(self.invalid_transition(UserStatus::LOCKED)),
+        }
+    }
+
+    pub fn close(&self) -> Result<UserStatus, UserStatusErrors> {
+        match self {
+            UserStatus::Closed => Err(self.invalid_transition(UserStatus::CLOSED)),
+            _ => Ok(UserStatus::Closed),
+        }
+    }
+
+    fn invalid_transition(&self, to: &'static str) -> UserStatusErrors {
+        UserStatusErrors::InvalidTransition {
+            from: self.get(),
+            to,
+        }
+    }
+}
+
+impl Display for UserStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
@@ -1 +1,7 @@
+pub mod access_token_signer_jwt;
+pub mod password_hasher_argon2;
+pub mod password_policy_zxcvbn;
+#[cfg(test)]
+pub(crate) mod in_memory;
 pub mod sqlite;
+pub mod totp_rfc6238;
@@ -0,0 +1,89 @@
+use chrono::DateTime;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use password_hash::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use shaku::Component;
+
+use crate::users::domain::users::access_token::{
+    AccessToken, AccessTokenErrors, AccessTokenSigner,
+};
+
+/// Random bytes of the signing key generated when none is configured.
+const GENERATED_KEY_LENGTH: usize = 32;
+
+/// Claims of the JWT, as registered by RFC 7519 plus the id of the session.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: String,
+    sid: String,
+    iat: i64,
+    exp: i64,
+}
+
+/// Signing key configured for the deployment, or a random one when there is none.
+pub fn signing_key(configured: Option<&str>) -> Vec<u8> {
+    match configured {
+        Some(key) => key.as_bytes().to_vec(),
+        None => {
+            let mut key = vec![0u8; GENERATED_KEY_LENGTH];
+            OsRng.fill_bytes(&mut key);
+            key
+        }
+    }
+}
+
+/// Signs access tokens as HS256 JWTs.
+#[derive(Component)]
+#[shaku(interface = AccessTokenSigner)]
+pub struct AccessTokenSignerJwt {
+    signing_key: Vec<u8>,
+}
+
+impl AccessTokenSigner for AccessTokenSignerJwt {
+    fn sign(&self, token: &AccessToken) -> Result<String, AccessTokenErrors> {
+        let claims = Claims {
+            sub: token.get_user_id().to_owned(),
+            sid: token.get_session_id().to_owned(),
+            iat: token.get_issued_at().timestamp(),
+            exp: token.get_expires_at().timestamp(),
+        };
+
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(&self.signing_key),
+        )
+        .map_err(|error| AccessTokenErrors::InternalServerError {
+            source: error.into(),
+        })
+    }
+
+    fn verify(&self, value: &str) -> Result<AccessToken, AccessTokenErrors> {
+        // Expiry is checked against the clock of the application instead of the system one.
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_exp = false;
+        validation.required_spec_claims.clear();
+
+        let claims = jsonwebtoken::decode::<Claims>(
+            value,
+            &DecodingKey::from_secret(&self.signing_key),
+            &validation,
+        )
+        .map_err(|_| AccessTokenErrors::Invalid)?
+        .claims;
+
+        let (Some(issued_at), Some(expires_at)) = (
+            DateTime::from_timestamp(claims.iat, 0),
+            DateTime::from_timestamp(claims.exp, 0),
+        ) else {
+            return Err(AccessTokenErrors::Invalid);
+        };
+
+        Ok(AccessToken::new(
+            &claims.sub,
+            &claims.sid,
+            issued_at,
+            expires_at,
+        ))
+    }
+}
@@ -0,0 +1,720 @@
+//! Doubles of the user ports keeping everything in memory, so the services can be tested without a
+//! database, a mail server or slow hashing.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chrono::{DateTime, TimeZone, Utc};
+use password_hash::rand_core::OsRng;
+use password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+
+use crate::shared::domain::clock::Clock;
+use crate::shared::domain::event_bus::{DomainEvent, EventBus, EventBusErrors, EventSubscriber};
+use crate::shared::infrastructure::clock_fixed::ClockFixed;
+use crate::shared::infrastructure::mailer::in_memory::MailerInMemory;
+use crate::users::application::find::{UserFind, UserFindErrors};
+use crate::users::application::request_email_verification::{
+    UserEmailVerificationRequest, UserEmailVerificationRequestErrors,
+};
+use crate::users::domain::users::access_token::{
+    AccessToken, AccessTokenErrors, AccessTokenSigner,
+};
+use crate::users::domain::users::api_key::ApiKey;
+use crate::users::domain::users::api_key_repository::{self, ApiKeyRepository};
+use crate::users::domain::users::email_policy::EmailPolicy;
+use crate::users::domain::users::email_verification::EmailVerification;
+use crate::users::domain::users::email_verification_token::EmailVerificationToken;
+use crate::users::domain::users::email_verification_token_repository::{
+    self, EmailVerificationTokenRepository,
+};
+use crate::users::domain::users::login_failures::LoginFailures;
+use crate::users::domain::users::login_failures_repository::{self, LoginFailuresRepository};
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::password_history_repository::{self, PasswordHistoryRepository};
+use crate::users::domain::users::password_policy::{PasswordPolicy, MIN_PASSWORD_LENGTH};
+use crate::users::domain::users::password_reset_token::PasswordResetToken;
+use crate::users::domain::users::password_reset_token_repository::{
+    self, PasswordResetTokenRepository,
+};
+use crate::users::domain::users::refresh_token::RefreshToken;
+use crate::users::domain::users::refresh_token_repository::{self, RefreshTokenRepository};
+use crate::users::domain::users::two_factor::TwoFactor;
+use crate::users::domain::users::two_factor_repository::{self, TwoFactorRepository};
+use crate::users::domain::users::user_audit::UserAudit;
+use crate::users::domain::users::user_email::{UserEmail, UserEmailErrors};
+use crate::users::domain::users::user_events::UserStatusChanged;
+use crate::users::domain::users::user_id::UserID;
+use crate::users::domain::users::user_name::UserName;
+use crate::users::domain::users::user_password::{UserPassword, UserPasswordErrors};
+use crate::users::domain::users::user_repository::{RepositoryErrors, UserRepository};
+use crate::users::domain::users::user_session::UserSession;
+use crate::users::domain::users::user_session_repository::{self, UserSessionRepository};
+use crate::users::domain::users::user_status::UserStatus;
+use crate::users::domain::users::User;
+
+/// Id of the user the fixtures are built around.
+pub const USER_ID: &str = "01a153b2-0000-7000-8000-000000000002";
+
+/// Time the fixture clock starts at.
+pub fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+}
+
+/// Password of the users the fixtures save.
+pub const PASSWORD: &str = "Jane's passw0rd!";
+
+/// Hash of [`PASSWORD`] made by [`PasswordHasherCheap`].
+const PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=8,t=1,p=1$MdriQ1KcLc9Vbw5Y/lSpNw$XkvJoeOJQ0r4KZlJRwujnrO4UGWePZPPQSe5W8EUsfs";
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Copy of the user owning its fields, as a database would read it back.
+pub fn owned(user: &User) -> User<'static> {
+    User::new(
+        UserID::try_from(user.get_id().to_owned()).expect("Invalid UserID"),
+        UserName::try_from(user.get_name().to_owned()).expect("Invalid UserName"),
+        UserPassword::try_from(user.get_password().to_owned()).expect("Invalid UserPassword"),
+        UserEmail::from_parsed(
+            user.get_email().to_owned(),
+            user.get_normalized_email().to_owned(),
+        ),
+        user.get_email_verification(),
+        user.get_status().clone(),
+        user.get_audit().clone(),
+    )
+}
+
+fn owned_email(email: UserEmail) -> UserEmail<'static> {
+    UserEmail::from_parsed(email.get().to_owned(), email.get_normalized().to_owned())
+}
+
+/// User registered at the given time with the email and [`PASSWORD`], in the given status.
+fn user(id: &str, email: &str, status: UserStatus, at: DateTime<Utc>) -> User<'static> {
+    User::new(
+        UserID::try_from(id.to_owned()).expect("Invalid UserID"),
+        UserName::try_from("Jane Doe".to_owned()).expect("Invalid UserName"),
+        UserPassword::try_from(PASSWORD_HASH.to_owned()).expect("Invalid UserPassword"),
+        owned_email(EmailPolicyPlain.parse(email).expect("Invalid UserEmail")),
+        EmailVerification::Unverified,
+        status,
+        UserAudit::created(at, None),
+    )
+}
+
+/// In-memory dependencies the user services are built from in their tests, with a clock frozen
+/// at [`now`].
+pub struct Fixture {
+    pub users: Arc<UserRepositoryInMemory>,
+    pub password_history: Arc<PasswordHistoryRepositoryInMemory>,
+    pub email_verification_requests: Arc<EmailVerificationRequestRecording>,
+    pub password_reset_tokens: Arc<PasswordResetTokenRepositoryInMemory>,
+    pub email_verification_tokens: Arc<EmailVerificationTokenRepositoryInMemory>,
+    pub login_failures: Arc<LoginFailuresRepositoryInMemory>,
+    pub sessions: Arc<UserSessionRepositoryInMemory>,
+    pub refresh_tokens: Arc<RefreshTokenRepositoryInMemory>,
+    pub access_token_signer: Arc<AccessTokenSignerInMemory>,
+    pub api_keys: Arc<ApiKeyRepositoryInMemory>,
+    pub two_factors: Arc<TwoFactorRepositoryInMemory>,
+    pub mailer: Arc<MailerInMemory>,
+    pub event_bus: Arc<EventBusRecording>,
+    pub clock: Arc<ClockFixed>,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Fixture {
+            users: Default::default(),
+            password_history: Default::default(),
+            email_verification_requests: Default::default(),
+            password_reset_tokens: Default::default(),
+            email_verification_tokens: Default::default(),
+            login_failures: Default::default(),
+            sessions: Default::default(),
+            refresh_tokens: Default::default(),
+            access_token_signer: Default::default(),
+            api_keys: Default::default(),
+            two_factors: Default::default(),
+            mailer: Default::default(),
+            event_bus: Default::default(),
+            clock: Arc::new(ClockFixed::new(now())),
+        }
+    }
+}
+
+impl Fixture {
+    /// Saves a user registered with the id and email at the current time of the clock.
+    pub fn with_user(self, id: &str, email: &str) -> Self {
+        self.with_user_in(id, email, UserStatus::Pending)
+    }
+
+    /// Saves a user like [`Fixture::with_user`], which has moved to the given status since.
+    pub fn with_user_in(self, id: &str, email: &str, status: UserStatus) -> Self {
+        let user = user(id, email, status, self.clock.now());
+        self.users.save(&user).expect("User not saved");
+
+        self
+    }
+
+    /// Finds the users saved in the fixture, for the services depending on [`UserFind`].
+    pub fn user_find(&self) -> Arc<UserFindInMemory> {
+        Arc::new(UserFindInMemory(self.users.clone()))
+    }
+
+    /// The user with the id as saved.
+    pub fn user(&self, id: &str) -> User<'static> {
+        let id = UserID::try_from(id).expect("Invalid UserID");
+
+        self.users
+            .find_by(&id)
+            .as_ref()
+            .map(owned)
+            .expect("User not saved")
+    }
+}
+
+#[derive(Default)]
+pub struct UserRepositoryInMemory {
+    users: Mutex<Vec<User<'static>>>,
+}
+
+impl UserRepository for UserRepositoryInMemory {
+    fn save(&self, user: &User) -> Result<(), RepositoryErrors> {
+        let mut users = lock(&self.users);
+
+        if users.iter().any(|saved| saved.get_id() == user.get_id()) {
+            return Err(RepositoryErrors::AlreadyExists);
+        }
+
+        users.push(owned(user));
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &UserID) -> Option<User<'_>> {
+        lock(&self.users)
+            .iter()
+            .find(|user| user.get_id() == id.get() && !user.is_deleted())
+            .map(owned)
+    }
+
+    fn find_deleted_by(&self, id: &UserID) -> Result<Option<User<'_>>, RepositoryErrors> {
+        Ok(lock(&self.users)
+            .iter()
+            .find(|user| user.get_id() == id.get() && user.is_deleted())
+            .map(owned))
+    }
+
+    fn find_by_email(&self, normalized_email: &str) -> Result<Option<User<'_>>, RepositoryErrors> {
+        Ok(lock(&self.users)
+            .iter()
+            .find(|user| user.get_normalized_email() == normalized_email)
+            .map(owned))
+    }
+
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>> {
+        lock(&self.users)
+            .iter()
+            .filter(|user| include_deleted || !user.is_deleted())
+            .map(owned)
+            .collect()
+    }
+
+    fn update(&self, user: &User) -> Result<(), RepositoryErrors> {
+        let mut users = lock(&self.users);
+
+        if let Some(saved) = users
+            .iter_mut()
+            .find(|saved| saved.get_id() == user.get_id())
+        {
+            *saved = owned(user);
+        }
+
+        Ok(())
+    }
+
+    fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<usize, RepositoryErrors> {
+        let mut users = lock(&self.users);
+        let count = users.len();
+
+        users.retain(|user| {
+            user.get_audit()
+                .get_deleted_at()
+                .is_none_or(|deleted_at| deleted_at >= before)
+        });
+
+        Ok(count - users.len())
+    }
+}
+
+/// Finds the users of a repository like the service does.
+pub struct UserFindInMemory(Arc<UserRepositoryInMemory>);
+
+impl UserFind for UserFindInMemory {
+    fn find_by(&self, id: &str) -> Result<Option<User<'_>>, UserFindErrors> {
+        Ok(self.0.find_by(&UserID::try_from(id)?))
+    }
+
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>> {
+        self.0.get_all(include_deleted)
+    }
+}
+
+#[derive(Default)]
+pub struct PasswordHistoryRepositoryInMemory {
+    passwords: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl PasswordHistoryRepository for PasswordHistoryRepositoryInMemory {
+    fn find_last(
+        &self,
+        user_id: &str,
+        count: usize,
+    ) -> password_history_repository::Result<Vec<String>> {
+        Ok(lock(&self.passwords)
+            .get(user_id)
+            .map(|passwords| passwords.iter().take(count).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn add(
+        &self,
+        user_id: &str,
+        password: &str,
+        keep: usize,
+    ) -> password_history_repository::Result<()> {
+        let mut passwords = lock(&self.passwords);
+        let passwords = passwords.entry(user_id.to_owned()).or_default();
+
+        passwords.insert(0, password.to_owned());
+        passwords.truncate(keep);
+
+        Ok(())
+    }
+}
+
+/// Keeps the ids of the users an email verification is requested for, mailing nothing.
+#[derive(Default)]
+pub struct EmailVerificationRequestRecording {
+    users: Mutex<Vec<String>>,
+}
+
+impl EmailVerificationRequestRecording {
+    /// Ids of the users an email verification was requested for, oldest first.
+    pub fn requested(&self) -> Vec<String> {
+        lock(&self.users).clone()
+    }
+}
+
+impl UserEmailVerificationRequest for EmailVerificationRequestRecording {
+    fn request(&self, user: &User) -> Result<(), UserEmailVerificationRequestErrors> {
+        lock(&self.users).push(user.get_id().to_owned());
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct UserSessionRepositoryInMemory {
+    sessions: Mutex<HashMap<String, UserSession>>,
+}
+
+impl UserSessionRepository for UserSessionRepositoryInMemory {
+    fn save(&self, session: &UserSession) -> user_session_repository::Result<()> {
+        lock(&self.sessions).insert(session.get_id().to_owned(), session.clone());
+
+        Ok(())
+    }
+
+    fn update(&self, session: &UserSession) -> user_session_repository::Result<()> {
+        self.save(session)
+    }
+
+    fn find_by(&self, id: &str) -> user_session_repository::Result<Option<UserSession>> {
+        Ok(lock(&self.sessions).get(id).cloned())
+    }
+
+    fn find_active_by_user(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> user_session_repository::Result<Vec<UserSession>> {
+        let mut sessions: Vec<UserSession> = lock(&self.sessions)
+            .values()
+            .filter(|session| session.get_user_id() == user_id && session.is_active(now))
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.get_last_used_at()));
+
+        Ok(sessions)
+    }
+
+    fn revoke_by_user(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> user_session_repository::Result<usize> {
+        let mut sessions = lock(&self.sessions);
+        let active: Vec<String> = sessions
+            .values()
+            .filter(|session| session.get_user_id() == user_id && session.is_active(now))
+            .map(|session| session.get_id().to_owned())
+            .collect();
+
+        for id in &active {
+            if let Some(session) = sessions.remove(id) {
+                sessions.insert(id.clone(), session.revoke());
+            }
+        }
+
+        Ok(active.len())
+    }
+}
+
+#[derive(Default)]
+pub struct RefreshTokenRepositoryInMemory {
+    tokens: Mutex<HashMap<String, RefreshToken>>,
+}
+
+impl RefreshTokenRepository for RefreshTokenRepositoryInMemory {
+    fn save(&self, token: &RefreshToken) -> refresh_token_repository::Result<()> {
+        lock(&self.tokens).insert(token.get_id().to_owned(), token.clone());
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> refresh_token_repository::Result<Option<RefreshToken>> {
+        Ok(lock(&self.tokens).get(id).cloned())
+    }
+
+    fn rotate(&self, id: &str) -> refresh_token_repository::Result<bool> {
+        let mut tokens = lock(&self.tokens);
+
+        let Some(token) = tokens.get(id).filter(|token| !token.is_rotated()) else {
+            return Ok(false);
+        };
+
+        let rotated = RefreshToken::new(
+            token.get_id(),
+            token.get_session_id(),
+            token.get_secret_hash(),
+            token.get_created_at(),
+            true,
+        );
+        tokens.insert(id.to_owned(), rotated);
+
+        Ok(true)
+    }
+}
+
+/// Hands out the position of the token among the signed ones as its value, which only this
+/// signer can verify.
+#[derive(Default)]
+pub struct AccessTokenSignerInMemory {
+    tokens: Mutex<Vec<AccessToken>>,
+}
+
+impl AccessTokenSigner for AccessTokenSignerInMemory {
+    fn sign(&self, token: &AccessToken) -> Result<String, AccessTokenErrors> {
+        let mut tokens = lock(&self.tokens);
+        tokens.push(token.clone());
+
+        Ok((tokens.len() - 1).to_string())
+    }
+
+    fn verify(&self, value: &str) -> Result<AccessToken, AccessTokenErrors> {
+        value
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| lock(&self.tokens).get(index).cloned())
+            .ok_or(AccessTokenErrors::Invalid)
+    }
+}
+
+#[derive(Default)]
+pub struct ApiKeyRepositoryInMemory {
+    api_keys: Mutex<HashMap<String, ApiKey>>,
+}
+
+impl ApiKeyRepository for ApiKeyRepositoryInMemory {
+    fn save(&self, api_key: &ApiKey) -> api_key_repository::Result<()> {
+        lock(&self.api_keys).insert(api_key.get_id().to_owned(), api_key.clone());
+
+        Ok(())
+    }
+
+    fn update(&self, api_key: &ApiKey) -> api_key_repository::Result<()> {
+        self.save(api_key)
+    }
+
+    fn find_by(&self, id: &str) -> api_key_repository::Result<Option<ApiKey>> {
+        Ok(lock(&self.api_keys).get(id).cloned())
+    }
+
+    fn find_by_prefix(&self, prefix: &str) -> api_key_repository::Result<Option<ApiKey>> {
+        Ok(lock(&self.api_keys)
+            .values()
+            .find(|api_key| api_key.get_prefix() == prefix)
+            .cloned())
+    }
+
+    fn find_active_by_user(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> api_key_repository::Result<Vec<ApiKey>> {
+        let mut api_keys: Vec<ApiKey> = lock(&self.api_keys)
+            .values()
+            .filter(|api_key| api_key.get_user_id() == user_id && api_key.is_active(now))
+            .cloned()
+            .collect();
+        api_keys.sort_by_key(|api_key| std::cmp::Reverse(api_key.get_created_at()));
+
+        Ok(api_keys)
+    }
+}
+
+#[derive(Default)]
+pub struct TwoFactorRepositoryInMemory {
+    two_factors: Mutex<HashMap<String, TwoFactor>>,
+}
+
+impl TwoFactorRepository for TwoFactorRepositoryInMemory {
+    fn find_by(&self, user_id: &str) -> two_factor_repository::Result<Option<TwoFactor>> {
+        Ok(lock(&self.two_factors).get(user_id).cloned())
+    }
+
+    fn save(&self, two_factor: &TwoFactor) -> two_factor_repository::Result<()> {
+        lock(&self.two_factors).insert(two_factor.get_user_id().to_owned(), two_factor.clone());
+
+        Ok(())
+    }
+
+    fn delete_by(&self, user_id: &str) -> two_factor_repository::Result<bool> {
+        Ok(lock(&self.two_factors).remove(user_id).is_some())
+    }
+}
+
+#[derive(Default)]
+pub struct PasswordResetTokenRepositoryInMemory {
+    tokens: Mutex<HashMap<String, PasswordResetToken>>,
+}
+
+impl PasswordResetTokenRepositoryInMemory {
+    /// Tokens saved so far, in no particular order.
+    pub fn saved(&self) -> Vec<PasswordResetToken> {
+        lock(&self.tokens).values().cloned().collect()
+    }
+}
+
+impl PasswordResetTokenRepository for PasswordResetTokenRepositoryInMemory {
+    fn save(&self, token: &PasswordResetToken) -> password_reset_token_repository::Result<()> {
+        lock(&self.tokens).insert(token.get_id().to_owned(), token.clone());
+
+        Ok(())
+    }
+
+    fn find_by(
+        &self,
+        id: &str,
+    ) -> password_reset_token_repository::Result<Option<PasswordResetToken>> {
+        Ok(lock(&self.tokens).get(id).cloned())
+    }
+
+    fn delete_by(&self, id: &str) -> password_reset_token_repository::Result<bool> {
+        Ok(lock(&self.tokens).remove(id).is_some())
+    }
+
+    fn delete_by_user(&self, user_id: &str) -> password_reset_token_repository::Result<()> {
+        lock(&self.tokens).retain(|_, token| token.get_user_id() != user_id);
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct EmailVerificationTokenRepositoryInMemory {
+    tokens: Mutex<HashMap<String, EmailVerificationToken>>,
+}
+
+impl EmailVerificationTokenRepositoryInMemory {
+    /// Tokens saved so far, in no particular order.
+    pub fn saved(&self) -> Vec<EmailVerificationToken> {
+        lock(&self.tokens).values().cloned().collect()
+    }
+}
+
+impl EmailVerificationTokenRepository for EmailVerificationTokenRepositoryInMemory {
+    fn save(
+        &self,
+        token: &EmailVerificationToken,
+    ) -> email_verification_token_repository::Result<()> {
+        lock(&self.tokens).insert(token.get_id().to_owned(), token.clone());
+
+        Ok(())
+    }
+
+    fn find_by(
+        &self,
+        id: &str,
+    ) -> email_verification_token_repository::Result<Option<EmailVerificationToken>> {
+        Ok(lock(&self.tokens).get(id).cloned())
+    }
+
+    fn delete_by(&self, id: &str) -> email_verification_token_repository::Result<bool> {
+        Ok(lock(&self.tokens).remove(id).is_some())
+    }
+
+    fn delete_by_user(&self, user_id: &str) -> email_verification_token_repository::Result<()> {
+        lock(&self.tokens).retain(|_, token| token.get_user_id() != user_id);
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct LoginFailuresRepositoryInMemory {
+    failures: Mutex<HashMap<String, LoginFailures>>,
+}
+
+impl LoginFailuresRepository for LoginFailuresRepositoryInMemory {
+    fn find_by(&self, key: &str) -> login_failures_repository::Result<Option<LoginFailures>> {
+        Ok(lock(&self.failures).get(key).cloned())
+    }
+
+    fn save(&self, failures: &LoginFailures) -> login_failures_repository::Result<()> {
+        lock(&self.failures).insert(failures.get_key().to_owned(), failures.clone());
+
+        Ok(())
+    }
+
+    fn delete_by(&self, key: &str) -> login_failures_repository::Result<()> {
+        lock(&self.failures).remove(key);
+
+        Ok(())
+    }
+}
+
+/// Keeps the status changes published, no subscriber is ever called.
+#[derive(Default)]
+pub struct EventBusRecording {
+    status_changes: Mutex<Vec<UserStatusChanged>>,
+}
+
+impl EventBusRecording {
+    /// Status changes published so far, oldest first.
+    pub fn status_changes(&self) -> Vec<UserStatusChanged> {
+        lock(&self.status_changes).clone()
+    }
+}
+
+impl EventBus for EventBusRecording {
+    fn publish(&self, event: &dyn DomainEvent) -> Result<(), EventBusErrors> {
+        if let Some(changed) = event.as_any().downcast_ref::<UserStatusChanged>() {
+            lock(&self.status_changes).push(changed.clone());
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&self, _subscriber: Arc<dyn EventSubscriber>) {}
+}
+
+/// Accepts every password, for the tests that aren't about the policy.
+pub struct PasswordPolicyAcceptAll;
+
+impl PasswordPolicy for PasswordPolicyAcceptAll {
+    fn check(&self, _password: &str, _name: &str, _email: &str) -> Result<(), UserPasswordErrors> {
+        Ok(())
+    }
+
+    fn history_size(&self) -> usize {
+        0
+    }
+}
+
+/// Only refuses passwords shorter than the default policy does, for the tests about the policy
+/// being applied.
+pub struct PasswordPolicyMinLength;
+
+impl PasswordPolicy for PasswordPolicyMinLength {
+    fn check(&self, password: &str, _name: &str, _email: &str) -> Result<(), UserPasswordErrors> {
+        let length = password.chars().count();
+        if length < MIN_PASSWORD_LENGTH {
+            return Err(UserPasswordErrors::NotLongEnough {
+                length,
+                min: MIN_PASSWORD_LENGTH,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn history_size(&self) -> usize {
+        5
+    }
+}
+
+/// Only ignores the case of the emails, for the tests that aren't about the policy.
+pub struct EmailPolicyPlain;
+
+impl EmailPolicy for EmailPolicyPlain {
+    fn parse<'a>(&self, email: &'a str) -> Result<UserEmail<'a>, UserEmailErrors> {
+        UserEmail::parse(email, |local_part, domain| {
+            format!("{}@{domain}", local_part.to_lowercase())
+        })
+    }
+
+    fn check(&self, _email: &UserEmail) -> Result<(), UserEmailErrors> {
+        Ok(())
+    }
+}
+
+/// Argon2 with the lowest costs it accepts, so hashing doesn't slow the tests down.
+pub struct PasswordHasherCheap;
+
+impl PasswordHasherCheap {
+    fn argon2() -> Argon2<'static> {
+        let params = Params::new(
+            Params::MIN_M_COST,
+            Params::MIN_T_COST,
+            Params::MIN_P_COST,
+            None,
+        )
+        .expect("Invalid Argon2 parameters");
+
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    }
+}
+
+impl PasswordHasher for PasswordHasherCheap {
+    fn hash(&self, password: &str) -> Result<String, UserPasswordErrors> {
+        let salt = SaltString::generate(OsRng);
+
+        Ok(Self::argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("Password not hashed")
+            .to_string())
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, UserPasswordErrors> {
+        let hash = PasswordHash::new(hash).expect("Invalid PHC string");
+
+        Ok(Self::argon2()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok())
+    }
+
+    fn verify_dummy(&self, password: &str) {
+        let _ = self.verify(password, PASSWORD_HASH);
+    }
+
+    fn needs_rehash(&self, _hash: &str) -> bool {
+        false
+    }
+}
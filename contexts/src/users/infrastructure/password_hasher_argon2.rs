@@ -0,0 +1,149 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use password_hash::rand_core::OsRng;
+use password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use serde::Deserialize;
+use shaku::Component;
+
+use crate::users::domain::users::password_hasher::PasswordHasher;
+use crate::users::domain::users::user_password::UserPasswordErrors;
+use crate::users::domain::users::user_password::UserPasswordErrors::{
+    HashingError, PHCFormatError,
+};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Argon2Algorithm {
+    Argon2d,
+    Argon2i,
+    #[default]
+    Argon2id,
+}
+
+impl From<Argon2Algorithm> for Algorithm {
+    fn from(value: Argon2Algorithm) -> Self {
+        match value {
+            Argon2Algorithm::Argon2d => Algorithm::Argon2d,
+            Argon2Algorithm::Argon2i => Algorithm::Argon2i,
+            Argon2Algorithm::Argon2id => Algorithm::Argon2id,
+        }
+    }
+}
+
+/// Parameters of the Argon2 hashes, configured per deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Argon2Config {
+    pub algorithm: Argon2Algorithm,
+    /// Memory cost in KiB.
+    pub memory_cost: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Server-side secret mixed into every hash, changing it invalidates every password.
+    pub pepper: Option<String>,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Argon2Config {
+            algorithm: Argon2Algorithm::default(),
+            memory_cost: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            pepper: None,
+        }
+    }
+}
+
+/// Salt and output of the hash verified when there is none, which no password matches.
+const DUMMY_SALT: &str = "ZHVtbXlzYWx0ZHVtbXk";
+const DUMMY_OUTPUT: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+fn hashing_error(error: impl Into<anyhow::Error>) -> UserPasswordErrors {
+    HashingError {
+        source: error.into(),
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = PasswordHasher)]
+pub struct PasswordHasherArgon2 {
+    config: Argon2Config,
+}
+
+impl PasswordHasherArgon2 {
+    fn params(&self) -> Result<Params, UserPasswordErrors> {
+        Params::new(
+            self.config.memory_cost,
+            self.config.iterations,
+            self.config.parallelism,
+            None,
+        )
+        .map_err(hashing_error)
+    }
+
+    fn argon2(&self) -> Result<Argon2<'_>, UserPasswordErrors> {
+        let algorithm = Algorithm::from(self.config.algorithm);
+        let params = self.params()?;
+
+        match &self.config.pepper {
+            None => Ok(Argon2::new(algorithm, Version::V0x13, params)),
+            Some(pepper) => {
+                Argon2::new_with_secret(pepper.as_bytes(), algorithm, Version::V0x13, params)
+                    .map_err(hashing_error)
+            }
+        }
+    }
+}
+
+impl PasswordHasher for PasswordHasherArgon2 {
+    fn hash(&self, password: &str) -> Result<String, UserPasswordErrors> {
+        let salt = SaltString::generate(OsRng);
+
+        self.argon2()?
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(hashing_error)
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> Result<bool, UserPasswordErrors> {
+        let hash = PasswordHash::new(hash).map_err(|error| PHCFormatError {
+            source: error.into(),
+        })?;
+
+        match self.argon2()?.verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(error) => Err(hashing_error(error)),
+        }
+    }
+
+    fn verify_dummy(&self, password: &str) {
+        let hash = format!(
+            "${}$v={}$m={},t={},p={}${DUMMY_SALT}${DUMMY_OUTPUT}",
+            Algorithm::from(self.config.algorithm).ident(),
+            u32::from(Version::V0x13),
+            self.config.memory_cost,
+            self.config.iterations,
+            self.config.parallelism,
+        );
+
+        // Only the time spent matters, the password never matches.
+        let _ = self.verify(password, &hash);
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+
+        let Ok(params) = Params::try_from(&hash) else {
+            return true;
+        };
+
+        hash.algorithm != Algorithm::from(self.config.algorithm).ident()
+            || hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != self.config.memory_cost
+            || params.t_cost() != self.config.iterations
+            || params.p_cost() != self.config.parallelism
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+
+use shaku::Component;
+
+use crate::shared::domain::regex::{has_lowercase, has_number, has_symbol, has_uppercase};
+use crate::users::domain::users::password_policy::{PasswordPolicy, PasswordPolicyRules};
+use crate::users::domain::users::user_password::UserPasswordErrors;
+use crate::users::domain::users::user_password::UserPasswordErrors::{
+    Banned, ContainsUserData, Missing, NotLongEnough, TooLong, TooWeak,
+};
+
+/// Parts of the name or email shorter than this aren't searched for inside passwords.
+const MIN_USER_INPUT_LENGTH: usize = 4;
+
+#[derive(Component)]
+#[shaku(interface = PasswordPolicy)]
+pub struct PasswordPolicyZxcvbn {
+    rules: PasswordPolicyRules,
+    /// Lowercase banned passwords, loaded from the file of the rules.
+    banned_passwords: HashSet<String>,
+}
+
+impl PasswordPolicyZxcvbn {
+    fn user_inputs<'a>(name: &'a str, email: &'a str) -> Vec<&'a str> {
+        let local_part = email.split('@').next().unwrap_or_default();
+
+        name.split_whitespace()
+            .chain([local_part])
+            .filter(|input| input.chars().count() >= MIN_USER_INPUT_LENGTH)
+            .collect()
+    }
+}
+
+impl PasswordPolicy for PasswordPolicyZxcvbn {
+    fn check(&self, password: &str, name: &str, email: &str) -> Result<(), UserPasswordErrors> {
+        let rules = &self.rules;
+
+        let length = password.chars().count();
+        if length < rules.min_length {
+            return Err(NotLongEnough {
+                length,
+                min: rules.min_length,
+            });
+        }
+
+        if length > rules.max_length {
+            return Err(TooLong {
+                length,
+                max: rules.max_length,
+            });
+        }
+
+        if rules.require_lowercase && !has_lowercase(password) {
+            return Err(Missing("Lowercase letters"));
+        }
+
+        if rules.require_uppercase && !has_uppercase(password) {
+            return Err(Missing("Uppercase letters"));
+        }
+
+        if rules.require_symbol && !has_symbol(password) {
+            return Err(Missing("Symbols"));
+        }
+
+        if rules.require_number && !has_number(password) {
+            return Err(Missing("Numbers"));
+        }
+
+        let lowercase = password.to_lowercase();
+        if self.banned_passwords.contains(&lowercase) {
+            return Err(Banned);
+        }
+
+        let user_inputs = Self::user_inputs(name, email);
+        if rules.forbid_user_data
+            && user_inputs
+                .iter()
+                .any(|input| lowercase.contains(&input.to_lowercase()))
+        {
+            return Err(ContainsUserData);
+        }
+
+        if rules.min_strength > 0 {
+            let score = u8::from(zxcvbn::zxcvbn(password, &user_inputs).score());
+            if score < rules.min_strength {
+                return Err(TooWeak {
+                    score,
+                    min: rules.min_strength,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn history_size(&self) -> usize {
+        self.rules.history_size
+    }
+}
@@ -1,11 +1,24 @@
 use crate::shared::domain::criteria::filter::Operator;
 use crate::shared::domain::criteria::order::OrderType;
+use crate::users::domain::users::email_policy::EmailPolicy;
+use sqlite::{Connection, Error as SQLiteError, State};
+use thiserror::Error;
 
+mod api_key_repository_sqlite;
 pub mod container;
 mod criteria_sqlite;
 mod mappers;
+mod password_history_repository_sqlite;
+mod email_verification_token_repository_sqlite;
+mod login_failures_repository_sqlite;
+mod password_reset_token_repository_sqlite;
+mod refresh_token_repository_sqlite;
+mod two_factor_challenge_repository_sqlite;
+mod two_factor_repository_sqlite;
 mod user_criteria_repository_sqlite;
 mod user_repository_sqlite;
+mod user_search_repository_sqlite;
+mod user_session_repository_sqlite;
 
 const DATABASE_FILE: &str = "database.sqlite";
 
@@ -15,22 +28,492 @@ CREATE TABLE users (
     id TEXT PRIMARY KEY NOT NULL,
     name TEXT NOT NULL,
     password TEXT NOT NULL,
-    email TEXT NOT NULL
+    email TEXT NOT NULL,
+    email_verified_at INTEGER,
+    email_normalized TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    created_by TEXT,
+    updated_at INTEGER NOT NULL,
+    updated_by TEXT,
+    last_login_at INTEGER,
+    deleted_at INTEGER,
+    status TEXT NOT NULL,
+    status_reason TEXT
 )"#;
 
+// language=SQL
+const SQL_COLUMN_USERS_EMAIL_VERIFIED_AT: &str =
+    "ALTER TABLE users ADD COLUMN email_verified_at INTEGER";
+
+// Filled by `migrate_emails`, normalizing needs the email policy.
+// language=SQL
+const SQL_COLUMN_USERS_EMAIL_NORMALIZED: &str =
+    "ALTER TABLE users ADD COLUMN email_normalized TEXT";
+
+// language=SQL
+const SQL_SELECT_USERS_EMAIL: &str = "SELECT id, email, email_normalized FROM users";
+
+// language=SQL
+const SQL_UPDATE_USERS_EMAIL_NORMALIZED: &str =
+    "UPDATE users SET email_normalized = ? WHERE id = ?";
+
+// language=SQL
+const SQL_SELECT_USERS_EMAIL_DUPLICATED: &str = r#"
+SELECT email_normalized, group_concat(id, ', ') FROM users
+GROUP BY email_normalized HAVING count(*) > 1
+LIMIT 1
+"#;
+
+// Users created before are considered created when the columns are added.
+// language=SQL
+const SQL_COLUMN_USERS_AUDIT: &str = r#"
+ALTER TABLE users ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN created_by TEXT;
+ALTER TABLE users ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN updated_by TEXT;
+ALTER TABLE users ADD COLUMN last_login_at INTEGER;
+
+UPDATE users SET created_at = strftime('%s', 'now'), updated_at = strftime('%s', 'now')
+WHERE created_at = 0;
+"#;
+
+// language=SQL
+const SQL_COLUMN_USERS_DELETED_AT: &str = "ALTER TABLE users ADD COLUMN deleted_at INTEGER";
+
+// Users created before are active once they verified their email, pending until then.
+// language=SQL
+const SQL_COLUMN_USERS_STATUS: &str = r#"
+ALTER TABLE users ADD COLUMN status TEXT NOT NULL DEFAULT 'pending';
+ALTER TABLE users ADD COLUMN status_reason TEXT;
+
+UPDATE users SET status = 'active' WHERE email_verified_at IS NOT NULL;
+"#;
+
+// language=SQL
+const SQL_DROP_INDEX_USERS_EMAIL: &str = "DROP INDEX IF EXISTS users_email_normalized";
+
+// language=SQL
+const SQL_INDEX_USERS_EMAIL: &str =
+    "CREATE UNIQUE INDEX IF NOT EXISTS users_email_normalized ON users (email_normalized)";
+
+// Indexes the names and emails of the users without a copy of them, the implicit rowid of the
+// users table links both. A VACUUM can renumber it, the index has to be rebuilt after one.
+// language=SQL
+const SQL_TABLE_USERS_SEARCH: &str = r#"
+CREATE VIRTUAL TABLE users_search USING fts5(
+    name,
+    email,
+    content='users',
+    content_rowid='rowid'
+)"#;
+
+// language=SQL
+const SQL_POPULATE_USERS_SEARCH: &str =
+    "INSERT INTO users_search (users_search) VALUES ('rebuild')";
+
+// External content tables only forget what they're told to, with the old values.
+// language=SQL
+const SQL_TRIGGERS_USERS_SEARCH: &str = r#"
+CREATE TRIGGER IF NOT EXISTS users_search_insert AFTER INSERT ON users BEGIN
+    INSERT INTO users_search (rowid, name, email) VALUES (new.rowid, new.name, new.email);
+END;
+
+CREATE TRIGGER IF NOT EXISTS users_search_update AFTER UPDATE OF name, email ON users BEGIN
+    INSERT INTO users_search (users_search, rowid, name, email)
+    VALUES ('delete', old.rowid, old.name, old.email);
+    INSERT INTO users_search (rowid, name, email) VALUES (new.rowid, new.name, new.email);
+END;
+
+CREATE TRIGGER IF NOT EXISTS users_search_delete AFTER DELETE ON users BEGIN
+    INSERT INTO users_search (users_search, rowid, name, email)
+    VALUES ('delete', old.rowid, old.name, old.email);
+END;
+"#;
+
+// The index of previous versions kept its own copy of the users, it's built again.
+// language=SQL
+const SQL_SELECT_USERS_SEARCH_COPY: &str = r#"
+SELECT count(*) FROM sqlite_master
+WHERE name = 'users_search' AND sql NOT LIKE '%content=%'
+"#;
+
+// language=SQL
+const SQL_DROP_USERS_SEARCH_COPY: &str = r#"
+DROP TRIGGER IF EXISTS users_search_insert;
+DROP TRIGGER IF EXISTS users_search_update;
+DROP TRIGGER IF EXISTS users_search_delete;
+DROP TABLE users_search;
+"#;
+
+// language=SQL
+const SQL_TABLE_PASSWORD_HISTORY: &str = r#"
+CREATE TABLE password_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    password TEXT NOT NULL
+)"#;
+
+// language=SQL
+const SQL_POPULATE_PASSWORD_HISTORY: &str = r#"
+INSERT INTO password_history (user_id, password) SELECT id, password FROM users
+"#;
+
+// language=SQL
+const SQL_TRIGGERS_PASSWORD_HISTORY: &str = r#"
+CREATE INDEX IF NOT EXISTS password_history_user_id ON password_history (user_id);
+
+CREATE TRIGGER IF NOT EXISTS password_history_delete AFTER DELETE ON users BEGIN
+    DELETE FROM password_history WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_PASSWORD_RESET_TOKENS: &str = r#"
+CREATE TABLE password_reset_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_PASSWORD_RESET_TOKENS: &str = r#"
+CREATE INDEX IF NOT EXISTS password_reset_tokens_user_id ON password_reset_tokens (user_id);
+
+CREATE TRIGGER IF NOT EXISTS password_reset_tokens_delete AFTER DELETE ON users BEGIN
+    DELETE FROM password_reset_tokens WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_EMAIL_VERIFICATION_TOKENS: &str = r#"
+CREATE TABLE email_verification_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    email TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_EMAIL_VERIFICATION_TOKENS: &str = r#"
+CREATE INDEX IF NOT EXISTS email_verification_tokens_user_id ON email_verification_tokens (user_id);
+
+CREATE TRIGGER IF NOT EXISTS email_verification_tokens_delete AFTER DELETE ON users BEGIN
+    DELETE FROM email_verification_tokens WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_LOGIN_FAILURES: &str = r#"
+CREATE TABLE login_failures (
+    key TEXT PRIMARY KEY NOT NULL,
+    failures INTEGER NOT NULL,
+    lockouts INTEGER NOT NULL,
+    last_failed_at INTEGER NOT NULL,
+    locked_until INTEGER
+)"#;
+
+// language=SQL
+const SQL_TABLE_USER_SESSIONS: &str = r#"
+CREATE TABLE user_sessions (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    user_agent TEXT,
+    ip TEXT,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    revoked INTEGER NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_USER_SESSIONS: &str = r#"
+CREATE INDEX IF NOT EXISTS user_sessions_user_id ON user_sessions (user_id);
+
+CREATE TRIGGER IF NOT EXISTS user_sessions_delete AFTER DELETE ON users BEGIN
+    DELETE FROM user_sessions WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_REFRESH_TOKENS: &str = r#"
+CREATE TABLE refresh_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    rotated INTEGER NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_REFRESH_TOKENS: &str = r#"
+CREATE INDEX IF NOT EXISTS refresh_tokens_session_id ON refresh_tokens (session_id);
+
+CREATE TRIGGER IF NOT EXISTS refresh_tokens_delete AFTER DELETE ON user_sessions BEGIN
+    DELETE FROM refresh_tokens WHERE session_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_TWO_FACTOR: &str = r#"
+CREATE TABLE two_factor (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    secret TEXT NOT NULL,
+    confirmed_at INTEGER,
+    last_used_step INTEGER,
+    recovery_codes TEXT NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_TWO_FACTOR: &str = r#"
+CREATE TRIGGER IF NOT EXISTS two_factor_delete AFTER DELETE ON users BEGIN
+    DELETE FROM two_factor WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_TWO_FACTOR_CHALLENGES: &str = r#"
+CREATE TABLE two_factor_challenges (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    secret_hash TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    attempts INTEGER NOT NULL
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_TWO_FACTOR_CHALLENGES: &str = r#"
+CREATE INDEX IF NOT EXISTS two_factor_challenges_user_id ON two_factor_challenges (user_id);
+
+CREATE TRIGGER IF NOT EXISTS two_factor_challenges_delete AFTER DELETE ON users BEGIN
+    DELETE FROM two_factor_challenges WHERE user_id = old.id;
+END;
+"#;
+
+// language=SQL
+const SQL_TABLE_API_KEYS: &str = r#"
+CREATE TABLE api_keys (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL UNIQUE,
+    secret_hash TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    last_used_at INTEGER,
+    revoked_at INTEGER
+)"#;
+
+// language=SQL
+const SQL_TRIGGERS_API_KEYS: &str = r#"
+CREATE INDEX IF NOT EXISTS api_keys_user_id ON api_keys (user_id);
+
+CREATE TRIGGER IF NOT EXISTS api_keys_delete AFTER DELETE ON users BEGIN
+    DELETE FROM api_keys WHERE user_id = old.id;
+END;
+"#;
+
 pub const USER_TABLE_NAME: &str = "users";
-pub const USER_TABLE_FIELDS: [&'static str; 4] = ["id", "name", "password", "email"];
+pub const USER_TABLE_FIELDS: [&str; 11] = [
+    "id",
+    "name",
+    "password",
+    "email",
+    "created_at",
+    "created_by",
+    "updated_at",
+    "updated_by",
+    "last_login_at",
+    "deleted_at",
+    "status",
+];
+/// Fields stored as seconds since the epoch, filtered with RFC 3339 timestamps or dates.
+pub const USER_TABLE_TIMESTAMP_FIELDS: [&str; 4] =
+    ["created_at", "updated_at", "last_login_at", "deleted_at"];
+/// Condition of the users that aren't deleted, the ones found unless deleted ones are asked for.
+pub const USER_TABLE_NOT_DELETED: &str = "deleted_at IS NULL";
 
 pub fn init() {
-    let result = sqlite::Connection::open_thread_safe(DATABASE_FILE)
-        .expect("Couldn't connect to the database")
-        .execute(SQL_TABLE_USERS);
+    let conn = sqlite::Connection::open_thread_safe(DATABASE_FILE)
+        .expect("Couldn't connect to the database");
+
+    if !create_table(&conn, SQL_TABLE_USERS) {
+        add_column(&conn, SQL_COLUMN_USERS_EMAIL_VERIFIED_AT);
+        add_column(&conn, SQL_COLUMN_USERS_EMAIL_NORMALIZED);
+        add_column(&conn, SQL_COLUMN_USERS_AUDIT);
+        add_column(&conn, SQL_COLUMN_USERS_DELETED_AT);
+        add_column(&conn, SQL_COLUMN_USERS_STATUS);
+    }
+
+    init_users_search(&conn);
+
+    if create_table(&conn, SQL_TABLE_PASSWORD_HISTORY) {
+        conn.execute(SQL_POPULATE_PASSWORD_HISTORY)
+            .expect("Password history couldn't be populated.");
+    }
+
+    conn.execute(SQL_TRIGGERS_PASSWORD_HISTORY)
+        .expect("Database couldn't be initialized.");
 
-    if let Err(err) = result {
-        if err.code.unwrap() != 1 {
-            panic!("Database couldn't be initialized.")
+    create_table(&conn, SQL_TABLE_PASSWORD_RESET_TOKENS);
+
+    conn.execute(SQL_TRIGGERS_PASSWORD_RESET_TOKENS)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_EMAIL_VERIFICATION_TOKENS);
+
+    conn.execute(SQL_TRIGGERS_EMAIL_VERIFICATION_TOKENS)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_LOGIN_FAILURES);
+
+    create_table(&conn, SQL_TABLE_USER_SESSIONS);
+
+    conn.execute(SQL_TRIGGERS_USER_SESSIONS)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_REFRESH_TOKENS);
+
+    conn.execute(SQL_TRIGGERS_REFRESH_TOKENS)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_TWO_FACTOR);
+
+    conn.execute(SQL_TRIGGERS_TWO_FACTOR)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_TWO_FACTOR_CHALLENGES);
+
+    conn.execute(SQL_TRIGGERS_TWO_FACTOR_CHALLENGES)
+        .expect("Database couldn't be initialized.");
+
+    create_table(&conn, SQL_TABLE_API_KEYS);
+
+    conn.execute(SQL_TRIGGERS_API_KEYS)
+        .expect("Database couldn't be initialized.");
+}
+
+#[derive(Error, Debug)]
+pub enum MigrationErrors {
+    #[error("Users {users} share the email '{email}' once normalized, they have to be merged before migrating")]
+    EmailShared { email: String, users: String },
+    #[error("Database couldn't be migrated")]
+    DatabaseError {
+        #[source]
+        source: SQLiteError,
+    },
+}
+
+impl From<SQLiteError> for MigrationErrors {
+    fn from(value: SQLiteError) -> Self {
+        MigrationErrors::DatabaseError { source: value }
+    }
+}
+
+/// Normalizes the emails of the users again with the rules of the policy, so the ones saved
+/// before they were, or before the rules changed, are compared as the new ones. Users sharing an
+/// email once normalized are reported and nothing is changed, they aren't merged.
+pub fn migrate_emails(email_policy: &dyn EmailPolicy) -> Result<(), MigrationErrors> {
+    let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+    migrate_emails_of(&conn, email_policy)
+}
+
+fn migrate_emails_of(
+    conn: &Connection,
+    email_policy: &dyn EmailPolicy,
+) -> Result<(), MigrationErrors> {
+    let mut changed = vec![];
+    let mut stmt = conn.prepare(SQL_SELECT_USERS_EMAIL)?;
+    while let State::Row = stmt.next()? {
+        let email = stmt.read::<String, _>(1)?;
+        let current = stmt.read::<Option<String>, _>(2)?;
+
+        // Emails saved before they were validated are only compared ignoring case.
+        let normalized = email_policy.parse(&email).map_or_else(
+            |_| email.trim().to_lowercase(),
+            |email| email.get_normalized().to_owned(),
+        );
+
+        if current.as_ref() != Some(&normalized) {
+            changed.push((stmt.read::<String, _>(0)?, normalized));
         }
     }
+
+    if changed.is_empty() {
+        conn.execute(SQL_INDEX_USERS_EMAIL)?;
+        return Ok(());
+    }
+
+    // The index is made again once every email changed, they may be shared until then.
+    conn.execute("BEGIN")?;
+    conn.execute(SQL_DROP_INDEX_USERS_EMAIL)?;
+    for (id, normalized) in &changed {
+        let mut stmt = conn.prepare(SQL_UPDATE_USERS_EMAIL_NORMALIZED)?;
+        stmt.bind((1, normalized.as_str()))?;
+        stmt.bind((2, id.as_str()))?;
+        stmt.next()?;
+    }
+
+    let mut stmt = conn.prepare(SQL_SELECT_USERS_EMAIL_DUPLICATED)?;
+    if let State::Row = stmt.next()? {
+        let error = MigrationErrors::EmailShared {
+            email: stmt.read::<String, _>(0)?,
+            users: stmt.read::<String, _>(1)?,
+        };
+        drop(stmt);
+        conn.execute("ROLLBACK")?;
+        return Err(error);
+    }
+    drop(stmt);
+
+    conn.execute(SQL_INDEX_USERS_EMAIL)?;
+    conn.execute("COMMIT")?;
+
+    Ok(())
+}
+
+/// Creates the search index of the users, replacing the one of previous versions.
+fn init_users_search(conn: &Connection) {
+    let mut stmt = conn
+        .prepare(SQL_SELECT_USERS_SEARCH_COPY)
+        .expect("Database couldn't be migrated.");
+    stmt.next().expect("Database couldn't be migrated.");
+    let has_copy = stmt.read::<i64, _>(0).expect("Database couldn't be migrated.") > 0;
+    drop(stmt);
+
+    if has_copy {
+        conn.execute(SQL_DROP_USERS_SEARCH_COPY)
+            .expect("Database couldn't be migrated.");
+    }
+
+    if create_table(conn, SQL_TABLE_USERS_SEARCH) {
+        conn.execute(SQL_POPULATE_USERS_SEARCH)
+            .expect("Search index couldn't be populated.");
+    }
+
+    conn.execute(SQL_TRIGGERS_USERS_SEARCH)
+        .expect("Database couldn't be initialized.");
+}
+
+/// Creates a table, returning `false` when it already existed.
+fn create_table(conn: &Connection, sql: &str) -> bool {
+    match conn.execute(sql) {
+        Ok(_) => true,
+        Err(err) if err.code == Some(1) => false,
+        Err(_) => panic!("Database couldn't be initialized."),
+    }
+}
+
+/// Adds a column to a table created by a previous version, doing nothing when it's already there.
+fn add_column(conn: &Connection, sql: &str) {
+    match conn.execute(sql) {
+        Ok(_) => {}
+        Err(err) if err.code == Some(1) => {}
+        Err(_) => panic!("Database couldn't be migrated."),
+    }
 }
 
 pub trait ToSQLite {
@@ -61,3 +544,191 @@ impl ToSQLite for OrderType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::infrastructure::in_memory::EmailPolicyPlain;
+
+    /// Database with users saved before their emails were normalized.
+    fn database(users: &[(&str, &str)]) -> Connection {
+        let conn = Connection::open(":memory:").unwrap();
+        conn.execute(
+            "CREATE TABLE users (id TEXT PRIMARY KEY NOT NULL, email TEXT NOT NULL, \
+             email_normalized TEXT)",
+        )
+        .unwrap();
+
+        for (id, email) in users {
+            let mut stmt = conn
+                .prepare("INSERT INTO users (id, email) VALUES (?, ?)")
+                .unwrap();
+            stmt.bind((1, *id)).unwrap();
+            stmt.bind((2, *email)).unwrap();
+            stmt.next().unwrap();
+        }
+
+        conn
+    }
+
+    fn normalized(conn: &Connection, id: &str) -> String {
+        let mut stmt = conn
+            .prepare("SELECT email_normalized FROM users WHERE id = ?")
+            .unwrap();
+        stmt.bind((1, id)).unwrap();
+        stmt.next().unwrap();
+
+        stmt.read::<String, _>(0).unwrap()
+    }
+
+    /// Whether another user with the normalized email can be saved.
+    fn can_save(conn: &Connection, email_normalized: &str) -> bool {
+        let mut stmt = conn
+            .prepare("INSERT INTO users (id, email, email_normalized) VALUES ('new', ?, ?)")
+            .unwrap();
+        stmt.bind((1, email_normalized)).unwrap();
+        stmt.bind((2, email_normalized)).unwrap();
+
+        stmt.next().is_ok()
+    }
+
+    #[test]
+    fn normalizes_the_emails_with_the_policy_and_makes_them_unique() {
+        let conn = database(&[("1", "Jane@Example.com"), ("2", "john@example.com")]);
+
+        migrate_emails_of(&conn, &EmailPolicyPlain).unwrap();
+
+        assert_eq!(normalized(&conn, "1"), "jane@example.com");
+        assert_eq!(normalized(&conn, "2"), "john@example.com");
+        assert!(!can_save(&conn, "jane@example.com"));
+    }
+
+    #[test]
+    fn normalizes_again_the_emails_normalized_with_other_rules() {
+        let conn = database(&[("1", "Jane@example.com")]);
+        conn.execute("UPDATE users SET email_normalized = 'Jane@example.com'")
+            .unwrap();
+
+        migrate_emails_of(&conn, &EmailPolicyPlain).unwrap();
+
+        assert_eq!(normalized(&conn, "1"), "jane@example.com");
+    }
+
+    #[test]
+    fn lowercases_emails_the_policy_refuses() {
+        let conn = database(&[("1", " Not An Email ")]);
+
+        migrate_emails_of(&conn, &EmailPolicyPlain).unwrap();
+
+        assert_eq!(normalized(&conn, "1"), "not an email");
+    }
+
+    #[test]
+    fn reports_users_sharing_an_email_once_normalized() {
+        let conn = database(&[
+            ("1", "Jane@example.com"),
+            ("2", "john@example.com"),
+            ("3", "jane@example.com"),
+        ]);
+
+        let result = migrate_emails_of(&conn, &EmailPolicyPlain);
+
+        assert!(matches!(
+            result,
+            Err(MigrationErrors::EmailShared { email, users })
+                if email == "jane@example.com" && users.contains('1') && users.contains('3')
+        ));
+        // Nothing is merged nor changed, the emails can't be unique yet.
+        let mut stmt = conn
+            .prepare("SELECT count(*) FROM users WHERE email_normalized IS NOT NULL")
+            .unwrap();
+        stmt.next().unwrap();
+        assert_eq!(stmt.read::<i64, _>(0).unwrap(), 0);
+        assert!(can_save(&conn, "jane@example.com"));
+    }
+
+    /// Database with users named as given, without a search index yet.
+    fn named_users(users: &[(&str, &str)]) -> Connection {
+        let conn = Connection::open(":memory:").unwrap();
+        conn.execute("CREATE TABLE users (id TEXT PRIMARY KEY NOT NULL, name TEXT, email TEXT)")
+            .unwrap();
+
+        for (id, name) in users {
+            insert_named(&conn, id, name);
+        }
+
+        conn
+    }
+
+    fn insert_named(conn: &Connection, id: &str, name: &str) {
+        let mut stmt = conn
+            .prepare("INSERT INTO users (id, name, email) VALUES (?, ?, ?)")
+            .unwrap();
+        stmt.bind((1, id)).unwrap();
+        stmt.bind((2, name)).unwrap();
+        stmt.bind((3, format!("{id}@example.com").as_str())).unwrap();
+        stmt.next().unwrap();
+    }
+
+    /// Ids of the users matching the search, in the order they were saved.
+    fn found(conn: &Connection, query: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT users.id FROM users_search \
+                 JOIN users ON users.rowid = users_search.rowid \
+                 WHERE users_search MATCH ? ORDER BY users.rowid",
+            )
+            .unwrap();
+        stmt.bind((1, query)).unwrap();
+
+        let mut ids = vec![];
+        while let State::Row = stmt.next().unwrap() {
+            ids.push(stmt.read::<String, _>(0).unwrap());
+        }
+
+        ids
+    }
+
+    #[test]
+    fn indexes_the_users_saved_before_and_after() {
+        let conn = named_users(&[("jane", "Jane Doe")]);
+
+        init_users_search(&conn);
+        insert_named(&conn, "john", "John Doe");
+
+        assert_eq!(found(&conn, "doe"), ["jane", "john"]);
+        assert_eq!(found(&conn, "\"john@example.com\""), ["john"]);
+    }
+
+    #[test]
+    fn forgets_the_old_names_of_updated_users_and_deleted_users() {
+        let conn = named_users(&[("jane", "Jane Doe"), ("john", "John Doe")]);
+        init_users_search(&conn);
+
+        conn.execute("UPDATE users SET name = 'Jane Roe' WHERE id = 'jane'")
+            .unwrap();
+        conn.execute("DELETE FROM users WHERE id = 'john'").unwrap();
+
+        assert!(found(&conn, "doe").is_empty());
+        assert_eq!(found(&conn, "roe"), ["jane"]);
+    }
+
+    #[test]
+    fn replaces_the_index_keeping_a_copy_of_the_users() {
+        let conn = named_users(&[("jane", "Jane Doe")]);
+        conn.execute(
+            "CREATE VIRTUAL TABLE users_search USING fts5(id UNINDEXED, name, email); \
+             INSERT INTO users_search (id, name, email) SELECT id, name, email FROM users; \
+             CREATE TRIGGER users_search_insert AFTER INSERT ON users BEGIN \
+                 INSERT INTO users_search (id, name, email) VALUES (new.id, new.name, new.email); \
+             END;",
+        )
+        .unwrap();
+
+        init_users_search(&conn);
+        init_users_search(&conn);
+        insert_named(&conn, "john", "John Doe");
+
+        assert_eq!(found(&conn, "doe"), ["jane", "john"]);
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::{State, Statement};
+
+use crate::users::domain::users::api_key::{ApiKey, ApiKeyScope};
+use crate::users::domain::users::api_key_repository::{
+    ApiKeyRepository, ApiKeyRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+/// Separates the scopes of a key.
+const SCOPES_SEPARATOR: char = ',';
+
+impl From<SQLiteError> for ApiKeyRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        ApiKeyRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = ApiKeyRepository)]
+pub struct ApiKeyRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str = r#"
+INSERT INTO api_keys (id, user_id, name, prefix, secret_hash, scopes, created_at, expires_at,
+    last_used_at, revoked_at)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_UPDATE: &str = "UPDATE api_keys SET last_used_at = ?, revoked_at = ? WHERE id = ?";
+// language=SQL
+const STMT_FIND_BY: &str = r#"
+SELECT id, user_id, name, prefix, secret_hash, scopes, created_at, expires_at, last_used_at,
+    revoked_at
+FROM api_keys WHERE id = ?
+"#;
+// language=SQL
+const STMT_FIND_BY_PREFIX: &str = r#"
+SELECT id, user_id, name, prefix, secret_hash, scopes, created_at, expires_at, last_used_at,
+    revoked_at
+FROM api_keys WHERE prefix = ?
+"#;
+// language=SQL
+const STMT_FIND_ACTIVE_BY_USER: &str = r#"
+SELECT id, user_id, name, prefix, secret_hash, scopes, created_at, expires_at, last_used_at,
+    revoked_at
+FROM api_keys WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ?
+ORDER BY created_at DESC
+"#;
+
+fn timestamp(stmt: &Statement, column: &str) -> Result<Option<DateTime<Utc>>> {
+    Ok(stmt
+        .read::<Option<i64>, _>(column)?
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)))
+}
+
+fn map(stmt: &Statement) -> Result<ApiKey> {
+    // Scopes dropped by a later version are ignored instead of failing every request of the key.
+    let scopes = stmt
+        .read::<String, _>("scopes")?
+        .split(SCOPES_SEPARATOR)
+        .filter_map(|scope| ApiKeyScope::try_from(scope).ok())
+        .collect();
+
+    Ok(ApiKey::new(
+        &stmt.read::<String, _>("id")?,
+        &stmt.read::<String, _>("user_id")?,
+        &stmt.read::<String, _>("name")?,
+        &stmt.read::<String, _>("prefix")?,
+        &stmt.read::<String, _>("secret_hash")?,
+        scopes,
+        timestamp(stmt, "created_at")?.unwrap_or_default(),
+        timestamp(stmt, "expires_at")?.unwrap_or_default(),
+        timestamp(stmt, "last_used_at")?,
+        timestamp(stmt, "revoked_at")?,
+    ))
+}
+
+impl ApiKeyRepositorySQLite {
+    fn find_one(&self, sql: &str, value: &str) -> Result<Option<ApiKey>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(sql)?;
+
+        stmt.bind((1, value))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        Ok(Some(map(&stmt)?))
+    }
+}
+
+impl ApiKeyRepository for ApiKeyRepositorySQLite {
+    fn save(&self, api_key: &ApiKey) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        let scopes: Vec<&str> = api_key.get_scopes().iter().map(ApiKeyScope::get).collect();
+
+        stmt.bind((1, api_key.get_id()))?;
+        stmt.bind((2, api_key.get_user_id()))?;
+        stmt.bind((3, api_key.get_name()))?;
+        stmt.bind((4, api_key.get_prefix()))?;
+        stmt.bind((5, api_key.get_secret_hash()))?;
+        stmt.bind((6, scopes.join(&SCOPES_SEPARATOR.to_string()).as_str()))?;
+        stmt.bind((7, api_key.get_created_at().timestamp()))?;
+        stmt.bind((8, api_key.get_expires_at().timestamp()))?;
+        stmt.bind((9, api_key.get_last_used_at().map(|at| at.timestamp())))?;
+        stmt.bind((10, api_key.get_revoked_at().map(|at| at.timestamp())))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn update(&self, api_key: &ApiKey) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_UPDATE)?;
+
+        stmt.bind((1, api_key.get_last_used_at().map(|at| at.timestamp())))?;
+        stmt.bind((2, api_key.get_revoked_at().map(|at| at.timestamp())))?;
+        stmt.bind((3, api_key.get_id()))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<ApiKey>> {
+        self.find_one(STMT_FIND_BY, id)
+    }
+
+    fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKey>> {
+        self.find_one(STMT_FIND_BY_PREFIX, prefix)
+    }
+
+    fn find_active_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<Vec<ApiKey>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_ACTIVE_BY_USER)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, now.timestamp()))?;
+
+        let mut api_keys = vec![];
+
+        while let State::Row = stmt.next()? {
+            api_keys.push(map(&stmt)?);
+        }
+
+        Ok(api_keys)
+    }
+}
@@ -1,21 +1,50 @@
 use crate::shared::infrastructure::dependency_container::DatabaseModule;
+use crate::users::infrastructure::sqlite::api_key_repository_sqlite::ApiKeyRepositorySQLite;
+use crate::users::infrastructure::sqlite::email_verification_token_repository_sqlite::EmailVerificationTokenRepositorySQLite;
 use crate::users::infrastructure::sqlite::init;
+use crate::users::infrastructure::sqlite::login_failures_repository_sqlite::LoginFailuresRepositorySQLite;
+use crate::users::infrastructure::sqlite::password_history_repository_sqlite::PasswordHistoryRepositorySQLite;
+use crate::users::infrastructure::sqlite::password_reset_token_repository_sqlite::PasswordResetTokenRepositorySQLite;
+use crate::users::infrastructure::sqlite::refresh_token_repository_sqlite::RefreshTokenRepositorySQLite;
 use crate::users::infrastructure::sqlite::user_criteria_repository_sqlite::UserCriteriaRepositorySQLite;
 use crate::users::infrastructure::sqlite::user_repository_sqlite::UserRepositorySQLite;
+use crate::users::infrastructure::sqlite::user_search_repository_sqlite::UserSearchRepositorySQLite;
+use crate::users::infrastructure::sqlite::two_factor_challenge_repository_sqlite::TwoFactorChallengeRepositorySQLite;
+use crate::users::infrastructure::sqlite::two_factor_repository_sqlite::{
+    encryption_key, TwoFactorRepositorySQLite, TwoFactorRepositorySQLiteParameters,
+};
+use crate::users::infrastructure::sqlite::user_session_repository_sqlite::UserSessionRepositorySQLite;
 use shaku::{module};
 
 module! {
     pub SQLiteDatabaseModule: DatabaseModule {
         components = [
             UserRepositorySQLite,
-            UserCriteriaRepositorySQLite
+            UserCriteriaRepositorySQLite,
+            UserSearchRepositorySQLite,
+            PasswordHistoryRepositorySQLite,
+            PasswordResetTokenRepositorySQLite,
+            EmailVerificationTokenRepositorySQLite,
+            LoginFailuresRepositorySQLite,
+            UserSessionRepositorySQLite,
+            RefreshTokenRepositorySQLite,
+            TwoFactorRepositorySQLite,
+            TwoFactorChallengeRepositorySQLite,
+            ApiKeyRepositorySQLite
         ],
         providers = []
     }
 }
 
-pub fn build_container() -> SQLiteDatabaseModule {
+/// Builds the module, the two-factor secrets being encrypted with the hex encoded key.
+pub fn build_container(two_factor_encryption_key: &str) -> SQLiteDatabaseModule {
     init();
 
-    SQLiteDatabaseModule::builder().build()
+    SQLiteDatabaseModule::builder()
+        .with_component_parameters::<TwoFactorRepositorySQLite>(
+            TwoFactorRepositorySQLiteParameters {
+                key: encryption_key(two_factor_encryption_key),
+            },
+        )
+        .build()
 }
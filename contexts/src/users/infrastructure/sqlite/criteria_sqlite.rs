@@ -1,12 +1,14 @@
 use crate::shared::domain::criteria::filter::Filter;
 use crate::shared::domain::criteria::order::Order;
 use crate::shared::domain::criteria::Criteria;
-use crate::users::domain::users::user_criteria_repository::CriteriaRepositoryErrors::FieldNotFound;
+use crate::users::domain::users::user_criteria_repository::CriteriaRepositoryErrors::{
+    FieldNotFound, InvalidTimestamp,
+};
 use crate::users::domain::users::user_criteria_repository::Result;
 use crate::users::infrastructure::sqlite::{ToSQLite, OP_LIKE};
+use chrono::{DateTime, NaiveDate};
 use sqlite::{Connection, State, Statement};
 
-const ORDER_BY: &str = " ORDER BY ? ";
 const LIMIT: &str = " LIMIT ?";
 const OFFSET: &str = " OFFSET ?";
 
@@ -14,37 +16,62 @@ const OFFSET: &str = " OFFSET ?";
 struct CriteriaQuery {
     pub query: String,
     pub parameters: Vec<String>,
+    /// Whether the query already has a `WHERE` clause the filters are added to.
+    pub filtered: bool,
 }
 
 impl CriteriaQuery {
-    fn new(table: &str) -> CriteriaQuery {
+    fn new(table: &str, scope: Option<&str>) -> CriteriaQuery {
+        let query = match scope {
+            Some(condition) => format!("SELECT * FROM {} WHERE {}", table, condition),
+            None => format!("SELECT * FROM {}", table),
+        };
+
         CriteriaQuery {
-            query: format!("SELECT * FROM {}", table),
+            query,
             parameters: Vec::new(),
+            filtered: scope.is_some(),
         }
     }
 
-    fn add_filter(&mut self, filter: &Filter, valid_fields: &[&str]) -> Result<()> {
+    fn add_filter(
+        &mut self,
+        filter: &Filter,
+        valid_fields: &[&str],
+        timestamp_fields: &[&str],
+    ) -> Result<()> {
         if !valid_fields.contains(&filter.field) {
             return Err(FieldNotFound(filter.field.to_owned()));
         };
 
-        self.query += &format!(" WHERE {} {} ?", filter.field, &filter.operator.to_sql());
+        let clause = if self.filtered { "AND" } else { "WHERE" };
+        self.filtered = true;
+        self.query += &format!(" {clause} {} {} ?", filter.field, filter.operator.to_sql());
+
+        let value = if timestamp_fields.contains(&filter.field) {
+            parse_timestamp(filter.value)?.to_string()
+        } else {
+            filter.value.to_owned()
+        };
 
         if OP_LIKE.contains(&filter.operator) {
-            self.parameters.push(format!("%{}%", filter.value));
+            self.parameters.push(format!("%{}%", value));
         } else {
-            self.parameters.push(filter.value.to_owned());
+            self.parameters.push(value);
         }
 
         Ok(())
     }
 
-    fn add_order(&mut self, order: &Order) {
-        self.query += ORDER_BY;
-        self.query += order.ty.to_sql();
+    fn add_order(&mut self, order: &Order, valid_fields: &[&str]) -> Result<()> {
+        if !valid_fields.contains(&order.field) {
+            return Err(FieldNotFound(order.field.to_owned()));
+        };
+
+        // Columns can't be bound as parameters, the field is one of the valid ones.
+        self.query += &format!(" ORDER BY {} {}", order.field, order.ty.to_sql());
 
-        self.parameters.push(order.field.to_owned());
+        Ok(())
     }
 
     fn add_offset(&mut self, offset: &u32) {
@@ -58,21 +85,39 @@ impl CriteriaQuery {
     }
 }
 
+/// Parses an RFC 3339 timestamp or a date, taken as its start in UTC, into seconds since the
+/// epoch as timestamps are stored.
+fn parse_timestamp(value: &str) -> Result<i64> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.timestamp());
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc().timestamp())
+        .ok_or_else(|| InvalidTimestamp(value.to_owned()))
+}
+
+/// Finds the rows of the table matching the criteria, among the ones meeting the scope condition
+/// when there's one.
 pub fn find_by<T>(
     conn: &Connection,
     table: &str,
+    scope: Option<&str>,
     valid_fields: &[&str],
+    timestamp_fields: &[&str],
     mapper: impl Fn(&Statement) -> T,
     criteria: &Criteria,
 ) -> Result<Vec<T>> {
-    let mut query = CriteriaQuery::new(table);
+    let mut query = CriteriaQuery::new(table, scope);
 
     for filter in &criteria.filters {
-        query.add_filter(filter, valid_fields)?;
+        query.add_filter(filter, valid_fields, timestamp_fields)?;
     }
 
     if let Some(order) = &criteria.order {
-        query.add_order(order);
+        query.add_order(order, valid_fields)?;
     }
 
     if let Some(limit) = &criteria.limit {
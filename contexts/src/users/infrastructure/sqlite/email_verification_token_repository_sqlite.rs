@@ -0,0 +1,100 @@
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::email_verification_token::EmailVerificationToken;
+use crate::users::domain::users::email_verification_token_repository::{
+    EmailVerificationTokenRepository, EmailVerificationTokenRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for EmailVerificationTokenRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        EmailVerificationTokenRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = EmailVerificationTokenRepository)]
+pub struct EmailVerificationTokenRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str = r#"
+INSERT INTO email_verification_tokens (id, user_id, email, secret_hash, expires_at)
+VALUES (?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY: &str =
+    "SELECT id, user_id, email, secret_hash, expires_at FROM email_verification_tokens WHERE id = ?";
+// language=SQL
+const STMT_DELETE_BY: &str = "DELETE FROM email_verification_tokens WHERE id = ?";
+// language=SQL
+const STMT_DELETE_BY_USER: &str = "DELETE FROM email_verification_tokens WHERE user_id = ?";
+
+impl EmailVerificationTokenRepository for EmailVerificationTokenRepositorySQLite {
+    fn save(&self, token: &EmailVerificationToken) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, token.get_id()))?;
+        stmt.bind((2, token.get_user_id()))?;
+        stmt.bind((3, token.get_email()))?;
+        stmt.bind((4, token.get_secret_hash()))?;
+        stmt.bind((5, token.get_expires_at().timestamp()))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<EmailVerificationToken>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let expires_at =
+            DateTime::from_timestamp(stmt.read::<i64, _>("expires_at")?, 0).unwrap_or_default();
+
+        Ok(Some(EmailVerificationToken::new(
+            &stmt.read::<String, _>("id")?,
+            &stmt.read::<String, _>("user_id")?,
+            &stmt.read::<String, _>("email")?,
+            &stmt.read::<String, _>("secret_hash")?,
+            expires_at,
+        )))
+    }
+
+    fn delete_by(&self, id: &str) -> Result<bool> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY)?;
+
+        stmt.bind((1, id))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count() > 0)
+    }
+
+    fn delete_by_user(&self, user_id: &str) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY_USER)?;
+
+        stmt.bind((1, user_id))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::login_failures::LoginFailures;
+use crate::users::domain::users::login_failures_repository::{
+    LoginFailuresRepository, LoginFailuresRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for LoginFailuresRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        LoginFailuresRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = LoginFailuresRepository)]
+pub struct LoginFailuresRepositorySQLite {}
+
+// language=SQL
+const STMT_UPSERT: &str = r#"
+INSERT OR REPLACE INTO login_failures (key, failures, lockouts, last_failed_at, locked_until)
+VALUES (?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY: &str = r#"
+SELECT key, failures, lockouts, last_failed_at, locked_until FROM login_failures WHERE key = ?
+"#;
+// language=SQL
+const STMT_DELETE_BY: &str = "DELETE FROM login_failures WHERE key = ?";
+
+impl LoginFailuresRepository for LoginFailuresRepositorySQLite {
+    fn find_by(&self, key: &str) -> Result<Option<LoginFailures>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, key))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let last_failed_at = DateTime::from_timestamp(stmt.read::<i64, _>("last_failed_at")?, 0)
+            .unwrap_or_default();
+        let locked_until = stmt
+            .read::<Option<i64>, _>("locked_until")?
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+
+        Ok(Some(LoginFailures::new(
+            &stmt.read::<String, _>("key")?,
+            u32::try_from(stmt.read::<i64, _>("failures")?).unwrap_or_default(),
+            u32::try_from(stmt.read::<i64, _>("lockouts")?).unwrap_or_default(),
+            last_failed_at,
+            locked_until,
+        )))
+    }
+
+    fn save(&self, failures: &LoginFailures) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_UPSERT)?;
+
+        stmt.bind((1, failures.get_key()))?;
+        stmt.bind((2, i64::from(failures.get_failures())))?;
+        stmt.bind((3, i64::from(failures.get_lockouts())))?;
+        stmt.bind((4, failures.get_last_failed_at().timestamp()))?;
+        stmt.bind((5, failures.get_locked_until().map(|at| at.timestamp())))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn delete_by(&self, key: &str) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY)?;
+
+        stmt.bind((1, key))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+}
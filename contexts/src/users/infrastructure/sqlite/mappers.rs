@@ -1,8 +1,13 @@
+use crate::users::domain::users::email_verification::EmailVerification;
+use crate::users::domain::users::user_audit::UserAudit;
 use crate::users::domain::users::user_email::UserEmail;
 use crate::users::domain::users::user_id::UserID;
 use crate::users::domain::users::user_name::UserName;
 use crate::users::domain::users::user_password::UserPassword;
+use crate::users::domain::users::user_search_repository::UserSearchMatch;
+use crate::users::domain::users::user_status::UserStatus;
 use crate::users::domain::users::User;
+use chrono::{DateTime, Utc};
 use sqlite::Statement;
 
 pub fn get_user(statement: &Statement) -> User<'static> {
@@ -25,11 +30,60 @@ pub fn get_user(statement: &Statement) -> User<'static> {
                 .expect("Expected String User Password"),
         )
         .expect("Invalid Database UserPassword"),
-        UserEmail::try_from(
+        UserEmail::from_parsed(
             statement
                 .read::<String, _>(3)
                 .expect("Expected String User Email"),
+            statement
+                .read::<String, _>("email_normalized")
+                .expect("Expected String User Normalized Email"),
+        ),
+        EmailVerification::from(
+            statement
+                .read::<Option<i64>, _>("email_verified_at")
+                .expect("Expected Integer User Email Verified At")
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)),
+        ),
+        UserStatus::from_parsed(
+            &statement
+                .read::<String, _>("status")
+                .expect("Expected String User Status"),
+            statement
+                .read::<Option<String>, _>("status_reason")
+                .expect("Expected String User Status Reason"),
         )
-        .expect("Invalid Database UserEmail"),
+        .expect("Invalid Database UserStatus"),
+        UserAudit::new(
+            get_timestamp(statement, "created_at").expect("Expected User Created At"),
+            statement
+                .read::<Option<String>, _>("created_by")
+                .expect("Expected String User Created By"),
+            get_timestamp(statement, "updated_at").expect("Expected User Updated At"),
+            statement
+                .read::<Option<String>, _>("updated_by")
+                .expect("Expected String User Updated By"),
+            get_timestamp(statement, "last_login_at"),
+            get_timestamp(statement, "deleted_at"),
+        ),
     )
 }
+
+fn get_timestamp(statement: &Statement, column: &str) -> Option<DateTime<Utc>> {
+    statement
+        .read::<Option<i64>, _>(column)
+        .expect("Expected Integer Timestamp")
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+}
+
+pub fn get_user_search_match(statement: &Statement) -> UserSearchMatch<'static> {
+    UserSearchMatch {
+        user: get_user(statement),
+        score: statement.read::<f64, _>(4).expect("Expected Float Score"),
+        name_highlight: statement
+            .read::<String, _>(5)
+            .expect("Expected String Name Highlight"),
+        email_highlight: statement
+            .read::<String, _>(6)
+            .expect("Expected String Email Highlight"),
+    }
+}
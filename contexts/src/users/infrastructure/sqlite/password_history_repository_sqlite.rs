@@ -0,0 +1,71 @@
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::password_history_repository::{
+    PasswordHistoryRepository, PasswordHistoryRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for PasswordHistoryRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        PasswordHistoryRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = PasswordHistoryRepository)]
+pub struct PasswordHistoryRepositorySQLite {}
+
+// language=SQL
+const STMT_FIND_LAST: &str =
+    "SELECT password FROM password_history WHERE user_id = ? ORDER BY id DESC LIMIT ?";
+// language=SQL
+const STMT_INSERT: &str = "INSERT INTO password_history (user_id, password) VALUES (?, ?)";
+// language=SQL
+const STMT_PRUNE: &str = r#"
+DELETE FROM password_history
+WHERE user_id = ?
+  AND id NOT IN (SELECT id FROM password_history WHERE user_id = ? ORDER BY id DESC LIMIT ?)
+"#;
+
+impl PasswordHistoryRepository for PasswordHistoryRepositorySQLite {
+    fn find_last(&self, user_id: &str, count: usize) -> Result<Vec<String>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_LAST)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, count as i64))?;
+
+        let mut passwords = vec![];
+        while let State::Row = stmt.next()? {
+            passwords.push(stmt.read::<String, _>("password")?);
+        }
+
+        Ok(passwords)
+    }
+
+    fn add(&self, user_id: &str, password: &str, keep: usize) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, password))?;
+
+        stmt.next()?;
+
+        let mut stmt = conn.prepare(STMT_PRUNE)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, user_id))?;
+        stmt.bind((3, keep as i64))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::password_reset_token::PasswordResetToken;
+use crate::users::domain::users::password_reset_token_repository::{
+    PasswordResetTokenRepository, PasswordResetTokenRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for PasswordResetTokenRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        PasswordResetTokenRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = PasswordResetTokenRepository)]
+pub struct PasswordResetTokenRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str =
+    "INSERT INTO password_reset_tokens (id, user_id, secret_hash, expires_at) VALUES (?, ?, ?, ?)";
+// language=SQL
+const STMT_FIND_BY: &str =
+    "SELECT id, user_id, secret_hash, expires_at FROM password_reset_tokens WHERE id = ?";
+// language=SQL
+const STMT_DELETE_BY: &str = "DELETE FROM password_reset_tokens WHERE id = ?";
+// language=SQL
+const STMT_DELETE_BY_USER: &str = "DELETE FROM password_reset_tokens WHERE user_id = ?";
+
+impl PasswordResetTokenRepository for PasswordResetTokenRepositorySQLite {
+    fn save(&self, token: &PasswordResetToken) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, token.get_id()))?;
+        stmt.bind((2, token.get_user_id()))?;
+        stmt.bind((3, token.get_secret_hash()))?;
+        stmt.bind((4, token.get_expires_at().timestamp()))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<PasswordResetToken>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let expires_at =
+            DateTime::from_timestamp(stmt.read::<i64, _>("expires_at")?, 0).unwrap_or_default();
+
+        Ok(Some(PasswordResetToken::new(
+            &stmt.read::<String, _>("id")?,
+            &stmt.read::<String, _>("user_id")?,
+            &stmt.read::<String, _>("secret_hash")?,
+            expires_at,
+        )))
+    }
+
+    fn delete_by(&self, id: &str) -> Result<bool> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY)?;
+
+        stmt.bind((1, id))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count() > 0)
+    }
+
+    fn delete_by_user(&self, user_id: &str) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY_USER)?;
+
+        stmt.bind((1, user_id))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::refresh_token::RefreshToken;
+use crate::users::domain::users::refresh_token_repository::{
+    RefreshTokenRepository, RefreshTokenRepositoryErrors, Result,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for RefreshTokenRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        RefreshTokenRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = RefreshTokenRepository)]
+pub struct RefreshTokenRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str = r#"
+INSERT INTO refresh_tokens (id, session_id, secret_hash, created_at, rotated) VALUES (?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY: &str =
+    "SELECT id, session_id, secret_hash, created_at, rotated FROM refresh_tokens WHERE id = ?";
+// language=SQL
+const STMT_ROTATE: &str = "UPDATE refresh_tokens SET rotated = 1 WHERE id = ? AND rotated = 0";
+
+impl RefreshTokenRepository for RefreshTokenRepositorySQLite {
+    fn save(&self, token: &RefreshToken) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, token.get_id()))?;
+        stmt.bind((2, token.get_session_id()))?;
+        stmt.bind((3, token.get_secret_hash()))?;
+        stmt.bind((4, token.get_created_at().timestamp()))?;
+        stmt.bind((5, i64::from(token.is_rotated())))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<RefreshToken>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let created_at =
+            DateTime::from_timestamp(stmt.read::<i64, _>("created_at")?, 0).unwrap_or_default();
+
+        Ok(Some(RefreshToken::new(
+            &stmt.read::<String, _>("id")?,
+            &stmt.read::<String, _>("session_id")?,
+            &stmt.read::<String, _>("secret_hash")?,
+            created_at,
+            stmt.read::<i64, _>("rotated")? != 0,
+        )))
+    }
+
+    fn rotate(&self, id: &str) -> Result<bool> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_ROTATE)?;
+
+        stmt.bind((1, id))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count() > 0)
+    }
+}
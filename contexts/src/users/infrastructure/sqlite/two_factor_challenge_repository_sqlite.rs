@@ -0,0 +1,102 @@
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::two_factor_challenge::TwoFactorChallenge;
+use crate::users::domain::users::two_factor_challenge_repository::{
+    Result, TwoFactorChallengeRepository, TwoFactorChallengeRepositoryErrors,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for TwoFactorChallengeRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        TwoFactorChallengeRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = TwoFactorChallengeRepository)]
+pub struct TwoFactorChallengeRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str = r#"
+INSERT INTO two_factor_challenges (id, user_id, secret_hash, expires_at, attempts)
+VALUES (?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY: &str = r#"
+SELECT id, user_id, secret_hash, expires_at, attempts FROM two_factor_challenges WHERE id = ?
+"#;
+// language=SQL
+const STMT_ADD_ATTEMPT: &str =
+    "UPDATE two_factor_challenges SET attempts = attempts + 1 WHERE id = ?";
+// language=SQL
+const STMT_DELETE_BY: &str = "DELETE FROM two_factor_challenges WHERE id = ?";
+
+impl TwoFactorChallengeRepository for TwoFactorChallengeRepositorySQLite {
+    fn save(&self, challenge: &TwoFactorChallenge) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, challenge.get_id()))?;
+        stmt.bind((2, challenge.get_user_id()))?;
+        stmt.bind((3, challenge.get_secret_hash()))?;
+        stmt.bind((4, challenge.get_expires_at().timestamp()))?;
+        stmt.bind((5, i64::from(challenge.get_attempts())))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<TwoFactorChallenge>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let expires_at =
+            DateTime::from_timestamp(stmt.read::<i64, _>("expires_at")?, 0).unwrap_or_default();
+
+        Ok(Some(TwoFactorChallenge::new(
+            &stmt.read::<String, _>("id")?,
+            &stmt.read::<String, _>("user_id")?,
+            &stmt.read::<String, _>("secret_hash")?,
+            expires_at,
+            u32::try_from(stmt.read::<i64, _>("attempts")?).unwrap_or_default(),
+        )))
+    }
+
+    fn add_attempt(&self, id: &str) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_ADD_ATTEMPT)?;
+
+        stmt.bind((1, id))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn delete_by(&self, id: &str) -> Result<bool> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY)?;
+
+        stmt.bind((1, id))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count() > 0)
+    }
+}
@@ -0,0 +1,239 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::DateTime;
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::two_factor::TwoFactor;
+use crate::users::domain::users::two_factor_repository::{
+    Result, TwoFactorRepository, TwoFactorRepositoryErrors,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+/// Bytes of the AES-256 key encrypting the secrets.
+const KEY_LENGTH: usize = 32;
+/// Bytes of the AES-GCM nonce stored before each encrypted secret.
+const NONCE_LENGTH: usize = 12;
+/// Separates the hashes of the recovery codes, which are hex encoded.
+const RECOVERY_CODES_SEPARATOR: char = ',';
+
+impl From<SQLiteError> for TwoFactorRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        TwoFactorRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+/// Key encrypting the secrets, hex encoded in the configuration.
+pub fn encryption_key(configured: &str) -> Vec<u8> {
+    from_hex(configured.trim())
+        .filter(|key| key.len() == KEY_LENGTH)
+        .expect("Two-factor encryption key has to be 32 hex encoded bytes.")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Stores the enrollments with their secrets encrypted with AES-256-GCM, as the nonce followed by
+/// the ciphertext, hex encoded.
+#[derive(Component)]
+#[shaku(interface = TwoFactorRepository)]
+pub struct TwoFactorRepositorySQLite {
+    key: Vec<u8>,
+}
+
+impl TwoFactorRepositorySQLite {
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key))
+    }
+
+    fn encrypt(&self, secret: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(&nonce, secret.as_bytes())
+            .map_err(|_| internal_error("Two-factor secret couldn't be encrypted"))?;
+
+        Ok(to_hex(&[nonce.as_slice(), &ciphertext].concat()))
+    }
+
+    fn decrypt(&self, encrypted: &str) -> Result<String> {
+        let bytes = from_hex(encrypted)
+            .filter(|bytes| bytes.len() > NONCE_LENGTH)
+            .ok_or_else(|| internal_error("Two-factor secret is malformed"))?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+
+        let secret = self
+            .cipher()
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| internal_error("Two-factor secret couldn't be decrypted"))?;
+
+        String::from_utf8(secret).map_err(|error| TwoFactorRepositoryErrors::InternalServerError {
+            source: error.into(),
+        })
+    }
+}
+
+fn internal_error(message: &'static str) -> TwoFactorRepositoryErrors {
+    TwoFactorRepositoryErrors::InternalServerError {
+        source: anyhow::anyhow!(message),
+    }
+}
+
+// language=SQL
+const STMT_UPSERT: &str = r#"
+INSERT OR REPLACE INTO two_factor (user_id, secret, confirmed_at, last_used_step, recovery_codes)
+VALUES (?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY: &str = r#"
+SELECT user_id, secret, confirmed_at, last_used_step, recovery_codes FROM two_factor
+WHERE user_id = ?
+"#;
+// language=SQL
+const STMT_DELETE_BY: &str = "DELETE FROM two_factor WHERE user_id = ?";
+
+impl TwoFactorRepository for TwoFactorRepositorySQLite {
+    fn find_by(&self, user_id: &str) -> Result<Option<TwoFactor>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, user_id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        let confirmed_at = stmt
+            .read::<Option<i64>, _>("confirmed_at")?
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+        let last_used_step = stmt
+            .read::<Option<i64>, _>("last_used_step")?
+            .and_then(|step| u64::try_from(step).ok());
+        let recovery_code_hashes = stmt
+            .read::<String, _>("recovery_codes")?
+            .split(RECOVERY_CODES_SEPARATOR)
+            .filter(|hash| !hash.is_empty())
+            .map(str::to_owned)
+            .collect();
+
+        Ok(Some(TwoFactor::new(
+            &stmt.read::<String, _>("user_id")?,
+            &self.decrypt(&stmt.read::<String, _>("secret")?)?,
+            confirmed_at,
+            last_used_step,
+            recovery_code_hashes,
+        )))
+    }
+
+    fn save(&self, two_factor: &TwoFactor) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_UPSERT)?;
+
+        stmt.bind((1, two_factor.get_user_id()))?;
+        stmt.bind((2, self.encrypt(two_factor.get_secret())?.as_str()))?;
+        stmt.bind((3, two_factor.get_confirmed_at().map(|at| at.timestamp())))?;
+        stmt.bind((
+            4,
+            two_factor
+                .get_last_used_step()
+                .and_then(|step| i64::try_from(step).ok()),
+        ))?;
+        stmt.bind((
+            5,
+            two_factor
+                .get_recovery_code_hashes()
+                .join(&RECOVERY_CODES_SEPARATOR.to_string())
+                .as_str(),
+        ))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn delete_by(&self, user_id: &str) -> Result<bool> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_DELETE_BY)?;
+
+        stmt.bind((1, user_id))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn repository(key: &str) -> TwoFactorRepositorySQLite {
+        TwoFactorRepositorySQLite {
+            key: encryption_key(key),
+        }
+    }
+
+    #[test]
+    fn decrypts_what_it_encrypts() {
+        let repository = repository(KEY);
+        let encrypted = repository.encrypt("JBSWY3DPEHPK3PXP").unwrap();
+
+        assert!(!encrypted.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(repository.decrypt(&encrypted).unwrap(), "JBSWY3DPEHPK3PXP");
+    }
+
+    #[test]
+    fn encrypts_with_a_new_nonce_every_time() {
+        let repository = repository(KEY);
+
+        assert_ne!(
+            repository.encrypt("JBSWY3DPEHPK3PXP").unwrap(),
+            repository.encrypt("JBSWY3DPEHPK3PXP").unwrap()
+        );
+    }
+
+    #[test]
+    fn refuses_tampered_secrets_and_other_keys() {
+        let encrypted = repository(KEY).encrypt("JBSWY3DPEHPK3PXP").unwrap();
+
+        let mut tampered = encrypted.clone().into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'0' { b'1' } else { b'0' };
+        let tampered = String::from_utf8(tampered).unwrap();
+
+        assert!(repository(KEY).decrypt(&tampered).is_err());
+        assert!(repository(&KEY.replace("00", "ff"))
+            .decrypt(&encrypted)
+            .is_err());
+        assert!(repository(KEY)
+            .decrypt(&encrypted[..NONCE_LENGTH * 2])
+            .is_err());
+        assert!(repository(KEY).decrypt("not hex").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "32 hex encoded bytes")]
+    fn requires_keys_of_32_bytes() {
+        encryption_key("0001020304");
+    }
+}
@@ -5,25 +5,19 @@ use crate::users::domain::users::user_criteria_repository::{
 use crate::users::domain::users::User;
 use crate::users::infrastructure::sqlite::mappers::get_user;
 use crate::users::infrastructure::sqlite::{
-    criteria_sqlite, DATABASE_FILE, USER_TABLE_FIELDS, USER_TABLE_NAME,
+    criteria_sqlite, DATABASE_FILE, USER_TABLE_FIELDS, USER_TABLE_NAME, USER_TABLE_NOT_DELETED,
+    USER_TABLE_TIMESTAMP_FIELDS,
 };
 use shaku::Component;
 use sqlite::{Error as SQLiteError};
 
 impl From<SQLiteError> for CriteriaRepositoryErrors {
     fn from(value: SQLiteError) -> Self {
-        if let Some(code) = value.code {
-            match code {
-                _ => unmapped_error(value),
-            }
-        } else {
-            unmapped_error(value)
-        }
+        unmapped_error(value)
     }
 }
 
 fn unmapped_error(error: SQLiteError) -> CriteriaRepositoryErrors {
-    dbg!(&error);
     CriteriaRepositoryErrors::InternalServerError {
         source: anyhow::Error::from(error),
     }
@@ -34,15 +28,17 @@ fn unmapped_error(error: SQLiteError) -> CriteriaRepositoryErrors {
 pub struct UserCriteriaRepositorySQLite {}
 
 impl UserCriteriaRepository for UserCriteriaRepositorySQLite {
-    fn find_by(&self, criteria: &Criteria) -> Result<Vec<User>> {
+    fn find_by(&self, criteria: &Criteria, include_deleted: bool) -> Result<Vec<User<'_>>> {
         let conn = sqlite::Connection::open(DATABASE_FILE)?;
 
-        Ok(criteria_sqlite::find_by(
+        criteria_sqlite::find_by(
             &conn,
             USER_TABLE_NAME,
+            (!include_deleted).then_some(USER_TABLE_NOT_DELETED),
             &USER_TABLE_FIELDS,
+            &USER_TABLE_TIMESTAMP_FIELDS,
             get_user,
             criteria,
-        )?)
+        )
     }
 }
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use shaku::Component;
 use sqlite::Error as SQLiteError;
 use sqlite::State;
@@ -12,6 +13,13 @@ impl From<SQLiteError> for RepositoryErrors {
     fn from(value: SQLiteError) -> Self {
         if let Some(code) = value.code {
             match code {
+                19 if value
+                    .message
+                    .as_deref()
+                    .is_some_and(|message| message.contains(EMAIL_CONSTRAINT)) =>
+                {
+                    RepositoryErrors::EmailAlreadyTaken
+                }
                 19 => RepositoryErrors::AlreadyExists,
                 _ => unmapped_error(value),
             }
@@ -22,26 +30,60 @@ impl From<SQLiteError> for RepositoryErrors {
 }
 
 fn unmapped_error(error: SQLiteError) -> RepositoryErrors {
-    dbg!(&error);
     RepositoryErrors::InternalServerError {
         source: anyhow::Error::from(error),
     }
 }
 
+fn email_verified_at(user: &User) -> Option<i64> {
+    user.get_email_verification()
+        .verified_at()
+        .map(|at| at.timestamp())
+}
+
+fn last_login_at(user: &User) -> Option<i64> {
+    user.get_audit()
+        .get_last_login_at()
+        .map(|at| at.timestamp())
+}
+
+fn deleted_at(user: &User) -> Option<i64> {
+    user.get_audit().get_deleted_at().map(|at| at.timestamp())
+}
+
+/// Column of the unique index, named by SQLite in the message of the constraint violations.
+const EMAIL_CONSTRAINT: &str = "users.email_normalized";
+
 #[derive(Component)]
 #[shaku(interface = UserRepository)]
 pub struct UserRepositorySQLite {}
 
 // language=SQL
-const STMT_INSERT: &str = "INSERT INTO users (id, name, password, email) VALUES (?, ?, ?, ?)";
+const STMT_INSERT: &str = r#"
+INSERT INTO users (
+    id, name, password, email, email_verified_at, email_normalized,
+    created_at, created_by, updated_at, updated_by, last_login_at, deleted_at, status, status_reason
+)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_FIND_BY_ID: &str = "SELECT * FROM users WHERE id = ? AND deleted_at IS NULL";
+// language=SQL
+const STMT_FIND_DELETED_BY_ID: &str = "SELECT * FROM users WHERE id = ? AND deleted_at IS NOT NULL";
+// language=SQL
+const STMT_FIND_BY_EMAIL: &str = "SELECT * FROM users WHERE email_normalized = ? LIMIT 1";
 // language=SQL
-const STMT_FIND_BY_ID: &str = "SELECT * FROM users WHERE id = ?";
+const STMT_GET_ALL: &str = "SELECT * FROM users WHERE deleted_at IS NULL";
 // language=SQL
-const STMT_GET_ALL: &str = "SELECT * FROM users";
+const STMT_GET_ALL_INCLUDING_DELETED: &str = "SELECT * FROM users";
 // language=SQL
-const STMT_UPDATE: &str = "UPDATE users SET name = ?, password = ?, email = ? WHERE id = ?";
+const STMT_UPDATE: &str = r#"
+UPDATE users SET name = ?, password = ?, email = ?, email_verified_at = ?, email_normalized = ?,
+    updated_at = ?, updated_by = ?, last_login_at = ?, deleted_at = ?, status = ?, status_reason = ?
+WHERE id = ?
+"#;
 // language=SQL
-const STMT_DELETE: &str = "DELETE FROM users WHERE id = ?";
+const STMT_PURGE_DELETED: &str = "DELETE FROM users WHERE deleted_at < ?";
 
 impl UserRepository for UserRepositorySQLite {
     fn save(&self, user: &User) -> Result<(), RepositoryErrors> {
@@ -53,31 +95,72 @@ impl UserRepository for UserRepositorySQLite {
         stmt.bind((2, user.get_name()))?;
         stmt.bind((3, user.get_password()))?;
         stmt.bind((4, user.get_email()))?;
+        stmt.bind((5, email_verified_at(user)))?;
+        stmt.bind((6, user.get_normalized_email()))?;
+        stmt.bind((7, user.get_audit().get_created_at().timestamp()))?;
+        stmt.bind((8, user.get_audit().get_created_by()))?;
+        stmt.bind((9, user.get_audit().get_updated_at().timestamp()))?;
+        stmt.bind((10, user.get_audit().get_updated_by()))?;
+        stmt.bind((11, last_login_at(user)))?;
+        stmt.bind((12, deleted_at(user)))?;
+        stmt.bind((13, user.get_status().get()))?;
+        stmt.bind((14, user.get_status().get_reason()))?;
 
         stmt.next()?;
 
         Ok(())
     }
 
-    fn find_by(&self, id: &UserID) -> Option<User> {
+    fn find_by(&self, id: &UserID) -> Option<User<'_>> {
         let conn = sqlite::Connection::open(DATABASE_FILE).ok()?;
 
         let mut stmt = conn.prepare(STMT_FIND_BY_ID).ok()?;
 
         stmt.bind((1, id.to_string().as_str())).ok()?;
 
-        stmt.next().ok()?;
+        match stmt.next().ok()? {
+            State::Row => Some(get_user(&stmt)),
+            State::Done => None,
+        }
+    }
+
+    fn find_deleted_by(&self, id: &UserID) -> Result<Option<User<'_>>, RepositoryErrors> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_DELETED_BY_ID)?;
+
+        stmt.bind((1, id.to_string().as_str()))?;
+
+        match stmt.next()? {
+            State::Row => Ok(Some(get_user(&stmt))),
+            State::Done => Ok(None),
+        }
+    }
+
+    fn find_by_email(&self, normalized_email: &str) -> Result<Option<User<'_>>, RepositoryErrors> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY_EMAIL)?;
+
+        stmt.bind((1, normalized_email))?;
 
-        Some(get_user(&stmt))
+        match stmt.next()? {
+            State::Row => Ok(Some(get_user(&stmt))),
+            State::Done => Ok(None),
+        }
     }
 
-    fn get_all(&self) -> Vec<User> {
+    fn get_all(&self, include_deleted: bool) -> Vec<User<'_>> {
         let conn = match sqlite::Connection::open(DATABASE_FILE) {
             Ok(conn) => conn,
             Err(_) => return vec![],
         };
 
-        let stmt = conn.prepare(STMT_GET_ALL);
+        let stmt = conn.prepare(if include_deleted {
+            STMT_GET_ALL_INCLUDING_DELETED
+        } else {
+            STMT_GET_ALL
+        });
 
         if stmt.is_err() {
             return vec![];
@@ -93,30 +176,38 @@ impl UserRepository for UserRepositorySQLite {
         users
     }
 
-    fn delete_by(&self, id: &UserID) -> Result<(), RepositoryErrors> {
+    fn update(&self, user: &User) -> Result<(), RepositoryErrors> {
         let conn = sqlite::Connection::open(DATABASE_FILE)?;
 
-        let mut stmt = conn.prepare(STMT_DELETE)?;
+        let mut stmt = conn.prepare(STMT_UPDATE)?;
 
-        stmt.bind((1, id.to_string().as_str()))?;
+        stmt.bind((1, user.get_name()))?;
+        stmt.bind((2, user.get_password()))?;
+        stmt.bind((3, user.get_email()))?;
+        stmt.bind((4, email_verified_at(user)))?;
+        stmt.bind((5, user.get_normalized_email()))?;
+        stmt.bind((6, user.get_audit().get_updated_at().timestamp()))?;
+        stmt.bind((7, user.get_audit().get_updated_by()))?;
+        stmt.bind((8, last_login_at(user)))?;
+        stmt.bind((9, deleted_at(user)))?;
+        stmt.bind((10, user.get_status().get()))?;
+        stmt.bind((11, user.get_status().get_reason()))?;
+        stmt.bind((12, user.get_id()))?;
 
         stmt.next()?;
 
         Ok(())
     }
 
-    fn update(&self, user: &User) -> Result<(), RepositoryErrors> {
+    fn purge_deleted_before(&self, before: DateTime<Utc>) -> Result<usize, RepositoryErrors> {
         let conn = sqlite::Connection::open(DATABASE_FILE)?;
 
-        let mut stmt = conn.prepare(STMT_UPDATE)?;
+        let mut stmt = conn.prepare(STMT_PURGE_DELETED)?;
 
-        stmt.bind((1, user.get_name()))?;
-        stmt.bind((2, user.get_password()))?;
-        stmt.bind((3, user.get_email()))?;
-        stmt.bind((4, user.get_id()))?;
+        stmt.bind((1, before.timestamp()))?;
 
         stmt.next()?;
 
-        Ok(())
+        Ok(conn.change_count())
     }
 }
@@ -0,0 +1,73 @@
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::State;
+
+use crate::users::domain::users::user_search_repository::{
+    Result, SearchRepositoryErrors, UserSearchMatch, UserSearchRepository,
+};
+use crate::users::infrastructure::sqlite::mappers::get_user_search_match;
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for SearchRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        SearchRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = UserSearchRepository)]
+pub struct UserSearchRepositorySQLite {}
+
+// language=SQL
+const STMT_SEARCH: &str = r#"
+SELECT users.id, users.name, users.password, users.email,
+       -bm25(users_search) AS score,
+       highlight(users_search, 0, '<mark>', '</mark>'),
+       highlight(users_search, 1, '<mark>', '</mark>'),
+       users.email_verified_at, users.email_normalized,
+       users.created_at, users.created_by, users.updated_at, users.updated_by, users.last_login_at,
+       users.deleted_at, users.status, users.status_reason
+FROM users_search
+JOIN users ON users.rowid = users_search.rowid
+WHERE users_search MATCH ? AND users.deleted_at IS NULL
+ORDER BY score DESC
+LIMIT ?
+"#;
+
+/// Turns free text into an FTS5 query, every term is quoted so user input can't inject
+/// query syntax, and matched as a prefix so partial names are found.
+fn to_match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"*", term))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl UserSearchRepository for UserSearchRepositorySQLite {
+    fn search(&self, query: &str, limit: u32) -> Result<Vec<UserSearchMatch<'_>>> {
+        let expression = to_match_expression(query);
+
+        if expression.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_SEARCH)?;
+
+        stmt.bind((1, expression.as_str()))?;
+        stmt.bind((2, limit as i64))?;
+
+        let mut matches = vec![];
+        while let State::Row = stmt.next()? {
+            matches.push(get_user_search_match(&stmt));
+        }
+
+        Ok(matches)
+    }
+}
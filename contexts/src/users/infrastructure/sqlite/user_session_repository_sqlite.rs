@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use shaku::Component;
+use sqlite::Error as SQLiteError;
+use sqlite::{State, Statement};
+
+use crate::users::domain::users::user_session::UserSession;
+use crate::users::domain::users::user_session_repository::{
+    Result, UserSessionRepository, UserSessionRepositoryErrors,
+};
+use crate::users::infrastructure::sqlite::DATABASE_FILE;
+
+impl From<SQLiteError> for UserSessionRepositoryErrors {
+    fn from(value: SQLiteError) -> Self {
+        UserSessionRepositoryErrors::InternalServerError {
+            source: anyhow::Error::from(value),
+        }
+    }
+}
+
+#[derive(Component)]
+#[shaku(interface = UserSessionRepository)]
+pub struct UserSessionRepositorySQLite {}
+
+// language=SQL
+const STMT_INSERT: &str = r#"
+INSERT INTO user_sessions (id, user_id, user_agent, ip, created_at, last_used_at, expires_at, revoked)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+"#;
+// language=SQL
+const STMT_UPDATE: &str = r#"
+UPDATE user_sessions SET user_agent = ?, ip = ?, last_used_at = ?, expires_at = ?, revoked = ?
+WHERE id = ?
+"#;
+// language=SQL
+const STMT_FIND_BY: &str = r#"
+SELECT id, user_id, user_agent, ip, created_at, last_used_at, expires_at, revoked
+FROM user_sessions WHERE id = ?
+"#;
+// language=SQL
+const STMT_FIND_ACTIVE_BY_USER: &str = r#"
+SELECT id, user_id, user_agent, ip, created_at, last_used_at, expires_at, revoked
+FROM user_sessions WHERE user_id = ? AND revoked = 0 AND expires_at > ?
+ORDER BY last_used_at DESC
+"#;
+// language=SQL
+const STMT_REVOKE_BY_USER: &str = r#"
+UPDATE user_sessions SET revoked = 1 WHERE user_id = ? AND revoked = 0 AND expires_at > ?
+"#;
+
+fn timestamp(stmt: &Statement, column: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::from_timestamp(stmt.read::<i64, _>(column)?, 0).unwrap_or_default())
+}
+
+fn map(stmt: &Statement) -> Result<UserSession> {
+    Ok(UserSession::new(
+        &stmt.read::<String, _>("id")?,
+        &stmt.read::<String, _>("user_id")?,
+        stmt.read::<Option<String>, _>("user_agent")?,
+        stmt.read::<Option<String>, _>("ip")?,
+        timestamp(stmt, "created_at")?,
+        timestamp(stmt, "last_used_at")?,
+        timestamp(stmt, "expires_at")?,
+        stmt.read::<i64, _>("revoked")? != 0,
+    ))
+}
+
+impl UserSessionRepository for UserSessionRepositorySQLite {
+    fn save(&self, session: &UserSession) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_INSERT)?;
+
+        stmt.bind((1, session.get_id()))?;
+        stmt.bind((2, session.get_user_id()))?;
+        stmt.bind((3, session.get_user_agent()))?;
+        stmt.bind((4, session.get_ip()))?;
+        stmt.bind((5, session.get_created_at().timestamp()))?;
+        stmt.bind((6, session.get_last_used_at().timestamp()))?;
+        stmt.bind((7, session.get_expires_at().timestamp()))?;
+        stmt.bind((8, i64::from(session.is_revoked())))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn update(&self, session: &UserSession) -> Result<()> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_UPDATE)?;
+
+        stmt.bind((1, session.get_user_agent()))?;
+        stmt.bind((2, session.get_ip()))?;
+        stmt.bind((3, session.get_last_used_at().timestamp()))?;
+        stmt.bind((4, session.get_expires_at().timestamp()))?;
+        stmt.bind((5, i64::from(session.is_revoked())))?;
+        stmt.bind((6, session.get_id()))?;
+
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    fn find_by(&self, id: &str) -> Result<Option<UserSession>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_BY)?;
+
+        stmt.bind((1, id))?;
+
+        if let State::Done = stmt.next()? {
+            return Ok(None);
+        }
+
+        Ok(Some(map(&stmt)?))
+    }
+
+    fn find_active_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<Vec<UserSession>> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_FIND_ACTIVE_BY_USER)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, now.timestamp()))?;
+
+        let mut sessions = vec![];
+
+        while let State::Row = stmt.next()? {
+            sessions.push(map(&stmt)?);
+        }
+
+        Ok(sessions)
+    }
+
+    fn revoke_by_user(&self, user_id: &str, now: DateTime<Utc>) -> Result<usize> {
+        let conn = sqlite::Connection::open(DATABASE_FILE)?;
+
+        let mut stmt = conn.prepare(STMT_REVOKE_BY_USER)?;
+
+        stmt.bind((1, user_id))?;
+        stmt.bind((2, now.timestamp()))?;
+
+        stmt.next()?;
+
+        Ok(conn.change_count())
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use password_hash::rand_core::{OsRng, RngCore};
+use shaku::Component;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::users::domain::users::totp::{Totp, TotpErrors};
+
+/// Random bytes of the secrets, the length recommended by RFC 4226.
+const SECRET_LENGTH: usize = 20;
+/// Digits of the codes, the ones authenticator apps expect.
+const DIGITS: usize = 6;
+/// Seconds each code is valid for.
+const STEP: u64 = 30;
+
+/// Codes generated with HMAC-SHA1 every 30 seconds, the defaults every authenticator app supports.
+#[derive(Component)]
+#[shaku(interface = Totp)]
+pub struct TotpRfc6238 {
+    issuer: String,
+}
+
+impl TotpRfc6238 {
+    fn totp(&self, secret: &str, account: &str) -> Result<TOTP, TotpErrors> {
+        let bytes = Secret::Encoded(secret.to_owned())
+            .to_bytes()
+            .map_err(|error| TotpErrors::InvalidSecret {
+                source: error.into(),
+            })?;
+
+        // Drift is tolerated by checking the steps around the current one instead.
+        TOTP::new(
+            Algorithm::SHA1,
+            DIGITS,
+            0,
+            STEP,
+            bytes,
+            Some(self.issuer.clone()),
+            account.to_owned(),
+        )
+        .map_err(|error| TotpErrors::InvalidSecret {
+            source: error.into(),
+        })
+    }
+}
+
+impl Totp for TotpRfc6238 {
+    fn generate_secret(&self) -> String {
+        let mut bytes = vec![0u8; SECRET_LENGTH];
+        OsRng.fill_bytes(&mut bytes);
+
+        Secret::Raw(bytes).to_encoded().to_string()
+    }
+
+    fn provisioning_uri(&self, secret: &str, account: &str) -> Result<String, TotpErrors> {
+        Ok(self.totp(secret, account)?.get_url())
+    }
+
+    fn verify(
+        &self,
+        secret: &str,
+        code: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<u64>, TotpErrors> {
+        let totp = self.totp(secret, "")?;
+        let current = u64::try_from(at.timestamp()).unwrap_or_default() / STEP;
+
+        Ok([current.saturating_sub(1), current, current + 1]
+            .into_iter()
+            .find(|step| totp.check(code.trim(), step * STEP)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::users::domain::users::two_factor::TwoFactor;
+
+    fn totp() -> TotpRfc6238 {
+        TotpRfc6238 {
+            issuer: "Example".to_owned(),
+        }
+    }
+
+    fn code(secret: &str, at: DateTime<Utc>) -> String {
+        totp()
+            .totp(secret, "")
+            .unwrap()
+            .generate(u64::try_from(at.timestamp()).unwrap())
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 15).unwrap()
+    }
+
+    #[test]
+    fn generates_base32_secrets_of_160_bits() {
+        let secret = totp().generate_secret();
+
+        assert_eq!(secret.len(), 32);
+        assert_ne!(secret, totp().generate_secret());
+    }
+
+    #[test]
+    fn provisioning_uris_name_the_issuer_and_account() {
+        let uri = totp()
+            .provisioning_uri(&totp().generate_secret(), "john@example.com")
+            .unwrap();
+
+        assert!(uri.starts_with("otpauth://totp/Example:john%40example.com?"));
+        assert!(uri.contains("issuer=Example"));
+    }
+
+    #[test]
+    fn verifies_codes_of_the_current_and_adjacent_steps() {
+        let secret = totp().generate_secret();
+        let step = u64::try_from(now().timestamp()).unwrap() / STEP;
+
+        for (offset, expected) in [(-30, step - 1), (0, step), (30, step + 1)] {
+            let code = code(&secret, now() + Duration::seconds(offset));
+
+            assert_eq!(
+                totp().verify(&secret, &code, now()).unwrap(),
+                Some(expected)
+            );
+        }
+
+        let late = code(&secret, now() - Duration::seconds(90));
+        assert_eq!(totp().verify(&secret, &late, now()).unwrap(), None);
+        assert_eq!(totp().verify(&secret, "000000x", now()).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_secrets_that_are_not_base32() {
+        assert!(totp().verify("not base32!", "123456", now()).is_err());
+    }
+
+    #[test]
+    fn codes_of_a_used_step_are_not_accepted_again() {
+        let secret = totp().generate_secret();
+        let code = code(&secret, now());
+        let step = totp().verify(&secret, &code, now()).unwrap().unwrap();
+
+        let two_factor = TwoFactor::enroll("user", &secret).used_step(step);
+
+        // Replaying the same code, or an earlier one still in the drift window, is refused.
+        assert!(!two_factor.accepts_step(step));
+        assert!(!two_factor.accepts_step(step - 1));
+        assert!(two_factor.accepts_step(step + 1));
+    }
+}